@@ -0,0 +1,35 @@
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion};
+use jupiter_amm_interface::{
+    bench_utils::{quote_params_fixtures, quote_throughput},
+    reference::{ConstantProductAmm, TokenSwapFixture},
+    Amm, SwapMode,
+};
+
+const AMOUNTS: [u64; 4] = [1, 1_000, 1_000_000, 1_000_000_000];
+
+/// A Token-Swap pool with 1_000 A and 2_000 B of 6 decimals and 0.3% fees
+fn constant_product_amm() -> ConstantProductAmm {
+    TokenSwapFixture::new(1_000_000_000, 2_000_000_000)
+        .amm()
+        .unwrap()
+}
+
+fn bench_quote(c: &mut Criterion) {
+    let amm = constant_product_amm();
+    for swap_mode in [SwapMode::ExactIn, SwapMode::ExactOut] {
+        let fixtures = quote_params_fixtures(&amm, &AMOUNTS, swap_mode);
+        c.bench_function(&format!("constant_product_quote_{swap_mode:?}"), |b| {
+            b.iter(|| quote_throughput(&amm, &fixtures))
+        });
+    }
+}
+
+fn bench_clone_amm(c: &mut Criterion) {
+    let amm = constant_product_amm().clone_amm();
+    c.bench_function("constant_product_clone_amm", |b| {
+        b.iter_batched(|| (), |_| amm.clone(), BatchSize::SmallInput)
+    });
+}
+
+criterion_group!(benches, bench_quote, bench_clone_amm);
+criterion_main!(benches);
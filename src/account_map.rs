@@ -0,0 +1,308 @@
+use std::hash::{BuildHasher, Hash, Hasher};
+
+use anyhow::{ensure, Context, Result};
+use borsh::BorshDeserialize;
+use solana_sdk::{
+    account::Account,
+    hash::hash,
+    program_pack::{IsInitialized, Pack},
+    pubkey::Pubkey,
+    rent::Rent,
+};
+
+use crate::{try_get_account, try_get_account_data_and_owner, AccountMap};
+
+/// An Anchor account, identified by the first 8 bytes of its data
+pub trait AnchorAccount: BorshDeserialize {
+    const DISCRIMINATOR: [u8; 8];
+}
+
+/// The discriminator Anchor derives for an account named `account_name`
+pub fn anchor_account_discriminator(account_name: &str) -> [u8; 8] {
+    let mut discriminator = [0u8; 8];
+    discriminator
+        .copy_from_slice(&hash(format!("account:{account_name}").as_bytes()).to_bytes()[..8]);
+    discriminator
+}
+
+fn try_get_owned_account_data<'a>(
+    account_map: &'a AccountMap,
+    address: &Pubkey,
+    expected_owner: &Pubkey,
+) -> Result<&'a [u8]> {
+    let (data, owner) = try_get_account_data_and_owner(account_map, address)?;
+    ensure!(
+        owner == expected_owner,
+        "Account {address} is owned by {owner}, expected {expected_owner}"
+    );
+    Ok(data)
+}
+
+/// Deserializes an Anchor account after checking its owner and discriminator
+pub fn try_deserialize_anchor_account<T: AnchorAccount>(
+    account_map: &AccountMap,
+    address: &Pubkey,
+    expected_owner: &Pubkey,
+) -> Result<T> {
+    let data = try_get_owned_account_data(account_map, address, expected_owner)?;
+    let mut data = data
+        .strip_prefix(&T::DISCRIMINATOR)
+        .with_context(|| format!("Account {address} has an invalid discriminator"))?;
+    T::deserialize(&mut data).with_context(|| format!("Failed to deserialize account {address}"))
+}
+
+/// Unpacks an initialized account, e.g. an SPL token account or mint, after checking its owner
+pub fn try_deserialize_packed<T: Pack + IsInitialized>(
+    account_map: &AccountMap,
+    address: &Pubkey,
+    expected_owner: &Pubkey,
+) -> Result<T> {
+    let data = try_get_owned_account_data(account_map, address, expected_owner)?;
+    T::unpack(data).with_context(|| format!("Failed to unpack account {address}"))
+}
+
+pub fn try_get_lamports(account_map: &AccountMap, address: &Pubkey) -> Result<u64> {
+    try_get_account(account_map, address).map(|account| account.lamports)
+}
+
+pub fn is_rent_exempt(account: &Account, rent: &Rent) -> bool {
+    rent.is_exempt(account.lamports, account.data.len())
+}
+
+/// The lamports on top of the rent exempt minimum, e.g. the SOL reserve of a bonding curve account
+pub fn lamports_above_rent(account: &Account, rent: &Rent) -> u64 {
+    account
+        .lamports
+        .saturating_sub(rent.minimum_balance(account.data.len()))
+}
+
+/// Checks that `address` holds at least `min_lamports`, returning its lamports
+pub fn assert_min_lamports(
+    account_map: &AccountMap,
+    address: &Pubkey,
+    min_lamports: u64,
+) -> Result<u64> {
+    let lamports = try_get_lamports(account_map, address)?;
+    ensure!(
+        lamports >= min_lamports,
+        "Account {address} holds {lamports} lamports, expected at least {min_lamports}"
+    );
+    Ok(lamports)
+}
+
+/// Fixed seeds so hashes are comparable across update cycles of the process
+fn account_hasher() -> ahash::AHasher {
+    ahash::RandomState::with_seeds(
+        0x6a75_7069_7465_7231,
+        0x6163_636f_756e_7473,
+        0x6861_7368_5f76_3031,
+        0x6469_6765_7374_3031,
+    )
+    .build_hasher()
+}
+
+/// A fast hash of the lamports, owner and data of `account`, not stable across crate versions
+pub fn account_hash(account: &Account) -> u64 {
+    let mut hasher = account_hasher();
+    account.lamports.hash(&mut hasher);
+    account.owner.hash(&mut hasher);
+    account.data.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Change detection over a whole [`AccountMap`]
+pub trait AccountMapDigest {
+    /// A digest of every key and account, independent of the iteration order of the map
+    fn content_digest(&self) -> u64;
+}
+
+impl AccountMapDigest for AccountMap {
+    fn content_digest(&self) -> u64 {
+        self.iter()
+            .fold(self.len() as u64, |digest, (address, account)| {
+                let mut hasher = account_hasher();
+                address.hash(&mut hasher);
+                account_hash(account).hash(&mut hasher);
+                digest.wrapping_add(hasher.finish())
+            })
+    }
+}
+
+/// The keys of the accounts that changed between two account maps, e.g. consecutive geyser snapshots
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct AccountMapDiff {
+    pub added: Vec<Pubkey>,
+    pub removed: Vec<Pubkey>,
+    /// Accounts present in both maps whose data, lamports or owner differ
+    pub changed: Vec<Pubkey>,
+}
+
+impl AccountMapDiff {
+    pub fn compute(old: &AccountMap, new: &AccountMap) -> Self {
+        let mut diff = AccountMapDiff::default();
+        for (address, new_account) in new {
+            match old.get(address) {
+                None => diff.added.push(*address),
+                Some(old_account) => {
+                    if old_account.lamports != new_account.lamports
+                        || old_account.owner != new_account.owner
+                        || old_account.data != new_account.data
+                    {
+                        diff.changed.push(*address);
+                    }
+                }
+            }
+        }
+        diff.removed = old
+            .keys()
+            .filter(|address| !new.contains_key(address))
+            .copied()
+            .collect();
+        diff.added.sort();
+        diff.removed.sort();
+        diff.changed.sort();
+        diff
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+
+    /// Indicates if any of `addresses` was added, removed or changed
+    pub fn affects(&self, addresses: &[Pubkey]) -> bool {
+        [&self.added, &self.removed, &self.changed]
+            .into_iter()
+            .any(|keys| {
+                addresses
+                    .iter()
+                    .any(|address| keys.binary_search(address).is_ok())
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use borsh::BorshSerialize;
+
+    #[derive(BorshSerialize, BorshDeserialize, Debug, PartialEq)]
+    struct Pool {
+        reserve: u64,
+    }
+
+    impl AnchorAccount for Pool {
+        const DISCRIMINATOR: [u8; 8] = [241, 154, 109, 4, 17, 177, 109, 188];
+    }
+
+    #[test]
+    fn test_try_deserialize_anchor_account() {
+        assert_eq!(anchor_account_discriminator("Pool"), Pool::DISCRIMINATOR);
+
+        let owner = Pubkey::new_unique();
+        let address = Pubkey::new_unique();
+        let mut data = Pool::DISCRIMINATOR.to_vec();
+        data.extend(Pool { reserve: 42 }.try_to_vec().unwrap());
+        let mut account_map = AccountMap::default();
+        account_map.insert(
+            address,
+            Account {
+                data,
+                owner,
+                ..Account::default()
+            },
+        );
+
+        assert_eq!(
+            try_deserialize_anchor_account::<Pool>(&account_map, &address, &owner).unwrap(),
+            Pool { reserve: 42 }
+        );
+        assert!(try_deserialize_anchor_account::<Pool>(
+            &account_map,
+            &address,
+            &Pubkey::new_unique()
+        )
+        .is_err());
+
+        account_map.get_mut(&address).unwrap().data[0] ^= 1;
+        assert!(try_deserialize_anchor_account::<Pool>(&account_map, &address, &owner).is_err());
+    }
+
+    #[test]
+    fn test_lamports_helpers() {
+        let rent = Rent::default();
+        let address = Pubkey::new_unique();
+        let rent_exempt_minimum = rent.minimum_balance(165);
+        let account_map = AccountMap::from_iter([(
+            address,
+            Account {
+                lamports: rent_exempt_minimum + 1_000,
+                data: vec![0; 165],
+                ..Account::default()
+            },
+        )]);
+        let account = try_get_account(&account_map, &address).unwrap();
+
+        assert!(is_rent_exempt(account, &rent));
+        assert_eq!(lamports_above_rent(account, &rent), 1_000);
+        assert_eq!(
+            assert_min_lamports(&account_map, &address, rent_exempt_minimum).unwrap(),
+            rent_exempt_minimum + 1_000
+        );
+        assert!(assert_min_lamports(&account_map, &address, rent_exempt_minimum + 1_001).is_err());
+        assert!(try_get_lamports(&account_map, &Pubkey::new_unique()).is_err());
+    }
+
+    #[test]
+    fn test_account_map_diff() {
+        let (unchanged, changed, removed, added) = (
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+        );
+        let account = |data: Vec<u8>| Account {
+            data,
+            ..Account::default()
+        };
+        let old = AccountMap::from_iter([
+            (unchanged, account(vec![1])),
+            (changed, account(vec![1])),
+            (removed, account(vec![1])),
+        ]);
+        let new = AccountMap::from_iter([
+            (unchanged, account(vec![1])),
+            (changed, account(vec![2])),
+            (added, account(vec![1])),
+        ]);
+
+        let diff = AccountMapDiff::compute(&old, &new);
+        assert_eq!(diff.added, vec![added]);
+        assert_eq!(diff.removed, vec![removed]);
+        assert_eq!(diff.changed, vec![changed]);
+        assert!(!diff.affects(&[unchanged]));
+        assert!(diff.affects(&[unchanged, changed]));
+        assert!(AccountMapDiff::compute(&new, &new).is_empty());
+    }
+
+    #[test]
+    fn test_content_digest() {
+        let (address_a, address_b) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let account = |lamports: u64| Account {
+            lamports,
+            data: vec![1, 2, 3],
+            ..Account::default()
+        };
+        let account_map = AccountMap::from_iter([(address_a, account(1)), (address_b, account(2))]);
+        let reordered = AccountMap::from_iter([(address_b, account(2)), (address_a, account(1))]);
+        assert_eq!(account_map.content_digest(), reordered.content_digest());
+        assert_eq!(account_hash(&account(1)), account_hash(&account(1)));
+        assert_ne!(account_hash(&account(1)), account_hash(&account(2)));
+
+        let swapped = AccountMap::from_iter([(address_a, account(2)), (address_b, account(1))]);
+        assert_ne!(account_map.content_digest(), swapped.content_digest());
+        assert_ne!(
+            account_map.content_digest(),
+            AccountMap::default().content_digest()
+        );
+    }
+}
@@ -0,0 +1,32 @@
+use std::borrow::Cow;
+
+use serde::Serialize;
+
+/// The semantic role of a swap account meta, see `Amm::get_swap_account_roles`
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "snake_case")]
+#[non_exhaustive]
+pub enum AccountRole {
+    /// The DEX program
+    Program,
+    TokenProgram,
+    /// The pool state or market account
+    Pool,
+    /// The PDA signing for the pool vaults
+    PoolAuthority,
+    /// The vault receiving the in amount
+    VaultIn,
+    /// The vault paying out the out amount
+    VaultOut,
+    Oracle,
+    /// The account receiving protocol fees
+    FeeAccount,
+    /// The token transfer authority of the user
+    UserAuthority,
+    UserSource,
+    UserDestination,
+    /// The jupiter program id standing in for an optional account, see `SwapParams::placeholder_account_meta`
+    Placeholder,
+    /// A role specific to the DEX
+    Other(Cow<'static, str>),
+}
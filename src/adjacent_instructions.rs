@@ -0,0 +1,14 @@
+use solana_sdk::instruction::Instruction;
+
+/// Instructions a swap leg needs right before and after the route instruction, e.g. sync_native or cranks
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct AdjacentInstructions {
+    pub pre: Vec<Instruction>,
+    pub post: Vec<Instruction>,
+}
+
+impl AdjacentInstructions {
+    pub fn is_empty(&self) -> bool {
+        self.pre.is_empty() && self.post.is_empty()
+    }
+}
@@ -0,0 +1,171 @@
+//! Sharing the immutable parts of adapters across `clone_amm` clones
+//!
+//! Hosts clone every Amm for each routing snapshot, adapters should keep large precomputed tables in a
+//! [`SharedStatic`] and their updated state in a [`PerMarketState`] so clones only bump reference counts
+use std::{ops::Deref, sync::Arc};
+
+use anyhow::Result;
+use solana_sdk::pubkey::Pubkey;
+
+use crate::{
+    forward::forward_amm_methods, AccountMap, AccountMapDiff, Amm, AmmContext, KeyedAccount,
+    MissingAccountsAction,
+};
+
+/// Data never changing after `from_keyed_account`, e.g. curve tables, shared by every clone
+#[derive(Debug, Default)]
+pub struct SharedStatic<T>(Arc<T>);
+
+impl<T> SharedStatic<T> {
+    pub fn new(value: T) -> Self {
+        SharedStatic(Arc::new(value))
+    }
+}
+
+impl<T> Clone for SharedStatic<T> {
+    fn clone(&self) -> Self {
+        SharedStatic(Arc::clone(&self.0))
+    }
+}
+
+impl<T> Deref for SharedStatic<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+/// State changed by `update`, shared by clones until one of them is updated and copies it
+#[derive(Debug, Default)]
+pub struct PerMarketState<T>(Arc<T>);
+
+impl<T: Clone> PerMarketState<T> {
+    pub fn new(value: T) -> Self {
+        PerMarketState(Arc::new(value))
+    }
+
+    /// Copies the state first if other clones still share it
+    pub fn get_mut(&mut self) -> &mut T {
+        Arc::make_mut(&mut self.0)
+    }
+}
+
+impl<T> Clone for PerMarketState<T> {
+    fn clone(&self) -> Self {
+        PerMarketState(Arc::clone(&self.0))
+    }
+}
+
+impl<T> Deref for PerMarketState<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+/// Wraps a whole Amm in an `Arc`, `clone_amm` only bumps the reference count and updates copy on write
+///
+/// Suited to Amms updated much less often than they are cloned
+#[derive(Debug)]
+pub struct ArcAmm<A> {
+    inner: Arc<A>,
+}
+
+impl<A> ArcAmm<A> {
+    pub fn new(amm: A) -> Self {
+        ArcAmm {
+            inner: Arc::new(amm),
+        }
+    }
+}
+
+impl<A> Clone for ArcAmm<A> {
+    fn clone(&self) -> Self {
+        ArcAmm {
+            inner: Arc::clone(&self.inner),
+        }
+    }
+}
+
+impl<A: Amm + Clone + Send + Sync + 'static> ArcAmm<A> {
+    fn inner_mut(&mut self) -> &mut A {
+        Arc::make_mut(&mut self.inner)
+    }
+}
+
+impl<A: Amm + Clone + Send + Sync + 'static> Amm for ArcAmm<A> {
+    fn from_keyed_account(keyed_account: &KeyedAccount, amm_context: &AmmContext) -> Result<Self> {
+        A::from_keyed_account(keyed_account, amm_context).map(ArcAmm::new)
+    }
+
+    forward_amm_methods!(
+        inner =>
+        label, dex_id, program_id, key, get_reserve_mints, get_accounts_to_update,
+        get_accounts_to_update_with_priority, get_static_accounts, get_dynamic_accounts,
+        predict_accounts_for_amount_range, plan_update, update_shared, quote, quote_into,
+        quote_with_current_token_balance, min_in_amount, min_out_amount, max_in_amount,
+        max_out_amount, quote_cache_key, last_update_slot, state_version, snapshot_state,
+        quoting_blackout, quote_depends_on_clock, max_quote_age_slots, quote_rate_limit,
+        routing_hints, settlement_constraints, execution_constraints, get_swap_and_account_metas,
+        get_adjacent_instructions, get_swap_and_account_metas_v2, writable_accounts,
+        get_swap_account_roles, quote_liquidity, get_liquidity_ix_account_metas,
+        get_swap_and_account_metas_candidates, get_user_token_accounts, tracing_fields,
+        get_pool_metadata, fee_schedule, current_fee_bps, fee_mint_for, accepts_native_sol,
+        handles_token2022_fees, get_oracle_requirements, has_dynamic_accounts,
+        requires_update_for_reserve_mints, supports_exact_out, get_user_setup, get_user_setup_v2,
+        clone_amm, interface_version, unidirectional, capabilities, inner_program_ids,
+        get_program_dependencies, get_accounts_len, underlying_liquidities,
+        get_tvl_in_reserve_terms, get_cumulative_volume, created_at, is_active, activity_status,
+        update_failure_policy, successor_market,
+    );
+
+    fn update(&mut self, account_map: &AccountMap) -> Result<()> {
+        self.inner_mut().update(account_map)
+    }
+
+    fn update_with_diff(&mut self, account_map: &AccountMap, diff: &AccountMapDiff) -> Result<()> {
+        if !diff.affects(&self.inner.get_accounts_to_update()) {
+            return Ok(());
+        }
+        self.inner_mut().update_with_diff(account_map, diff)
+    }
+
+    fn apply_update(&mut self, fetched: &AccountMap, plan_id: u64) -> Result<()> {
+        self.inner_mut().apply_update(fetched, plan_id)
+    }
+
+    fn restore_state(&mut self, bytes: &[u8]) -> Result<()> {
+        self.inner_mut().restore_state(bytes)
+    }
+
+    fn on_accounts_missing(&mut self, missing: &[Pubkey]) -> MissingAccountsAction {
+        self.inner_mut().on_accounts_missing(missing)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::reference::MockAmm;
+
+    #[test]
+    fn test_arc_amm_copies_on_update() {
+        let mint = Pubkey::new_unique();
+        let amm = ArcAmm::new(MockAmm::new(Pubkey::new_unique(), vec![mint]));
+        let mut updated_amm = amm.clone();
+        assert!(Arc::ptr_eq(&amm.inner, &updated_amm.inner));
+
+        updated_amm.update(&AccountMap::default()).unwrap();
+        assert!(!Arc::ptr_eq(&amm.inner, &updated_amm.inner));
+        assert_eq!(updated_amm.get_reserve_mints(), vec![mint]);
+
+        let table = SharedStatic::new(vec![0u64; 1_000]);
+        assert!(Arc::ptr_eq(&table.clone().0, &table.0));
+        let mut state = PerMarketState::new(1u64);
+        let shared_state = state.clone();
+        *state.get_mut() += 1;
+        assert_eq!((*state, *shared_state), (2, 1));
+    }
+}
@@ -0,0 +1,58 @@
+use std::time::{Duration, Instant};
+
+use crate::{Amm, Quote, QuoteParams, SwapMode};
+
+/// The outcome of quoting every fixture once
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct QuoteThroughput {
+    pub quotes: usize,
+    pub errors: usize,
+    pub elapsed: Duration,
+}
+
+impl QuoteThroughput {
+    pub fn quotes_per_second(&self) -> f64 {
+        self.quotes as f64 / self.elapsed.as_secs_f64().max(f64::EPSILON)
+    }
+}
+
+/// Quotes every fixture once, errors are counted and not propagated as some amounts are expected to fail
+pub fn quote_throughput<A: Amm + ?Sized>(amm: &A, fixtures: &[QuoteParams]) -> QuoteThroughput {
+    let mut quote = Quote::default();
+    let start = Instant::now();
+    let errors = fixtures
+        .iter()
+        .filter(|quote_params| {
+            std::hint::black_box(amm.quote_into(quote_params, &mut quote)).is_err()
+        })
+        .count();
+
+    QuoteThroughput {
+        quotes: fixtures.len(),
+        errors,
+        elapsed: start.elapsed(),
+    }
+}
+
+/// Quote params for every ordered pair of reserve mints and every amount, in the given swap mode
+pub fn quote_params_fixtures<A: Amm + ?Sized>(
+    amm: &A,
+    amounts: &[u64],
+    swap_mode: SwapMode,
+) -> Vec<QuoteParams> {
+    let reserve_mints = amm.get_reserve_mints();
+    let mut fixtures = vec![];
+    for input_mint in &reserve_mints {
+        for output_mint in reserve_mints.iter().filter(|mint| *mint != input_mint) {
+            fixtures.extend(amounts.iter().map(|amount| QuoteParams {
+                amount: *amount,
+                input_mint: *input_mint,
+                output_mint: *output_mint,
+                swap_mode,
+                budget: None,
+                limit_price: None,
+            }));
+        }
+    }
+    fixtures
+}
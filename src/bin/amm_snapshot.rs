@@ -0,0 +1,159 @@
+//! Captures an `AmmSnapshot` fixture from an RPC and prints a regression test skeleton using it
+//!
+//! The accounts to update of the Amm are passed with `--account`, since only the adapter crate can build the Amm
+//!
+//! Usage: amm-snapshot --rpc-url <URL> --pool <ADDRESS> [--account <ADDRESS>]... [--params <JSON>] [--output <PATH>]
+
+use std::{fs, str::FromStr};
+
+use anyhow::{anyhow, bail, Context, Result};
+use jupiter_amm_interface::{AmmSnapshot, KeyedAccount, KeyedUiAccount};
+use serde_json::{json, Value};
+use solana_account_decoder::{UiAccount, UiAccountEncoding};
+use solana_sdk::{account::Account, pubkey::Pubkey};
+
+struct Args {
+    rpc_url: String,
+    pool: Pubkey,
+    accounts: Vec<Pubkey>,
+    params: Option<Value>,
+    output: String,
+}
+
+fn parse_args() -> Result<Args> {
+    let mut rpc_url = None;
+    let mut pool = None;
+    let mut accounts = vec![];
+    let mut params = None;
+    let mut output = None;
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let mut value = || {
+            args.next()
+                .with_context(|| format!("Missing value for {arg}"))
+        };
+        match arg.as_str() {
+            "--rpc-url" => rpc_url = Some(value()?),
+            "--pool" => pool = Some(Pubkey::from_str(&value()?)?),
+            "--account" => accounts.push(Pubkey::from_str(&value()?)?),
+            "--params" => params = Some(serde_json::from_str(&value()?)?),
+            "--output" => output = Some(value()?),
+            _ => bail!("Unexpected argument {arg}"),
+        }
+    }
+
+    let pool = pool.context("Missing --pool")?;
+    Ok(Args {
+        rpc_url: rpc_url.context("Missing --rpc-url")?,
+        pool,
+        accounts,
+        params,
+        output: output.unwrap_or_else(|| format!("{pool}.json")),
+    })
+}
+
+fn get_multiple_accounts(rpc_url: &str, addresses: &[Pubkey]) -> Result<Vec<Option<Account>>> {
+    let addresses = addresses
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>();
+    let response: Value = ureq::post(rpc_url)
+        .send_json(json!({
+            "jsonrpc": "2.0",
+            "id": 1,
+            "method": "getMultipleAccounts",
+            "params": [addresses, { "encoding": "base64" }],
+        }))?
+        .into_json()?;
+    if let Some(error) = response.get("error") {
+        bail!("RPC error: {error}");
+    }
+
+    let ui_accounts: Vec<Option<UiAccount>> =
+        serde_json::from_value(response["result"]["value"].clone())?;
+    ui_accounts
+        .into_iter()
+        .map(|ui_account| {
+            ui_account
+                .map(|ui_account| {
+                    ui_account
+                        .decode()
+                        .ok_or_else(|| anyhow!("Failed to decode account"))
+                })
+                .transpose()
+        })
+        .collect()
+}
+
+fn main() -> Result<()> {
+    let args = parse_args()?;
+
+    let mut addresses = vec![args.pool];
+    addresses.extend(&args.accounts);
+    let mut accounts = get_multiple_accounts(&args.rpc_url, &addresses)?.into_iter();
+    let pool_account = accounts
+        .next()
+        .flatten()
+        .with_context(|| format!("Pool {} not found", args.pool))?;
+
+    let snapshot = AmmSnapshot {
+        pool: KeyedUiAccount::from_keyed_account_with_encoding(
+            KeyedAccount {
+                key: args.pool,
+                account: pool_account,
+                params: args.params,
+            },
+            UiAccountEncoding::Base64Zstd,
+        ),
+        accounts: args
+            .accounts
+            .iter()
+            .zip(accounts)
+            .filter_map(|(key, account)| {
+                let Some(account) = account else {
+                    eprintln!("Account {key} not found, skipping");
+                    return None;
+                };
+                Some(KeyedUiAccount::from_keyed_account_with_encoding(
+                    KeyedAccount {
+                        key: *key,
+                        account,
+                        params: None,
+                    },
+                    UiAccountEncoding::Base64Zstd,
+                ))
+            })
+            .collect(),
+    };
+    fs::write(&args.output, serde_json::to_string_pretty(&snapshot)?)?;
+    eprintln!("Wrote snapshot to {}", args.output);
+
+    println!(
+        r#"#[test]
+fn test_quote_{pool}() {{
+    let snapshot: AmmSnapshot =
+        serde_json::from_str(include_str!("{output}")).unwrap();
+    let amm_context = AmmContext::new(ClockRef::default());
+    // Replace with the Amm of the adapter
+    let amm: MyAmm = snapshot.load_amm(&amm_context).unwrap();
+
+    let reserve_mints = amm.get_reserve_mints();
+    let quote = amm
+        .quote(&QuoteParams {{
+            amount: 1_000_000,
+            input_mint: reserve_mints[0],
+            output_mint: reserve_mints[1],
+            swap_mode: SwapMode::ExactIn,
+            budget: None,
+            limit_price: None,
+        }})
+        .unwrap();
+    // Replace with the expected amounts
+    assert_eq!(quote.out_amount, 0);
+}}"#,
+        pool = args.pool.to_string().to_lowercase(),
+        output = args.output,
+    );
+    Ok(())
+}
@@ -0,0 +1,44 @@
+use solana_sdk::epoch_schedule::EpochSchedule;
+
+/// Slots during which the host should not route through an Amm, from `start_slot` to `end_slot` inclusive
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BlackoutWindow {
+    pub start_slot: u64,
+    pub end_slot: u64,
+}
+
+impl BlackoutWindow {
+    /// The last `slots_before` slots of `epoch` and the first `slots_after` slots of the next epoch
+    pub fn around_epoch_end(
+        epoch_schedule: &EpochSchedule,
+        epoch: u64,
+        slots_before: u64,
+        slots_after: u64,
+    ) -> Self {
+        let next_epoch_first_slot = epoch_schedule.get_last_slot_in_epoch(epoch) + 1;
+        BlackoutWindow {
+            start_slot: next_epoch_first_slot.saturating_sub(slots_before),
+            end_slot: (next_epoch_first_slot + slots_after).saturating_sub(1),
+        }
+    }
+
+    pub fn contains(&self, slot: u64) -> bool {
+        (self.start_slot..=self.end_slot).contains(&slot)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_around_epoch_end() {
+        let epoch_schedule = EpochSchedule::without_warmup();
+        let blackout_window = BlackoutWindow::around_epoch_end(&epoch_schedule, 600, 100, 10);
+        let next_epoch_first_slot = epoch_schedule.get_first_slot_in_epoch(601);
+        assert!(!blackout_window.contains(next_epoch_first_slot - 101));
+        assert!(blackout_window.contains(next_epoch_first_slot - 100));
+        assert!(blackout_window.contains(next_epoch_first_slot + 9));
+        assert!(!blackout_window.contains(next_epoch_first_slot + 10));
+    }
+}
@@ -0,0 +1,26 @@
+use bitflags::bitflags;
+
+bitflags! {
+    /// What an Amm supports and requires, for hosts to filter Amms on a cached u32
+    #[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+    pub struct AmmCapabilities: u32 {
+        /// Supports `SwapMode::ExactOut`
+        const EXACT_OUT = 1 << 0;
+        /// The accounts to update can change with the state
+        const DYNAMIC_ACCOUNTS = 1 << 1;
+        /// Only trades from its first mint to its second mint
+        const UNIDIRECTIONAL = 1 << 2;
+        /// Supports Token-2022 mints
+        const TOKEN_2022 = 1 << 3;
+        /// Swapping requires a user setup step, e.g. open orders
+        const NEEDS_USER_SETUP = 1 << 4;
+        /// Quotes change with the clock alone
+        const CLOCK_SENSITIVE = 1 << 5;
+        /// Quotes come from a market maker with last look
+        const RFQ = 1 << 6;
+        /// Supports `Amm::update_shared`, updating through a shared reference
+        const SHARED_UPDATE = 1 << 7;
+        /// Fills up to `QuoteParams::limit_price`
+        const LIMIT_PRICE = 1 << 8;
+    }
+}
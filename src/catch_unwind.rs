@@ -0,0 +1,309 @@
+use std::{
+    any::Any,
+    panic::{catch_unwind, AssertUnwindSafe},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+};
+
+use anyhow::{anyhow, Error, Result};
+use solana_sdk::pubkey::Pubkey;
+
+use crate::{
+    forward::forward_amm_methods, AccountMap, AccountMapDiff, ActivityStatus, AdjacentInstructions,
+    Amm, AmmContext, KeyedAccount, LiquidityParams, LiquidityQuote, Quote, QuoteError, QuoteParams,
+    RequiredTokenAccount, SwapAndAccountMetas, SwapContext, SwapLegParams, SwapParams,
+    TokenBalanceSource,
+};
+
+/// Wraps an untrusted Amm, turning panics in its fallible methods into errors
+///
+/// The Amm deactivates itself after `max_panics` panics, clones share the panic counter
+/// The infallible methods, e.g. `get_reserve_mints` or `get_accounts_len`, are forwarded unguarded,
+/// only `label` and `key` are read once in `new` so that panic errors can name the Amm
+#[derive(Clone)]
+pub struct CatchUnwindAmm {
+    inner: Box<dyn Amm + Send + Sync>,
+    label: String,
+    key: Pubkey,
+    panics: Arc<AtomicUsize>,
+    max_panics: usize,
+}
+
+fn panic_message(payload: &(dyn Any + Send)) -> &str {
+    payload
+        .downcast_ref::<&str>()
+        .copied()
+        .or_else(|| payload.downcast_ref::<String>().map(String::as_str))
+        .unwrap_or("unknown panic")
+}
+
+impl CatchUnwindAmm {
+    pub fn new(inner: Box<dyn Amm + Send + Sync>, max_panics: usize) -> Self {
+        CatchUnwindAmm {
+            label: inner.label(),
+            key: inner.key(),
+            inner,
+            panics: Arc::default(),
+            max_panics,
+        }
+    }
+
+    pub fn panics(&self) -> usize {
+        self.panics.load(Ordering::Relaxed)
+    }
+
+    fn is_deactivated(&self) -> bool {
+        self.panics() >= self.max_panics
+    }
+
+    fn panicked(&self, method: &str, payload: Box<dyn Any + Send>) -> Error {
+        self.panics.fetch_add(1, Ordering::Relaxed);
+        anyhow!(
+            "{} {} panicked in {method}: {}",
+            self.label,
+            self.key,
+            panic_message(payload.as_ref())
+        )
+    }
+
+    fn catch_unwind<T>(&self, method: &str, f: impl FnOnce() -> Result<T>) -> Result<T> {
+        catch_unwind(AssertUnwindSafe(f))
+            .unwrap_or_else(|payload| Err(self.panicked(method, payload)))
+    }
+
+    fn catch_unwind_mut<T>(
+        &mut self,
+        method: &str,
+        f: impl FnOnce(&mut (dyn Amm + Send + Sync)) -> Result<T>,
+    ) -> Result<T> {
+        catch_unwind(AssertUnwindSafe(|| f(self.inner.as_mut())))
+            .unwrap_or_else(|payload| Err(self.panicked(method, payload)))
+    }
+
+    /// Quotes through `catch_unwind`, failing with `PoolPaused` once deactivated
+    fn catch_unwind_quote<T>(&self, method: &str, f: impl FnOnce() -> Result<T>) -> Result<T> {
+        if self.is_deactivated() {
+            return Err(QuoteError::PoolPaused.into());
+        }
+        self.catch_unwind(method, f)
+    }
+}
+
+impl Amm for CatchUnwindAmm {
+    fn from_keyed_account(
+        _keyed_account: &KeyedAccount,
+        _amm_context: &AmmContext,
+    ) -> Result<Self> {
+        Err(anyhow!("CatchUnwindAmm can only wrap an existing Amm"))
+    }
+
+    fn label(&self) -> String {
+        self.label.clone()
+    }
+
+    fn key(&self) -> Pubkey {
+        self.key
+    }
+
+    forward_amm_methods!(
+        inner =>
+        dex_id, program_id, get_reserve_mints, get_accounts_to_update,
+        get_accounts_to_update_with_priority, get_static_accounts, get_dynamic_accounts,
+        predict_accounts_for_amount_range, plan_update, min_in_amount, min_out_amount,
+        max_in_amount, max_out_amount, quote_cache_key, last_update_slot, state_version,
+        snapshot_state, quoting_blackout, quote_depends_on_clock, max_quote_age_slots,
+        quote_rate_limit, routing_hints, settlement_constraints, execution_constraints,
+        get_swap_account_roles, tracing_fields, get_pool_metadata, fee_schedule, current_fee_bps,
+        fee_mint_for, accepts_native_sol, handles_token2022_fees, get_oracle_requirements,
+        has_dynamic_accounts, requires_update_for_reserve_mints, supports_exact_out,
+        get_user_setup, get_user_setup_v2, clone_amm, interface_version, unidirectional,
+        capabilities, inner_program_ids, get_program_dependencies, get_accounts_len,
+        underlying_liquidities, get_tvl_in_reserve_terms, get_cumulative_volume, created_at,
+        update_failure_policy, on_accounts_missing, successor_market,
+    );
+
+    fn update(&mut self, account_map: &AccountMap) -> Result<()> {
+        self.catch_unwind_mut("update", |inner| inner.update(account_map))
+    }
+
+    fn update_with_diff(&mut self, account_map: &AccountMap, diff: &AccountMapDiff) -> Result<()> {
+        self.catch_unwind_mut("update_with_diff", |inner| {
+            inner.update_with_diff(account_map, diff)
+        })
+    }
+
+    fn apply_update(&mut self, fetched: &AccountMap, plan_id: u64) -> Result<()> {
+        self.catch_unwind_mut("apply_update", |inner| inner.apply_update(fetched, plan_id))
+    }
+
+    fn update_shared(&self, account_map: &AccountMap) -> Result<()> {
+        self.catch_unwind("update_shared", || self.inner.update_shared(account_map))
+    }
+
+    fn quote(&self, quote_params: &QuoteParams) -> Result<Quote> {
+        self.catch_unwind_quote("quote", || self.inner.quote(quote_params))
+    }
+
+    fn quote_into(&self, quote_params: &QuoteParams, quote: &mut Quote) -> Result<()> {
+        self.catch_unwind_quote("quote_into", || self.inner.quote_into(quote_params, quote))
+    }
+
+    fn quote_with_current_token_balance(
+        &self,
+        quote_params: &QuoteParams,
+        token_balances: &dyn TokenBalanceSource,
+    ) -> Result<Quote> {
+        self.catch_unwind_quote("quote_with_current_token_balance", || {
+            self.inner
+                .quote_with_current_token_balance(quote_params, token_balances)
+        })
+    }
+
+    fn restore_state(&mut self, bytes: &[u8]) -> Result<()> {
+        self.catch_unwind_mut("restore_state", |inner| inner.restore_state(bytes))
+    }
+
+    fn get_swap_and_account_metas(&self, swap_params: &SwapParams) -> Result<SwapAndAccountMetas> {
+        self.catch_unwind("get_swap_and_account_metas", || {
+            self.inner.get_swap_and_account_metas(swap_params)
+        })
+    }
+
+    fn get_adjacent_instructions(&self, swap_params: &SwapParams) -> Result<AdjacentInstructions> {
+        self.catch_unwind("get_adjacent_instructions", || {
+            self.inner.get_adjacent_instructions(swap_params)
+        })
+    }
+
+    fn get_swap_and_account_metas_v2(
+        &self,
+        leg: &SwapLegParams,
+        ctx: &SwapContext,
+    ) -> Result<SwapAndAccountMetas> {
+        self.catch_unwind("get_swap_and_account_metas_v2", || {
+            self.inner.get_swap_and_account_metas_v2(leg, ctx)
+        })
+    }
+
+    fn writable_accounts(&self, swap_params: &SwapParams) -> Result<Vec<Pubkey>> {
+        self.catch_unwind("writable_accounts", || {
+            self.inner.writable_accounts(swap_params)
+        })
+    }
+
+    fn quote_liquidity(&self, liquidity_params: &LiquidityParams) -> Result<LiquidityQuote> {
+        self.catch_unwind_quote("quote_liquidity", || {
+            self.inner.quote_liquidity(liquidity_params)
+        })
+    }
+
+    fn get_liquidity_ix_account_metas(
+        &self,
+        liquidity_params: &LiquidityParams,
+        swap_params: &SwapParams,
+    ) -> Result<SwapAndAccountMetas> {
+        self.catch_unwind("get_liquidity_ix_account_metas", || {
+            self.inner
+                .get_liquidity_ix_account_metas(liquidity_params, swap_params)
+        })
+    }
+
+    fn get_swap_and_account_metas_candidates(
+        &self,
+        swap_params: &SwapParams,
+    ) -> Result<Vec<SwapAndAccountMetas>> {
+        self.catch_unwind("get_swap_and_account_metas_candidates", || {
+            self.inner
+                .get_swap_and_account_metas_candidates(swap_params)
+        })
+    }
+
+    fn get_user_token_accounts(
+        &self,
+        swap_params: &SwapParams,
+    ) -> Result<Vec<RequiredTokenAccount>> {
+        self.catch_unwind("get_user_token_accounts", || {
+            self.inner.get_user_token_accounts(swap_params)
+        })
+    }
+
+    fn is_active(&self) -> bool {
+        !self.is_deactivated() && self.inner.is_active()
+    }
+
+    fn activity_status(&self) -> ActivityStatus {
+        if self.is_deactivated() {
+            ActivityStatus::Paused {
+                reason: Some(format!("Deactivated after {} panics", self.panics())),
+            }
+        } else {
+            self.inner.activity_status()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        reference::{FlakyAmm, MockAmm, TokenSwapFixture},
+        SwapMode,
+    };
+
+    #[test]
+    fn test_catch_unwind_amm_deactivates_after_max_panics() {
+        let flaky_amm = FlakyAmm::new(MockAmm::new(Pubkey::new_unique(), vec![]).clone_amm())
+            .with_panic_on_quote();
+        let amm = CatchUnwindAmm::new(flaky_amm.clone_amm(), 2);
+        let quote_params = QuoteParams {
+            amount: 1,
+            input_mint: Pubkey::new_unique(),
+            output_mint: Pubkey::new_unique(),
+            swap_mode: SwapMode::ExactIn,
+            budget: None,
+            limit_price: None,
+        };
+
+        let error = amm.quote(&quote_params).unwrap_err().to_string();
+        assert!(error.starts_with(&format!("Mock {} panicked in quote", amm.key())));
+        assert!(amm.is_active());
+        assert!(amm.clone_amm().quote(&quote_params).is_err());
+        assert_eq!(amm.panics(), 2);
+        assert!(!amm.is_active());
+        assert_eq!(
+            amm.quote(&quote_params)
+                .unwrap_err()
+                .downcast_ref::<QuoteError>(),
+            Some(&QuoteError::PoolPaused)
+        );
+    }
+
+    #[test]
+    fn test_catch_unwind_amm_forwards_to_inner() {
+        let fixture = TokenSwapFixture::new(1_000_000_000, 2_000_000_000);
+        let inner = fixture.amm().unwrap();
+        let mut amm = CatchUnwindAmm::new(inner.clone_amm(), 1);
+
+        assert_eq!(amm.dex_id(), inner.dex_id());
+        assert_eq!(amm.capabilities(), inner.capabilities());
+        assert_eq!(amm.fee_schedule(), inner.fee_schedule());
+        assert_eq!(
+            amm.fee_mint_for(&fixture.token_a_mint, &fixture.token_b_mint),
+            inner.fee_mint_for(&fixture.token_a_mint, &fixture.token_b_mint)
+        );
+        assert_eq!(
+            amm.max_out_amount(&fixture.token_b_mint),
+            inner.max_out_amount(&fixture.token_b_mint)
+        );
+        assert_eq!(amm.get_swap_account_roles(), inner.get_swap_account_roles());
+        assert_eq!(amm.quote_depends_on_clock(), inner.quote_depends_on_clock());
+
+        let snapshot = amm.snapshot_state().unwrap();
+        assert_eq!(snapshot, inner.snapshot_state().unwrap());
+        amm.restore_state(&snapshot).unwrap();
+        assert!(amm.restore_state(&snapshot[1..]).is_err());
+        assert_eq!(amm.panics(), 0);
+    }
+}
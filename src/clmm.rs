@@ -0,0 +1,283 @@
+//! Tick and sqrt price math shared by concentrated liquidity Amms, sqrt prices are Q64.64 fixed point numbers
+use rust_decimal::Decimal;
+use solana_sdk::pubkey::Pubkey;
+
+use crate::math::{checked_pow_decimal, U256};
+
+pub const MIN_TICK: i32 = -443_636;
+pub const MAX_TICK: i32 = 443_636;
+
+/// `2^64 / sqrt(1.0001)^(2^i)` for each bit `i` of the absolute tick, as in the Whirlpool program
+const NEGATIVE_TICK_FACTORS_X64: [u128; 19] = [
+    18445821805675392311,
+    18444899583751176498,
+    18443055278223354162,
+    18439367220385604838,
+    18431993317065449817,
+    18417254355718160513,
+    18387811781193591352,
+    18329067761203520168,
+    18212142134806087854,
+    17980523815641551639,
+    17526086738831147013,
+    16651378430235024244,
+    15030750278693429944,
+    12247334978882834399,
+    8131365268884726200,
+    3584323654723342297,
+    696457651847595233,
+    26294789957452057,
+    37481735321082,
+];
+
+/// `2^96 * sqrt(1.0001)^(2^i)` for each bit `i` of the tick, as in the Whirlpool program
+const POSITIVE_TICK_FACTORS_X96: [u128; 19] = [
+    79232123823359799118286999567,
+    79236085330515764027303304731,
+    79244008939048815603706035061,
+    79259858533276714757314932305,
+    79291567232598584799939703904,
+    79355022692464371645785046466,
+    79482085999252804386437311141,
+    79736823300114093921829183326,
+    80248749790819932309965073892,
+    81282483887344747381513967011,
+    83390072131320151908154831281,
+    87770609709833776024991924138,
+    97234110755111693312479820773,
+    119332217159966728226237229890,
+    179736315981702064433883588727,
+    407748233172238350107850275304,
+    2098478828474011932436660412517,
+    55581415166113811149459800483533,
+    38992368544603139932233054999993551,
+];
+
+/// `sqrt(1.0001^tick)` as Q64.64, None outside of [`MIN_TICK`, `MAX_TICK`]
+///
+/// Matches the Whirlpool program bit for bit, positive ticks are computed in Q96 instead of inverting a Q64.64 ratio
+pub fn sqrt_price_x64_from_tick(tick: i32) -> Option<u128> {
+    if !(MIN_TICK..=MAX_TICK).contains(&tick) {
+        return None;
+    }
+    let abs_tick = tick.unsigned_abs();
+    if tick >= 0 {
+        let mut ratio = U256::one() << 96;
+        for (bit, factor) in POSITIVE_TICK_FACTORS_X96.iter().enumerate() {
+            if abs_tick & (1 << bit) != 0 {
+                ratio = (ratio * U256::from(*factor)) >> 96;
+            }
+        }
+        Some((ratio >> 32).as_u128())
+    } else {
+        let mut ratio = 1u128 << 64;
+        for (bit, factor) in NEGATIVE_TICK_FACTORS_X64.iter().enumerate() {
+            if abs_tick & (1 << bit) != 0 {
+                ratio = (ratio * factor) >> 64;
+            }
+        }
+        Some(ratio)
+    }
+}
+
+/// The greatest tick whose sqrt price does not exceed `sqrt_price_x64`
+pub fn tick_from_sqrt_price_x64(sqrt_price_x64: u128) -> Option<i32> {
+    let min_sqrt_price_x64 = sqrt_price_x64_from_tick(MIN_TICK)?;
+    let max_sqrt_price_x64 = sqrt_price_x64_from_tick(MAX_TICK)?;
+    if !(min_sqrt_price_x64..=max_sqrt_price_x64).contains(&sqrt_price_x64) {
+        return None;
+    }
+    let (mut low, mut high) = (MIN_TICK, MAX_TICK);
+    while low < high {
+        let middle = low + (high - low + 1) / 2;
+        if sqrt_price_x64_from_tick(middle)? <= sqrt_price_x64 {
+            low = middle;
+        } else {
+            high = middle - 1;
+        }
+    }
+    Some(low)
+}
+
+/// The price of token a in token b in UI units, adjusting for the mint decimals
+pub fn price_from_sqrt_price_x64(
+    sqrt_price_x64: u128,
+    decimals_a: u8,
+    decimals_b: u8,
+) -> Option<Decimal> {
+    let sqrt_price = Decimal::try_from_i128_with_scale(i128::try_from(sqrt_price_x64).ok()?, 0)
+        .ok()?
+        .checked_div(Decimal::from(1u128 << 64))?;
+    let price = sqrt_price.checked_mul(sqrt_price)?;
+    if decimals_a >= decimals_b {
+        price.checked_mul(checked_pow_decimal(
+            Decimal::TEN,
+            u64::from(decimals_a - decimals_b),
+        )?)
+    } else {
+        price.checked_div(checked_pow_decimal(
+            Decimal::TEN,
+            u64::from(decimals_b - decimals_a),
+        )?)
+    }
+}
+
+/// The direction tick arrays are traversed in, a to b swaps move the price down
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TickArrayDirection {
+    Down,
+    Up,
+}
+
+impl TickArrayDirection {
+    pub fn from_a_to_b(a_to_b: bool) -> Self {
+        if a_to_b {
+            TickArrayDirection::Down
+        } else {
+            TickArrayDirection::Up
+        }
+    }
+}
+
+fn ticks_in_array(tick_spacing: u16, ticks_per_array: u16) -> Option<i32> {
+    i32::from(tick_spacing)
+        .checked_mul(i32::from(ticks_per_array))
+        .filter(|ticks_in_array| *ticks_in_array > 0)
+}
+
+/// The start tick of the tick array containing `tick`, None if the array size is zero or overflows
+pub fn tick_array_start_index(tick: i32, tick_spacing: u16, ticks_per_array: u16) -> Option<i32> {
+    let ticks_in_array = ticks_in_array(tick_spacing, ticks_per_array)?;
+    tick.div_euclid(ticks_in_array).checked_mul(ticks_in_array)
+}
+
+/// The start ticks of up to `count` consecutive tick arrays from the one containing `tick`, stopping at the tick bounds
+/// None if the array size is zero or overflows
+pub fn tick_array_start_indexes(
+    tick: i32,
+    tick_spacing: u16,
+    ticks_per_array: u16,
+    direction: TickArrayDirection,
+    count: usize,
+) -> Option<Vec<i32>> {
+    let ticks_in_array = ticks_in_array(tick_spacing, ticks_per_array)?;
+    let start_index = tick_array_start_index(tick, tick_spacing, ticks_per_array)?;
+    let step = match direction {
+        TickArrayDirection::Down => -ticks_in_array,
+        TickArrayDirection::Up => ticks_in_array,
+    };
+    // The start indexes are bounded by the tick range, i64 keeps the arithmetic from overflowing past it
+    Some(
+        (0..count as i64)
+            .map(|offset| i64::from(start_index) + offset * i64::from(step))
+            .take_while(|start_index| {
+                *start_index + i64::from(ticks_in_array) > i64::from(MIN_TICK)
+                    && *start_index <= i64::from(MAX_TICK)
+            })
+            .map(|start_index| start_index as i32)
+            .collect(),
+    )
+}
+
+/// The addresses of the tick arrays a swap from `tick` in `direction` traverses, `derive_address` maps a start tick to the DEX specific address
+pub fn tick_array_window(
+    tick: i32,
+    tick_spacing: u16,
+    ticks_per_array: u16,
+    direction: TickArrayDirection,
+    count: usize,
+    derive_address: impl Fn(i32) -> Pubkey,
+) -> Option<Vec<Pubkey>> {
+    Some(
+        tick_array_start_indexes(tick, tick_spacing, ticks_per_array, direction, count)?
+            .into_iter()
+            .map(derive_address)
+            .collect(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sqrt_price_x64_from_tick() {
+        assert_eq!(sqrt_price_x64_from_tick(0), Some(1 << 64));
+        assert_eq!(sqrt_price_x64_from_tick(MIN_TICK - 1), None);
+        assert_eq!(sqrt_price_x64_from_tick(MAX_TICK + 1), None);
+        // MIN_SQRT_PRICE_X64 and MAX_SQRT_PRICE_X64 of the Whirlpool program
+        assert_eq!(sqrt_price_x64_from_tick(MIN_TICK), Some(4_295_048_016));
+        assert_eq!(
+            sqrt_price_x64_from_tick(MAX_TICK),
+            Some(79_226_673_515_401_279_992_447_579_055)
+        );
+        assert_eq!(
+            sqrt_price_x64_from_tick(-1),
+            Some(18_445_821_805_675_392_311)
+        );
+        assert_eq!(
+            sqrt_price_x64_from_tick(1),
+            Some(18_447_666_387_855_959_850)
+        );
+
+        for tick in [-100_000, -1, 1, 100_000] {
+            let expected = 1.0001f64.powf(f64::from(tick) / 2.0) * 2f64.powi(64);
+            let actual = sqrt_price_x64_from_tick(tick).unwrap() as f64;
+            assert!((actual / expected - 1.0).abs() < 1e-12, "{tick}");
+        }
+    }
+
+    #[test]
+    fn test_tick_from_sqrt_price_x64_roundtrip() {
+        for tick in (MIN_TICK..=MAX_TICK)
+            .step_by(9_973)
+            .chain([MIN_TICK, -1, 0, 1, MAX_TICK])
+        {
+            let sqrt_price_x64 = sqrt_price_x64_from_tick(tick).unwrap();
+            assert_eq!(tick_from_sqrt_price_x64(sqrt_price_x64), Some(tick));
+            if tick < MAX_TICK {
+                assert_eq!(tick_from_sqrt_price_x64(sqrt_price_x64 + 1), Some(tick));
+            }
+        }
+        assert_eq!(tick_from_sqrt_price_x64(0), None);
+    }
+
+    #[test]
+    fn test_price_from_sqrt_price_x64() {
+        // SOL/USDC at tick 0 with 9 and 6 decimals
+        let price = price_from_sqrt_price_x64(1 << 64, 9, 6).unwrap();
+        assert_eq!(price, Decimal::from(1_000));
+    }
+
+    #[test]
+    fn test_tick_array_start_indexes() {
+        assert_eq!(tick_array_start_index(-1, 64, 88), Some(-5_632));
+        assert_eq!(tick_array_start_index(5_632, 64, 88), Some(5_632));
+        assert_eq!(tick_array_start_index(0, 0, 88), None);
+        assert_eq!(tick_array_start_index(0, 64, 0), None);
+        assert_eq!(
+            tick_array_start_indexes(100, 64, 88, TickArrayDirection::Down, 3),
+            Some(vec![0, -5_632, -11_264])
+        );
+        assert_eq!(
+            tick_array_start_indexes(100, 64, 88, TickArrayDirection::Up, 3),
+            Some(vec![0, 5_632, 11_264])
+        );
+        assert_eq!(
+            tick_array_start_indexes(100, 0, 88, TickArrayDirection::Up, 3),
+            None
+        );
+        // Stops at the tick bounds
+        let start_indexes = tick_array_start_indexes(MAX_TICK, 64, 88, TickArrayDirection::Up, 3);
+        assert_eq!(
+            start_indexes,
+            tick_array_start_index(MAX_TICK, 64, 88).map(|start_index| vec![start_index])
+        );
+        assert_eq!(
+            tick_array_start_indexes(MIN_TICK, u16::MAX, 88, TickArrayDirection::Down, 3)
+                .map(|start_indexes| start_indexes.len()),
+            Some(1)
+        );
+        assert_eq!(tick_array_start_index(0, u16::MAX, u16::MAX), None);
+    }
+}
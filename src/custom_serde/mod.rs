@@ -1 +1,3 @@
 pub mod field_as_string;
+pub mod option_field_as_string;
+pub mod vec_field_as_string;
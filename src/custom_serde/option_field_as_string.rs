@@ -0,0 +1,27 @@
+use {
+    serde::{de, Deserializer, Serializer},
+    serde::{Deserialize, Serialize},
+    std::str::FromStr,
+};
+
+pub fn serialize<T, S>(t: &Option<T>, serializer: S) -> Result<S::Ok, S::Error>
+where
+    T: ToString,
+    S: Serializer,
+{
+    t.as_ref().map(ToString::to_string).serialize(serializer)
+}
+
+pub fn deserialize<'de, T, D>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+    T: FromStr,
+    D: Deserializer<'de>,
+    <T as FromStr>::Err: std::fmt::Debug,
+{
+    let s: Option<String> = Option::deserialize(deserializer)?;
+    s.map(|s| {
+        s.parse()
+            .map_err(|e| de::Error::custom(format!("Parse error: {:?}", e)))
+    })
+    .transpose()
+}
@@ -0,0 +1,31 @@
+use {
+    serde::{de, Deserializer, Serializer},
+    serde::{Deserialize, Serialize},
+    std::str::FromStr,
+};
+
+pub fn serialize<T, S>(t: &[T], serializer: S) -> Result<S::Ok, S::Error>
+where
+    T: ToString,
+    S: Serializer,
+{
+    t.iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .serialize(serializer)
+}
+
+pub fn deserialize<'de, T, D>(deserializer: D) -> Result<Vec<T>, D::Error>
+where
+    T: FromStr,
+    D: Deserializer<'de>,
+    <T as FromStr>::Err: std::fmt::Debug,
+{
+    let v: Vec<String> = Vec::deserialize(deserializer)?;
+    v.into_iter()
+        .map(|s| {
+            s.parse()
+                .map_err(|e| de::Error::custom(format!("Parse error: {:?}", e)))
+        })
+        .collect()
+}
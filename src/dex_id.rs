@@ -0,0 +1,122 @@
+use std::{borrow::Cow, convert::Infallible, fmt, str::FromStr};
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+macro_rules! dex_ids {
+    ($($variant:ident => $id:literal,)*) => {
+        /// A stable identifier of the underlying DEX, unlike the free-form `label`
+        #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+        #[non_exhaustive]
+        pub enum DexId {
+            $($variant,)*
+            Other(Cow<'static, str>),
+        }
+
+        impl DexId {
+            pub fn as_str(&self) -> &str {
+                match self {
+                    $(DexId::$variant => $id,)*
+                    DexId::Other(id) => id,
+                }
+            }
+        }
+
+        impl FromStr for DexId {
+            type Err = Infallible;
+
+            fn from_str(s: &str) -> Result<Self, Self::Err> {
+                Ok(match s {
+                    $($id => DexId::$variant,)*
+                    _ => DexId::Other(Cow::Owned(s.to_string())),
+                })
+            }
+        }
+    };
+}
+
+dex_ids! {
+    Aldrin => "aldrin",
+    AldrinV2 => "aldrin-v2",
+    Balansol => "balansol",
+    Clone => "clone",
+    Crema => "crema",
+    Cropper => "cropper",
+    Cykura => "cykura",
+    DeltaFi => "deltafi",
+    Dradex => "dradex",
+    Fox => "fox",
+    GooseFX => "goosefx",
+    GooseFXV2 => "goosefx-v2",
+    HeliumTreasuryManagement => "helium-treasury-management",
+    Invariant => "invariant",
+    Lifinity => "lifinity",
+    LifinityV2 => "lifinity-v2",
+    MarcoPolo => "marco-polo",
+    Marinade => "marinade",
+    Mercurial => "mercurial",
+    Meteora => "meteora",
+    MeteoraDlmm => "meteora-dlmm",
+    Moonshot => "moonshot",
+    Obric => "obric",
+    OneIntro => "one-intro",
+    Openbook => "openbook",
+    OpenBookV2 => "openbook-v2",
+    Perps => "perps",
+    PerpsV2 => "perps-v2",
+    Phoenix => "phoenix",
+    Pumpdotfun => "pumpdotfun",
+    Raydium => "raydium",
+    RaydiumClmm => "raydium-clmm",
+    RaydiumCP => "raydium-cp",
+    Saber => "saber",
+    SanctumInfinity => "sanctum-infinity",
+    Sencha => "sencha",
+    Serum => "serum",
+    SolFi => "solfi",
+    Stabble => "stabble",
+    StakeDex => "stakedex",
+    Step => "step",
+    Symmetry => "symmetry",
+    TokenSwap => "token-swap",
+    TokenSwapV2 => "token-swap-v2",
+    Whirlpool => "whirlpool",
+}
+
+impl fmt::Display for DexId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl Serialize for DexId {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for DexId {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        Ok(s.parse().unwrap_or_else(|e: Infallible| match e {}))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dex_id_serde_roundtrip() {
+        for dex_id in [
+            DexId::RaydiumClmm,
+            DexId::Other(Cow::Borrowed("some-new-dex")),
+        ] {
+            let json = serde_json::to_string(&dex_id).unwrap();
+            assert_eq!(serde_json::from_str::<DexId>(&json).unwrap(), dex_id);
+        }
+        assert_eq!(
+            serde_json::to_string(&DexId::MeteoraDlmm).unwrap(),
+            r#""meteora-dlmm""#
+        );
+    }
+}
@@ -0,0 +1,37 @@
+use solana_sdk::pubkey::Pubkey;
+
+/// A filter on program accounts, mirroring the RPC `getProgramAccounts` filters
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AccountFilter {
+    /// The account data has exactly this length
+    DataSize(u64),
+    /// The account data contains `bytes` at `offset`
+    Memcmp { offset: usize, bytes: Vec<u8> },
+}
+
+impl AccountFilter {
+    pub fn matches(&self, data: &[u8]) -> bool {
+        match self {
+            AccountFilter::DataSize(data_size) => data.len() as u64 == *data_size,
+            AccountFilter::Memcmp { offset, bytes } => data
+                .get(*offset..)
+                .is_some_and(|data| data.starts_with(bytes)),
+        }
+    }
+}
+
+/// Lets a crawler find every market of a DEX without program specific knowledge
+pub trait MarketDiscovery {
+    /// The program owning the market accounts
+    fn program_id() -> Pubkey;
+
+    /// The filters to pass when fetching the program accounts
+    fn account_filters() -> Vec<AccountFilter>;
+
+    /// Indicates if the account data is a market the Amm can be constructed from
+    fn is_market_account(data: &[u8]) -> bool {
+        Self::account_filters()
+            .iter()
+            .all(|filter| filter.matches(data))
+    }
+}
@@ -0,0 +1,74 @@
+use std::fmt;
+
+/// Structured reasons for a failed quote
+///
+/// Returned through the `anyhow::Error` of `Amm::quote`, hosts can recover it with `error.downcast_ref::<QuoteError>()`
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum QuoteError {
+    /// The amount is above what the pool accepts in a single trade
+    ExceedsMaxTradeSize { max_in: Option<u64> },
+    /// The pool does not have enough reserves to fill the amount
+    InsufficientLiquidity { max_in: Option<u64> },
+    /// The pool is not trading at the moment
+    PoolPaused,
+    /// The amount is too small to produce any output
+    AmountTooSmall { min_in: Option<u64> },
+    /// The swap mode is not supported by the Amm
+    UnsupportedSwapMode,
+    /// The user balance of the input mint does not cover the in amount
+    InsufficientBalance { balance: u64 },
+    /// The quote ran out of the iterations or tick crossings of its [`crate::QuoteBudget`] before completing
+    BudgetExhausted,
+    /// The quote did not complete before the deadline of its [`crate::QuoteBudget`]
+    DeadlineExceeded,
+}
+
+impl QuoteError {
+    /// Indicates if quoting a smaller amount might succeed, as opposed to skipping the pool altogether
+    /// A smaller amount needs fewer iterations, but is as late for an expired deadline
+    pub fn is_retryable_with_smaller_amount(&self) -> bool {
+        matches!(
+            self,
+            QuoteError::ExceedsMaxTradeSize { .. }
+                | QuoteError::InsufficientLiquidity { .. }
+                | QuoteError::BudgetExhausted
+        )
+    }
+}
+
+impl fmt::Display for QuoteError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            QuoteError::ExceedsMaxTradeSize {
+                max_in: Some(max_in),
+            } => {
+                write!(f, "Exceeds max trade size of {max_in}")
+            }
+            QuoteError::ExceedsMaxTradeSize { max_in: None } => write!(f, "Exceeds max trade size"),
+            QuoteError::InsufficientLiquidity {
+                max_in: Some(max_in),
+            } => {
+                write!(f, "Insufficient liquidity, max in amount is {max_in}")
+            }
+            QuoteError::InsufficientLiquidity { max_in: None } => {
+                write!(f, "Insufficient liquidity")
+            }
+            QuoteError::PoolPaused => write!(f, "Pool is paused"),
+            QuoteError::AmountTooSmall {
+                min_in: Some(min_in),
+            } => {
+                write!(f, "Amount too small, min in amount is {min_in}")
+            }
+            QuoteError::AmountTooSmall { min_in: None } => write!(f, "Amount too small"),
+            QuoteError::UnsupportedSwapMode => write!(f, "Unsupported swap mode"),
+            QuoteError::BudgetExhausted => write!(f, "Quote budget exhausted"),
+            QuoteError::DeadlineExceeded => write!(f, "Quote deadline exceeded"),
+            QuoteError::InsufficientBalance { balance } => {
+                write!(f, "Insufficient balance of {balance}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for QuoteError {}
@@ -0,0 +1,20 @@
+/// How a swap through an Amm has to be executed, for order flow engines picking the execution path
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct ExecutionConstraints {
+    /// The swap has to land in a bundle, e.g. to protect the market maker from backruns
+    pub requires_bundle: bool,
+    /// The quote is last look, the market maker can still reject the swap at execution
+    pub last_look: bool,
+    /// The swap fails if it lands more than this many slots after the quote
+    pub max_quote_to_execution_slots: Option<u64>,
+}
+
+impl ExecutionConstraints {
+    /// Indicates if a swap quoted at `quote_slot` can still land at `current_slot`
+    pub fn is_executable_at(&self, quote_slot: u64, current_slot: u64) -> bool {
+        match self.max_quote_to_execution_slots {
+            Some(max_slots) => current_slot.saturating_sub(quote_slot) <= max_slots,
+            None => true,
+        }
+    }
+}
@@ -0,0 +1,160 @@
+use std::time::{Duration, Instant};
+
+/// How the host should handle consecutive `update` failures of an Amm
+///
+/// E.g. a bonding curve whose accounts legitimately close deactivates early, a CLMM with transient RPC gaps keeps retrying
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FailurePolicy {
+    /// Failures retried on the next update cycle before backing off
+    pub max_consecutive_failures: u32,
+    /// The first backoff, doubled on every further failure
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+    /// Consecutive failures after which the host should stop updating the Amm, None to never give up
+    pub deactivate_after: Option<u32>,
+}
+
+impl Default for FailurePolicy {
+    fn default() -> Self {
+        FailurePolicy {
+            max_consecutive_failures: 3,
+            initial_backoff: Duration::from_secs(1),
+            max_backoff: Duration::from_secs(60),
+            deactivate_after: None,
+        }
+    }
+}
+
+/// What the host should do after an update failure
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FailureAction {
+    Retry,
+    /// Skip updating the Amm, and quoting it from a stale state, for the duration
+    BackOff(Duration),
+    Deactivate,
+}
+
+/// Applies a [`FailurePolicy`] to the updates of one Amm
+#[derive(Debug, Clone)]
+pub struct UpdateFailureTracker {
+    policy: FailurePolicy,
+    consecutive_failures: u32,
+    /// When the backoff started and its duration, kept apart since their sum can overflow an Instant
+    backoff: Option<(Instant, Duration)>,
+}
+
+impl UpdateFailureTracker {
+    pub fn new(policy: FailurePolicy) -> Self {
+        UpdateFailureTracker {
+            policy,
+            consecutive_failures: 0,
+            backoff: None,
+        }
+    }
+
+    pub fn consecutive_failures(&self) -> u32 {
+        self.consecutive_failures
+    }
+
+    pub fn record_success(&mut self) {
+        self.consecutive_failures = 0;
+        self.backoff = None;
+    }
+
+    pub fn record_failure(&mut self, now: Instant) -> FailureAction {
+        self.consecutive_failures = self.consecutive_failures.saturating_add(1);
+        if self
+            .policy
+            .deactivate_after
+            .is_some_and(|deactivate_after| self.consecutive_failures >= deactivate_after)
+        {
+            return FailureAction::Deactivate;
+        }
+        let Some(backoffs) = self
+            .consecutive_failures
+            .checked_sub(self.policy.max_consecutive_failures.saturating_add(1))
+        else {
+            return FailureAction::Retry;
+        };
+
+        let backoff = self
+            .policy
+            .initial_backoff
+            .saturating_mul(2u32.saturating_pow(backoffs))
+            .min(self.policy.max_backoff);
+        self.backoff = Some((now, backoff));
+        FailureAction::BackOff(backoff)
+    }
+
+    /// Indicates if the Amm should be updated at `now`, false while backing off
+    pub fn should_update(&self, now: Instant) -> bool {
+        match self.backoff {
+            Some((backoff_start, backoff)) => {
+                now.saturating_duration_since(backoff_start) >= backoff
+            }
+            None => true,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_update_failure_tracker() {
+        let now = Instant::now();
+        let mut tracker = UpdateFailureTracker::new(FailurePolicy {
+            max_consecutive_failures: 1,
+            initial_backoff: Duration::from_secs(1),
+            max_backoff: Duration::from_secs(3),
+            deactivate_after: Some(5),
+        });
+        assert_eq!(tracker.record_failure(now), FailureAction::Retry);
+        assert_eq!(
+            tracker.record_failure(now),
+            FailureAction::BackOff(Duration::from_secs(1))
+        );
+        assert!(!tracker.should_update(now));
+        assert!(tracker.should_update(now + Duration::from_secs(1)));
+        assert_eq!(
+            tracker.record_failure(now),
+            FailureAction::BackOff(Duration::from_secs(2))
+        );
+        assert_eq!(
+            tracker.record_failure(now),
+            FailureAction::BackOff(Duration::from_secs(3))
+        );
+        assert_eq!(tracker.record_failure(now), FailureAction::Deactivate);
+
+        tracker.record_success();
+        assert_eq!(tracker.consecutive_failures(), 0);
+        assert!(tracker.should_update(now));
+    }
+
+    #[test]
+    fn test_update_failure_tracker_saturates() {
+        let now = Instant::now();
+        let mut tracker = UpdateFailureTracker::new(FailurePolicy {
+            max_consecutive_failures: u32::MAX,
+            ..FailurePolicy::default()
+        });
+        assert_eq!(tracker.record_failure(now), FailureAction::Retry);
+
+        let mut tracker = UpdateFailureTracker::new(FailurePolicy {
+            max_consecutive_failures: 0,
+            initial_backoff: Duration::MAX,
+            max_backoff: Duration::MAX,
+            deactivate_after: None,
+        });
+        assert_eq!(
+            tracker.record_failure(now),
+            FailureAction::BackOff(Duration::MAX)
+        );
+        assert_eq!(
+            tracker.record_failure(now),
+            FailureAction::BackOff(Duration::MAX)
+        );
+        assert!(!tracker.should_update(now + Duration::from_secs(365 * 24 * 60 * 60)));
+    }
+}
@@ -0,0 +1,107 @@
+use serde::{Deserialize, Serialize};
+
+const BPS_DENOMINATOR: u32 = 10_000;
+
+/// How the fee of a pool moves away from its base fee
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", tag = "type")]
+pub enum DynamicFeeModel {
+    /// The fee grows with recent price volatility, up to `max_fee_bps`
+    #[serde(rename_all = "camelCase")]
+    VolatilityBased { max_fee_bps: u16 },
+    /// The fee decays linearly from `initial_fee_bps` to the base fee, e.g. anti-sniping launch fees
+    #[serde(rename_all = "camelCase")]
+    TimeDecay {
+        initial_fee_bps: u16,
+        decay_start_unix_timestamp: i64,
+        decay_end_unix_timestamp: i64,
+    },
+}
+
+/// The fees a pool charges, so fees can be compared without quoting the pool
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FeeSchedule {
+    pub base_fee_bps: u16,
+    pub dynamic_fee: Option<DynamicFeeModel>,
+    /// The share of the fee going to the protocol rather than liquidity providers
+    pub protocol_fee_share_bps: u16,
+}
+
+impl FeeSchedule {
+    pub fn flat(base_fee_bps: u16) -> Self {
+        FeeSchedule {
+            base_fee_bps,
+            dynamic_fee: None,
+            protocol_fee_share_bps: 0,
+        }
+    }
+
+    /// The highest fee the pool can charge
+    pub fn max_fee_bps(&self) -> u16 {
+        match self.dynamic_fee {
+            None => self.base_fee_bps,
+            Some(DynamicFeeModel::VolatilityBased { max_fee_bps }) => {
+                max_fee_bps.max(self.base_fee_bps)
+            }
+            Some(DynamicFeeModel::TimeDecay {
+                initial_fee_bps, ..
+            }) => initial_fee_bps.max(self.base_fee_bps),
+        }
+    }
+
+    /// The fee charged at `unix_timestamp`, None when it depends on volatility
+    pub fn fee_bps_at(&self, unix_timestamp: i64) -> Option<u16> {
+        match self.dynamic_fee {
+            None => Some(self.base_fee_bps),
+            Some(DynamicFeeModel::VolatilityBased { .. }) => None,
+            Some(DynamicFeeModel::TimeDecay {
+                initial_fee_bps,
+                decay_start_unix_timestamp,
+                decay_end_unix_timestamp,
+            }) => {
+                if unix_timestamp <= decay_start_unix_timestamp {
+                    return Some(initial_fee_bps);
+                }
+                if unix_timestamp >= decay_end_unix_timestamp {
+                    return Some(self.base_fee_bps);
+                }
+                let elapsed = i128::from(unix_timestamp - decay_start_unix_timestamp);
+                let duration = i128::from(decay_end_unix_timestamp - decay_start_unix_timestamp);
+                let initial_fee_bps = i128::from(initial_fee_bps);
+                let base_fee_bps = i128::from(self.base_fee_bps);
+                let fee_bps =
+                    initial_fee_bps - (initial_fee_bps - base_fee_bps) * elapsed / duration;
+                u16::try_from(fee_bps).ok()
+            }
+        }
+    }
+
+    /// The part of `fee_bps` going to the protocol
+    pub fn protocol_fee_bps(&self, fee_bps: u16) -> u16 {
+        (u32::from(fee_bps) * u32::from(self.protocol_fee_share_bps) / BPS_DENOMINATOR) as u16
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_time_decay_fee_schedule() {
+        let fee_schedule = FeeSchedule {
+            base_fee_bps: 25,
+            dynamic_fee: Some(DynamicFeeModel::TimeDecay {
+                initial_fee_bps: 9_925,
+                decay_start_unix_timestamp: 1_000,
+                decay_end_unix_timestamp: 2_000,
+            }),
+            protocol_fee_share_bps: 2_000,
+        };
+        assert_eq!(fee_schedule.max_fee_bps(), 9_925);
+        assert_eq!(fee_schedule.fee_bps_at(0), Some(9_925));
+        assert_eq!(fee_schedule.fee_bps_at(1_500), Some(4_975));
+        assert_eq!(fee_schedule.fee_bps_at(3_000), Some(25));
+        assert_eq!(fee_schedule.protocol_fee_bps(25), 5);
+    }
+}
@@ -0,0 +1,434 @@
+/// Implements the listed [`Amm`](crate::Amm) methods by forwarding them to the `$inner` field
+///
+/// Lets wrappers write out only the methods they change, e.g. `forward_amm_methods!(inner => label, key)`
+macro_rules! forward_amm_methods {
+    ($inner:ident => $($method:ident),* $(,)?) => {
+        $(forward_amm_methods!(@method $inner, $method);)*
+    };
+    (@method $inner:ident, label) => {
+        fn label(&self) -> String {
+            self.$inner.label()
+        }
+    };
+    (@method $inner:ident, dex_id) => {
+        fn dex_id(&self) -> $crate::DexId {
+            self.$inner.dex_id()
+        }
+    };
+    (@method $inner:ident, program_id) => {
+        fn program_id(&self) -> $crate::Pubkey {
+            self.$inner.program_id()
+        }
+    };
+    (@method $inner:ident, key) => {
+        fn key(&self) -> $crate::Pubkey {
+            self.$inner.key()
+        }
+    };
+    (@method $inner:ident, get_reserve_mints) => {
+        fn get_reserve_mints(&self) -> Vec<$crate::Pubkey> {
+            self.$inner.get_reserve_mints()
+        }
+    };
+    (@method $inner:ident, get_accounts_to_update) => {
+        fn get_accounts_to_update(&self) -> Vec<$crate::Pubkey> {
+            self.$inner.get_accounts_to_update()
+        }
+    };
+    (@method $inner:ident, get_accounts_to_update_with_priority) => {
+        fn get_accounts_to_update_with_priority(
+            &self,
+        ) -> Vec<($crate::Pubkey, $crate::UpdatePriority)> {
+            self.$inner.get_accounts_to_update_with_priority()
+        }
+    };
+    (@method $inner:ident, get_static_accounts) => {
+        fn get_static_accounts(&self) -> Vec<$crate::Pubkey> {
+            self.$inner.get_static_accounts()
+        }
+    };
+    (@method $inner:ident, get_dynamic_accounts) => {
+        fn get_dynamic_accounts(&self, hint: Option<&$crate::QuoteParams>) -> Vec<$crate::Pubkey> {
+            self.$inner.get_dynamic_accounts(hint)
+        }
+    };
+    (@method $inner:ident, predict_accounts_for_amount_range) => {
+        fn predict_accounts_for_amount_range(
+            &self,
+            input_mint: &$crate::Pubkey,
+            min_amount: u64,
+            max_amount: u64,
+        ) -> Vec<$crate::Pubkey> {
+            self.$inner
+                .predict_accounts_for_amount_range(input_mint, min_amount, max_amount)
+        }
+    };
+    (@method $inner:ident, update) => {
+        fn update(&mut self, account_map: &$crate::AccountMap) -> $crate::Result<()> {
+            self.$inner.update(account_map)
+        }
+    };
+    (@method $inner:ident, update_with_diff) => {
+        fn update_with_diff(
+            &mut self,
+            account_map: &$crate::AccountMap,
+            diff: &$crate::AccountMapDiff,
+        ) -> $crate::Result<()> {
+            self.$inner.update_with_diff(account_map, diff)
+        }
+    };
+    (@method $inner:ident, plan_update) => {
+        fn plan_update(&self) -> $crate::UpdatePlan {
+            self.$inner.plan_update()
+        }
+    };
+    (@method $inner:ident, apply_update) => {
+        fn apply_update(
+            &mut self,
+            fetched: &$crate::AccountMap,
+            plan_id: u64,
+        ) -> $crate::Result<()> {
+            self.$inner.apply_update(fetched, plan_id)
+        }
+    };
+    (@method $inner:ident, update_shared) => {
+        fn update_shared(&self, account_map: &$crate::AccountMap) -> $crate::Result<()> {
+            self.$inner.update_shared(account_map)
+        }
+    };
+    (@method $inner:ident, quote) => {
+        fn quote(&self, quote_params: &$crate::QuoteParams) -> $crate::Result<$crate::Quote> {
+            self.$inner.quote(quote_params)
+        }
+    };
+    (@method $inner:ident, quote_into) => {
+        fn quote_into(
+            &self,
+            quote_params: &$crate::QuoteParams,
+            quote: &mut $crate::Quote,
+        ) -> $crate::Result<()> {
+            self.$inner.quote_into(quote_params, quote)
+        }
+    };
+    (@method $inner:ident, quote_with_current_token_balance) => {
+        fn quote_with_current_token_balance(
+            &self,
+            quote_params: &$crate::QuoteParams,
+            token_balances: &dyn $crate::TokenBalanceSource,
+        ) -> $crate::Result<$crate::Quote> {
+            self.$inner
+                .quote_with_current_token_balance(quote_params, token_balances)
+        }
+    };
+    (@method $inner:ident, min_in_amount) => {
+        fn min_in_amount(&self, input_mint: &$crate::Pubkey) -> Option<u64> {
+            self.$inner.min_in_amount(input_mint)
+        }
+    };
+    (@method $inner:ident, min_out_amount) => {
+        fn min_out_amount(&self, output_mint: &$crate::Pubkey) -> Option<u64> {
+            self.$inner.min_out_amount(output_mint)
+        }
+    };
+    (@method $inner:ident, max_in_amount) => {
+        fn max_in_amount(&self, input_mint: &$crate::Pubkey) -> Option<u64> {
+            self.$inner.max_in_amount(input_mint)
+        }
+    };
+    (@method $inner:ident, max_out_amount) => {
+        fn max_out_amount(&self, output_mint: &$crate::Pubkey) -> Option<u64> {
+            self.$inner.max_out_amount(output_mint)
+        }
+    };
+    (@method $inner:ident, quote_cache_key) => {
+        fn quote_cache_key(&self, quote_params: &$crate::QuoteParams) -> Option<u64> {
+            self.$inner.quote_cache_key(quote_params)
+        }
+    };
+    (@method $inner:ident, last_update_slot) => {
+        fn last_update_slot(&self) -> Option<u64> {
+            self.$inner.last_update_slot()
+        }
+    };
+    (@method $inner:ident, state_version) => {
+        fn state_version(&self) -> u64 {
+            self.$inner.state_version()
+        }
+    };
+    (@method $inner:ident, snapshot_state) => {
+        fn snapshot_state(&self) -> Option<Vec<u8>> {
+            self.$inner.snapshot_state()
+        }
+    };
+    (@method $inner:ident, restore_state) => {
+        fn restore_state(&mut self, bytes: &[u8]) -> $crate::Result<()> {
+            self.$inner.restore_state(bytes)
+        }
+    };
+    (@method $inner:ident, quoting_blackout) => {
+        fn quoting_blackout(&self, clock_ref: &$crate::ClockRef) -> Option<$crate::BlackoutWindow> {
+            self.$inner.quoting_blackout(clock_ref)
+        }
+    };
+    (@method $inner:ident, quote_depends_on_clock) => {
+        fn quote_depends_on_clock(&self) -> bool {
+            self.$inner.quote_depends_on_clock()
+        }
+    };
+    (@method $inner:ident, max_quote_age_slots) => {
+        fn max_quote_age_slots(&self) -> Option<u64> {
+            self.$inner.max_quote_age_slots()
+        }
+    };
+    (@method $inner:ident, quote_rate_limit) => {
+        fn quote_rate_limit(&self) -> Option<$crate::RateLimit> {
+            self.$inner.quote_rate_limit()
+        }
+    };
+    (@method $inner:ident, routing_hints) => {
+        fn routing_hints(&self) -> $crate::RoutingHints {
+            self.$inner.routing_hints()
+        }
+    };
+    (@method $inner:ident, settlement_constraints) => {
+        fn settlement_constraints(&self) -> Option<$crate::SettlementConstraints> {
+            self.$inner.settlement_constraints()
+        }
+    };
+    (@method $inner:ident, execution_constraints) => {
+        fn execution_constraints(&self) -> $crate::ExecutionConstraints {
+            self.$inner.execution_constraints()
+        }
+    };
+    (@method $inner:ident, get_swap_and_account_metas) => {
+        fn get_swap_and_account_metas(
+            &self,
+            swap_params: &$crate::SwapParams,
+        ) -> $crate::Result<$crate::SwapAndAccountMetas> {
+            self.$inner.get_swap_and_account_metas(swap_params)
+        }
+    };
+    (@method $inner:ident, get_adjacent_instructions) => {
+        fn get_adjacent_instructions(
+            &self,
+            swap_params: &$crate::SwapParams,
+        ) -> $crate::Result<$crate::AdjacentInstructions> {
+            self.$inner.get_adjacent_instructions(swap_params)
+        }
+    };
+    (@method $inner:ident, get_swap_and_account_metas_v2) => {
+        fn get_swap_and_account_metas_v2(
+            &self,
+            leg: &$crate::SwapLegParams,
+            ctx: &$crate::SwapContext,
+        ) -> $crate::Result<$crate::SwapAndAccountMetas> {
+            self.$inner.get_swap_and_account_metas_v2(leg, ctx)
+        }
+    };
+    (@method $inner:ident, writable_accounts) => {
+        fn writable_accounts(
+            &self,
+            swap_params: &$crate::SwapParams,
+        ) -> $crate::Result<Vec<$crate::Pubkey>> {
+            self.$inner.writable_accounts(swap_params)
+        }
+    };
+    (@method $inner:ident, get_swap_account_roles) => {
+        fn get_swap_account_roles(&self) -> Vec<$crate::AccountRole> {
+            self.$inner.get_swap_account_roles()
+        }
+    };
+    (@method $inner:ident, quote_liquidity) => {
+        fn quote_liquidity(
+            &self,
+            liquidity_params: &$crate::LiquidityParams,
+        ) -> $crate::Result<$crate::LiquidityQuote> {
+            self.$inner.quote_liquidity(liquidity_params)
+        }
+    };
+    (@method $inner:ident, get_liquidity_ix_account_metas) => {
+        fn get_liquidity_ix_account_metas(
+            &self,
+            liquidity_params: &$crate::LiquidityParams,
+            swap_params: &$crate::SwapParams,
+        ) -> $crate::Result<$crate::SwapAndAccountMetas> {
+            self.$inner
+                .get_liquidity_ix_account_metas(liquidity_params, swap_params)
+        }
+    };
+    (@method $inner:ident, get_swap_and_account_metas_candidates) => {
+        fn get_swap_and_account_metas_candidates(
+            &self,
+            swap_params: &$crate::SwapParams,
+        ) -> $crate::Result<Vec<$crate::SwapAndAccountMetas>> {
+            self.$inner
+                .get_swap_and_account_metas_candidates(swap_params)
+        }
+    };
+    (@method $inner:ident, get_user_token_accounts) => {
+        fn get_user_token_accounts(
+            &self,
+            swap_params: &$crate::SwapParams,
+        ) -> $crate::Result<Vec<$crate::RequiredTokenAccount>> {
+            self.$inner.get_user_token_accounts(swap_params)
+        }
+    };
+    (@method $inner:ident, tracing_fields) => {
+        fn tracing_fields(&self) -> Vec<(&'static str, String)> {
+            self.$inner.tracing_fields()
+        }
+    };
+    (@method $inner:ident, get_pool_metadata) => {
+        fn get_pool_metadata(&self) -> Option<$crate::PoolMetadata> {
+            self.$inner.get_pool_metadata()
+        }
+    };
+    (@method $inner:ident, fee_schedule) => {
+        fn fee_schedule(&self) -> Option<$crate::FeeSchedule> {
+            self.$inner.fee_schedule()
+        }
+    };
+    (@method $inner:ident, current_fee_bps) => {
+        fn current_fee_bps(
+            &self,
+            input_mint: &$crate::Pubkey,
+            amount_hint: Option<u64>,
+        ) -> Option<$crate::Decimal> {
+            self.$inner.current_fee_bps(input_mint, amount_hint)
+        }
+    };
+    (@method $inner:ident, fee_mint_for) => {
+        fn fee_mint_for(
+            &self,
+            input_mint: &$crate::Pubkey,
+            output_mint: &$crate::Pubkey,
+        ) -> Option<$crate::Pubkey> {
+            self.$inner.fee_mint_for(input_mint, output_mint)
+        }
+    };
+    (@method $inner:ident, accepts_native_sol) => {
+        fn accepts_native_sol(&self) -> $crate::NativeSolPolicy {
+            self.$inner.accepts_native_sol()
+        }
+    };
+    (@method $inner:ident, handles_token2022_fees) => {
+        fn handles_token2022_fees(&self) -> bool {
+            self.$inner.handles_token2022_fees()
+        }
+    };
+    (@method $inner:ident, get_oracle_requirements) => {
+        fn get_oracle_requirements(&self) -> Vec<$crate::OracleRequirement> {
+            self.$inner.get_oracle_requirements()
+        }
+    };
+    (@method $inner:ident, has_dynamic_accounts) => {
+        fn has_dynamic_accounts(&self) -> bool {
+            self.$inner.has_dynamic_accounts()
+        }
+    };
+    (@method $inner:ident, requires_update_for_reserve_mints) => {
+        fn requires_update_for_reserve_mints(&self) -> bool {
+            self.$inner.requires_update_for_reserve_mints()
+        }
+    };
+    (@method $inner:ident, supports_exact_out) => {
+        fn supports_exact_out(&self) -> bool {
+            self.$inner.supports_exact_out()
+        }
+    };
+    (@method $inner:ident, get_user_setup) => {
+        fn get_user_setup(&self) -> Option<$crate::AmmUserSetup> {
+            self.$inner.get_user_setup()
+        }
+    };
+    (@method $inner:ident, get_user_setup_v2) => {
+        fn get_user_setup_v2(&self, user: &$crate::Pubkey) -> $crate::UserSetupPlan {
+            self.$inner.get_user_setup_v2(user)
+        }
+    };
+    (@method $inner:ident, clone_amm) => {
+        fn clone_amm(&self) -> Box<dyn $crate::Amm + Send + Sync> {
+            Box::new(self.clone())
+        }
+    };
+    (@method $inner:ident, interface_version) => {
+        fn interface_version(&self) -> u32 {
+            self.$inner.interface_version()
+        }
+    };
+    (@method $inner:ident, unidirectional) => {
+        fn unidirectional(&self) -> bool {
+            self.$inner.unidirectional()
+        }
+    };
+    (@method $inner:ident, capabilities) => {
+        fn capabilities(&self) -> $crate::AmmCapabilities {
+            self.$inner.capabilities()
+        }
+    };
+    (@method $inner:ident, inner_program_ids) => {
+        fn inner_program_ids(&self) -> Vec<$crate::Pubkey> {
+            self.$inner.inner_program_ids()
+        }
+    };
+    (@method $inner:ident, get_program_dependencies) => {
+        fn get_program_dependencies(&self) -> Vec<$crate::ProgramDependency> {
+            self.$inner.get_program_dependencies()
+        }
+    };
+    (@method $inner:ident, get_accounts_len) => {
+        fn get_accounts_len(&self) -> usize {
+            self.$inner.get_accounts_len()
+        }
+    };
+    (@method $inner:ident, underlying_liquidities) => {
+        fn underlying_liquidities(&self) -> Option<$crate::HashSet<$crate::Pubkey>> {
+            self.$inner.underlying_liquidities()
+        }
+    };
+    (@method $inner:ident, get_tvl_in_reserve_terms) => {
+        fn get_tvl_in_reserve_terms(&self) -> Option<Vec<($crate::Pubkey, u64)>> {
+            self.$inner.get_tvl_in_reserve_terms()
+        }
+    };
+    (@method $inner:ident, get_cumulative_volume) => {
+        fn get_cumulative_volume(&self) -> Option<Vec<($crate::Pubkey, u128)>> {
+            self.$inner.get_cumulative_volume()
+        }
+    };
+    (@method $inner:ident, created_at) => {
+        fn created_at(&self) -> Option<$crate::PoolCreationInfo> {
+            self.$inner.created_at()
+        }
+    };
+    (@method $inner:ident, is_active) => {
+        fn is_active(&self) -> bool {
+            self.$inner.is_active()
+        }
+    };
+    (@method $inner:ident, activity_status) => {
+        fn activity_status(&self) -> $crate::ActivityStatus {
+            self.$inner.activity_status()
+        }
+    };
+    (@method $inner:ident, update_failure_policy) => {
+        fn update_failure_policy(&self) -> $crate::FailurePolicy {
+            self.$inner.update_failure_policy()
+        }
+    };
+    (@method $inner:ident, on_accounts_missing) => {
+        fn on_accounts_missing(
+            &mut self,
+            missing: &[$crate::Pubkey],
+        ) -> $crate::MissingAccountsAction {
+            self.$inner.on_accounts_missing(missing)
+        }
+    };
+    (@method $inner:ident, successor_market) => {
+        fn successor_market(&self) -> Option<$crate::Market> {
+            self.$inner.successor_market()
+        }
+    };
+}
+
+pub(crate) use forward_amm_methods;
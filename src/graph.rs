@@ -0,0 +1,139 @@
+//! The token graph routers search, with a directed edge per Amm and swap direction
+use std::collections::HashMap;
+
+use solana_sdk::pubkey::Pubkey;
+
+use crate::Amm;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Edge {
+    pub amm_key: Pubkey,
+    pub input_mint: Pubkey,
+    pub output_mint: Pubkey,
+    pub accounts_len: usize,
+    /// The base fee of `Amm::fee_schedule`, a hint for ordering edges before quoting
+    pub fee_bps: Option<u16>,
+    /// The Amm only trades from its first mint to its second mint
+    pub unidirectional: bool,
+}
+
+/// Adjacency lists of the edges leaving each mint
+#[derive(Debug, Default, Clone)]
+pub struct TokenGraph {
+    edges: HashMap<Pubkey, Vec<Edge>, ahash::RandomState>,
+}
+
+impl TokenGraph {
+    pub fn insert(&mut self, edge: Edge) {
+        self.edges.entry(edge.input_mint).or_default().push(edge);
+    }
+
+    /// Inserts the edges of every direction `amm` trades in, nothing if it is not active
+    pub fn insert_amm(&mut self, amm: &dyn Amm) {
+        if !amm.is_active() {
+            return;
+        }
+
+        let reserve_mints = amm.get_reserve_mints();
+        let unidirectional = amm.unidirectional();
+        let amm_key = amm.key();
+        let accounts_len = amm.get_accounts_len();
+        let fee_bps = amm
+            .fee_schedule()
+            .map(|fee_schedule| fee_schedule.base_fee_bps);
+        for (i, input_mint) in reserve_mints.iter().enumerate() {
+            for (j, output_mint) in reserve_mints.iter().enumerate() {
+                if input_mint == output_mint || (unidirectional && (i, j) != (0, 1)) {
+                    continue;
+                }
+                self.insert(Edge {
+                    amm_key,
+                    input_mint: *input_mint,
+                    output_mint: *output_mint,
+                    accounts_len,
+                    fee_bps,
+                    unidirectional,
+                });
+            }
+        }
+    }
+
+    pub fn mints(&self) -> impl Iterator<Item = &Pubkey> {
+        self.edges.keys()
+    }
+
+    pub fn edges_from(&self, input_mint: &Pubkey) -> &[Edge] {
+        self.edges.get(input_mint).map_or(&[], Vec::as_slice)
+    }
+
+    pub fn edges_between<'a>(
+        &'a self,
+        input_mint: &Pubkey,
+        output_mint: &'a Pubkey,
+    ) -> impl Iterator<Item = &'a Edge> {
+        self.edges_from(input_mint)
+            .iter()
+            .filter(move |edge| edge.output_mint == *output_mint)
+    }
+
+    /// The distinct mints reachable from `input_mint` in one swap, sorted
+    pub fn neighbors(&self, input_mint: &Pubkey) -> Vec<Pubkey> {
+        let mut neighbors = self
+            .edges_from(input_mint)
+            .iter()
+            .map(|edge| edge.output_mint)
+            .collect::<Vec<_>>();
+        neighbors.sort();
+        neighbors.dedup();
+        neighbors
+    }
+
+    pub fn edge_count(&self) -> usize {
+        self.edges.values().map(Vec::len).sum()
+    }
+
+    /// Prunes the edges not matching `f`, e.g. Amms needing too many accounts for a multi hop route
+    pub fn retain(&mut self, mut f: impl FnMut(&Edge) -> bool) {
+        self.edges.retain(|_, edges| {
+            edges.retain(&mut f);
+            !edges.is_empty()
+        });
+    }
+}
+
+/// Builds the token graph of the active Amms
+pub fn build(amms: &[Box<dyn Amm + Send + Sync>]) -> TokenGraph {
+    let mut token_graph = TokenGraph::default();
+    for amm in amms {
+        token_graph.insert_amm(amm.as_ref());
+    }
+    token_graph
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::reference::MockAmm;
+
+    #[test]
+    fn test_build() {
+        let (sol, usdc, bonk) = (
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+        );
+        let sol_usdc = MockAmm::new(Pubkey::new_unique(), vec![sol, usdc]);
+        let sol_usdc_bonk = MockAmm::new(Pubkey::new_unique(), vec![sol, usdc, bonk]);
+        let mut token_graph = build(&[sol_usdc.clone_amm(), sol_usdc_bonk.clone_amm()]);
+
+        assert_eq!(token_graph.edge_count(), 8);
+        assert_eq!(token_graph.edges_between(&sol, &usdc).count(), 2);
+        let mut neighbors = vec![usdc, bonk];
+        neighbors.sort();
+        assert_eq!(token_graph.neighbors(&sol), neighbors);
+
+        token_graph.retain(|edge| edge.amm_key == sol_usdc.key());
+        assert_eq!(token_graph.edge_count(), 2);
+        assert!(token_graph.edges_from(&bonk).is_empty());
+    }
+}
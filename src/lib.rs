@@ -1,7 +1,9 @@
 use anyhow::{anyhow, Context, Error, Result};
 use rust_decimal::Decimal;
-use serde::{Deserialize, Serialize};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use serde_json::Value;
+pub use smallvec;
+use smallvec::SmallVec;
 use solana_account_decoder::{UiAccount, UiAccountEncoding};
 use solana_sdk::clock::Clock;
 use std::collections::HashSet;
@@ -9,15 +11,132 @@ use std::collections::HashSet;
 use std::sync::atomic::{AtomicI64, AtomicU64};
 use std::sync::Arc;
 use std::{collections::HashMap, convert::TryFrom, str::FromStr};
+mod account_map;
+mod account_role;
+mod adjacent_instructions;
+mod arc_amm;
+#[cfg(feature = "bench")]
+pub mod bench_utils;
+mod blackout;
+mod capabilities;
+mod catch_unwind;
+pub mod clmm;
 mod custom_serde;
-mod swap;
-use custom_serde::field_as_string;
-pub use swap::{Side, Swap};
+mod dex_id;
+mod discovery;
+mod error;
+mod execution;
+mod failure_policy;
+mod fee_schedule;
+mod forward;
+pub mod graph;
+mod liquidity;
+#[cfg(feature = "market-catalog")]
+pub mod market_catalog;
+pub mod math;
+mod missing_account_policy;
+mod nonce;
+mod oracle;
+pub mod orderbook;
+pub mod pair;
+mod placeholder;
+mod pool_metadata;
+#[cfg(feature = "proptest")]
+pub mod proptest_utils;
+mod quote_warning;
+mod rate_limit;
+pub mod reference;
+pub mod route;
+mod routing_hints;
+#[cfg(feature = "schemars")]
+pub mod schema;
+mod settlement;
+pub mod shared;
+mod shared_account_cache;
+mod simulation;
+mod slippage;
+mod snapshot;
+pub mod swap;
+mod sysvar_cache;
+pub mod timeout;
+pub mod token2022;
+mod token_accounts;
+pub mod token_ledger;
+#[cfg(feature = "tracing")]
+pub mod tracing_utils;
+pub mod transfer_hook;
+pub mod tx_size;
+mod typed_params;
+mod update_plan;
+mod update_stamp;
+mod user_setup;
+pub use account_map::{
+    account_hash, anchor_account_discriminator, assert_min_lamports, is_rent_exempt,
+    lamports_above_rent, try_deserialize_anchor_account, try_deserialize_packed, try_get_lamports,
+    AccountMapDiff, AccountMapDigest, AnchorAccount,
+};
+pub use account_role::AccountRole;
+pub use adjacent_instructions::AdjacentInstructions;
+pub use arc_amm::{ArcAmm, PerMarketState, SharedStatic};
+pub use blackout::BlackoutWindow;
+pub use capabilities::AmmCapabilities;
+pub use catch_unwind::CatchUnwindAmm;
+use custom_serde::{field_as_string, option_field_as_string, vec_field_as_string};
+pub use dex_id::DexId;
+pub use discovery::{AccountFilter, MarketDiscovery};
+pub use error::QuoteError;
+pub use execution::ExecutionConstraints;
+pub use failure_policy::{FailureAction, FailurePolicy, UpdateFailureTracker};
+pub use fee_schedule::{DynamicFeeModel, FeeSchedule};
+pub use liquidity::{LiquidityAction, LiquidityParams, LiquidityQuote};
+pub use missing_account_policy::MissingAccountPolicy;
+pub use nonce::{SequentialNonceProvider, SwapLegNonceProvider};
+pub use oracle::{OracleKind, OracleRequirement};
+pub use placeholder::{is_placeholder, PlaceholderRegistry};
+pub use pool_metadata::{CurveType, PoolMetadata};
+pub use quote_warning::QuoteWarning;
+pub use rate_limit::{RateLimit, RateLimiter};
+pub use routing_hints::RoutingHints;
+pub use settlement::SettlementConstraints;
+pub use shared::SharedAmm;
+pub use shared_account_cache::{SharedAccountCache, SlotAccount};
+pub use simulation::{SimulationExpectation, SwapBalances};
+pub use slippage::{Slippage, ThresholdAmounts};
+pub use snapshot::AmmSnapshot;
+pub use swap::{Side, Swap, SwapKind};
+pub use sysvar_cache::{SysvarCache, MAX_RECENT_BLOCKHASHES};
+pub use timeout::QuoteBudget;
+pub use token_accounts::{
+    get_associated_token_address, NativeSolPolicy, RequiredTokenAccount, TokenBalanceSource,
+    SPL_ASSOCIATED_TOKEN_ACCOUNT_PROGRAM_ID, SPL_TOKEN_2022_PROGRAM_ID, SPL_TOKEN_PROGRAM_ID,
+    WSOL_MINT,
+};
+pub use typed_params::{OracleOverrideParams, SerumMarketParams, TypedParams};
+pub use update_plan::UpdatePlan;
+pub use update_stamp::UpdateStamp;
+pub use user_setup::{UserSetupInstructionBuilder, UserSetupPlan, UserSetupStep};
 
 /// An abstraction in order to share reserve mints and necessary data
 use solana_sdk::{account::Account, instruction::AccountMeta, pubkey::Pubkey};
 
-#[derive(Serialize, Deserialize, PartialEq, Clone, Copy, Default, Debug)]
+/// Bumped on any change to the Amm trait contract, e.g. account ordering or method semantics
+pub const INTERFACE_VERSION: u32 = 2;
+/// The oldest interface version adapters can be compiled against and still be loaded
+pub const MIN_SUPPORTED_INTERFACE_VERSION: u32 = 1;
+
+/// Checks that an adapter compiled against `interface_version` can be loaded by this host
+pub fn check_interface_version(interface_version: u32) -> Result<()> {
+    if (MIN_SUPPORTED_INTERFACE_VERSION..=INTERFACE_VERSION).contains(&interface_version) {
+        Ok(())
+    } else {
+        Err(anyhow!(
+            "Interface version {interface_version} is not supported, expected {MIN_SUPPORTED_INTERFACE_VERSION} to {INTERFACE_VERSION}"
+        ))
+    }
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Eq, Hash, Clone, Copy, Default, Debug)]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub enum SwapMode {
     #[default]
     ExactIn,
@@ -36,25 +155,152 @@ impl FromStr for SwapMode {
     }
 }
 
-#[derive(Debug)]
+/// Compared and hashed without `budget`, a deadline does not change what is quoted
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct QuoteParams {
+    #[serde(with = "field_as_string")]
+    #[cfg_attr(feature = "schemars", schemars(with = "String"))]
     pub amount: u64,
+    #[serde(with = "field_as_string")]
+    #[cfg_attr(feature = "schemars", schemars(with = "String"))]
     pub input_mint: Pubkey,
+    #[serde(with = "field_as_string")]
+    #[cfg_attr(feature = "schemars", schemars(with = "String"))]
     pub output_mint: Pubkey,
     pub swap_mode: SwapMode,
+    /// Caps on the work the quote may do, Amms with unbounded loops should check it, see [`timeout`]
+    #[serde(skip)]
+    pub budget: Option<QuoteBudget>,
+    /// The worst marginal price, in output atoms per input atom, the Amm may fill at
+    /// Amms declaring [`AmmCapabilities::LIMIT_PRICE`] fill up to the limit and flag [`Quote::reached_limit`], others ignore it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub limit_price: Option<Decimal>,
+}
+
+impl PartialEq for QuoteParams {
+    fn eq(&self, other: &Self) -> bool {
+        self.amount == other.amount
+            && self.input_mint == other.input_mint
+            && self.output_mint == other.output_mint
+            && self.swap_mode == other.swap_mode
+            && self.limit_price == other.limit_price
+    }
 }
 
-#[derive(Debug, Default, Clone, Copy)]
+impl Eq for QuoteParams {}
+
+impl std::hash::Hash for QuoteParams {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.amount.hash(state);
+        self.input_mint.hash(state);
+        self.output_mint.hash(state);
+        self.swap_mode.hash(state);
+        self.limit_price.hash(state);
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct Quote {
+    #[serde(default, with = "option_field_as_string")]
+    #[cfg_attr(feature = "schemars", schemars(with = "Option<String>"))]
     pub min_in_amount: Option<u64>,
+    #[serde(default, with = "option_field_as_string")]
+    #[cfg_attr(feature = "schemars", schemars(with = "Option<String>"))]
     pub min_out_amount: Option<u64>,
+    /// For ExactOut, the in amount including the overshoot buffer the Amm needs to guarantee the out amount
+    #[serde(default, with = "option_field_as_string")]
+    #[cfg_attr(feature = "schemars", schemars(with = "Option<String>"))]
+    pub max_in_amount: Option<u64>,
+    #[serde(with = "field_as_string")]
+    #[cfg_attr(feature = "schemars", schemars(with = "String"))]
     pub in_amount: u64,
+    #[serde(with = "field_as_string")]
+    #[cfg_attr(feature = "schemars", schemars(with = "String"))]
     pub out_amount: u64,
+    #[serde(with = "field_as_string")]
+    #[cfg_attr(feature = "schemars", schemars(with = "String"))]
     pub fee_amount: u64,
+    #[serde(with = "field_as_string")]
+    #[cfg_attr(feature = "schemars", schemars(with = "String"))]
     pub fee_mint: Pubkey,
     pub fee_pct: Decimal,
+    /// The fee in `fee_mint`, negative for rebates and subsidized swaps, supersedes `fee_amount` when set
+    #[serde(default, with = "option_field_as_string")]
+    #[cfg_attr(feature = "schemars", schemars(with = "Option<String>"))]
+    pub fee_amount_signed: Option<i64>,
+    /// The mode quoted, `amount_specified` is an in amount for ExactIn and an out amount for ExactOut
+    #[serde(default)]
+    pub swap_mode: SwapMode,
+    /// The amount of the quote params, an ExactOut quote can produce more than it
+    #[serde(default, with = "field_as_string")]
+    #[cfg_attr(feature = "schemars", schemars(with = "String"))]
+    pub amount_specified: u64,
+    /// Soft conditions the Amm flags without failing the quote
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub warnings: Vec<QuoteWarning>,
+    /// The fill stopped at `QuoteParams::limit_price`, the amounts are those of the partial fill
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub reached_limit: bool,
+    /// The venue that filled the quote, for wrappers over another venue, see `Amm::underlying_liquidities`
+    #[serde(default, with = "option_field_as_string")]
+    #[cfg_attr(feature = "schemars", schemars(with = "Option<String>"))]
+    pub liquidity_source: Option<Pubkey>,
 }
 
+impl Quote {
+    /// The fee paid in `fee_mint`, negative when the venue pays a rebate
+    pub fn net_fee_amount(&self) -> i64 {
+        self.fee_amount_signed
+            .unwrap_or_else(|| i64::try_from(self.fee_amount).unwrap_or(i64::MAX))
+    }
+
+    /// The amount the Amm computed, `out_amount` for ExactIn and `in_amount` for ExactOut
+    pub fn amount_computed(&self) -> u64 {
+        match self.swap_mode {
+            SwapMode::ExactIn => self.out_amount,
+            SwapMode::ExactOut => self.in_amount,
+        }
+    }
+
+    /// The price of the input in output UI units, None when the quote has no in amount or the price overflows
+    pub fn price(&self, in_decimals: u8, out_decimals: u8) -> Option<Decimal> {
+        ui_price(self.out_amount, out_decimals, self.in_amount, in_decimals)
+    }
+
+    /// The price of the output in input UI units, None when the quote has no out amount or the price overflows
+    pub fn inverse_price(&self, in_decimals: u8, out_decimals: u8) -> Option<Decimal> {
+        ui_price(self.in_amount, in_decimals, self.out_amount, out_decimals)
+    }
+}
+
+/// `numerator / denominator` once both amounts are converted to UI units
+fn ui_price(
+    numerator: u64,
+    numerator_decimals: u8,
+    denominator: u64,
+    denominator_decimals: u8,
+) -> Option<Decimal> {
+    let price = Decimal::from(numerator).checked_div(Decimal::from(denominator))?;
+    if denominator_decimals >= numerator_decimals {
+        price.checked_mul(math::checked_pow_decimal(
+            Decimal::TEN,
+            u64::from(denominator_decimals - numerator_decimals),
+        )?)
+    } else {
+        price.checked_div(math::checked_pow_decimal(
+            Decimal::TEN,
+            u64::from(numerator_decimals - denominator_decimals),
+        )?)
+    }
+}
+
+/// The decimals of the known mints, shared by the host with every Amm
+pub type MintDecimalsMap = HashMap<Pubkey, u8, ahash::RandomState>;
+
 pub type QuoteMintToReferrer = HashMap<Pubkey, Pubkey, ahash::RandomState>;
 
 pub struct SwapParams<'a, 'b> {
@@ -67,12 +313,28 @@ pub struct SwapParams<'a, 'b> {
     pub destination_token_account: Pubkey,
     /// This can be the user or the program authority over the source_token_account.
     pub token_transfer_authority: Pubkey,
+    /// The wallet of the user, for PDAs derived from it, e.g. per user state or referral accounts
+    /// Unlike `token_transfer_authority`, never a program authority, None if unknown
+    pub user: Option<Pubkey>,
     pub open_order_address: Option<Pubkey>,
     pub quote_mint_to_referrer: Option<&'a QuoteMintToReferrer>,
     pub jupiter_program_id: &'b Pubkey,
-    /// Instead of returning the relevant Err, replace dynamic accounts with the default Pubkey
-    /// This is useful for crawling market with no tick array
+    /// How dynamic accounts that cannot be found are handled
+    pub missing_account_policy: MissingAccountPolicy,
+    /// Kept for adapters not migrated yet, hosts should set it to `missing_account_policy == DefaultPubkey`
+    #[deprecated(note = "Use missing_account_policy instead")]
     pub missing_dynamic_accounts_as_default: bool,
+    /// Provides the nonces of legs needing one, see [`SwapParams::next_bridge_stake_seed`]
+    pub nonce_provider: Option<&'a dyn SwapLegNonceProvider>,
+    /// Generates distinct placeholders per optional account slot, see [`SwapParams::placeholder_account_meta_for`]
+    pub placeholder_registry: Option<&'a PlaceholderRegistry>,
+    /// For ExactOut, the buffer on top of in_amount the leg may consume to guarantee out_amount
+    pub exact_out_overshoot_bps: Option<u16>,
+    /// The source is native SOL held by the token transfer authority, the router skipped wrapping it
+    /// Only set for Amms whose [`NativeSolPolicy`] allows it
+    pub is_native_source: bool,
+    /// The destination is native SOL credited to the token transfer authority, the router skips unwrapping it
+    pub is_native_destination: bool,
 }
 
 impl<'a, 'b> SwapParams<'a, 'b> {
@@ -81,11 +343,142 @@ impl<'a, 'b> SwapParams<'a, 'b> {
     pub fn placeholder_account_meta(&self) -> AccountMeta {
         AccountMeta::new_readonly(*self.jupiter_program_id, false)
     }
+
+    /// The placeholder of the optional account `slot`, distinct per slot when the host provides a registry
+    pub fn placeholder_account_meta_for(&self, slot: &str) -> AccountMeta {
+        match self.placeholder_registry {
+            Some(placeholder_registry) => {
+                AccountMeta::new_readonly(placeholder_registry.placeholder(slot), false)
+            }
+            None => self.placeholder_account_meta(),
+        }
+    }
+
+    /// The seed of the bridge stake account of StakeDex swaps, from the nonce provider
+    pub fn next_bridge_stake_seed(&self) -> Result<u32> {
+        self.nonce_provider
+            .map(|nonce_provider| nonce_provider.next_bridge_stake_seed())
+            .context("Missing nonce provider for the bridge stake seed")
+    }
 }
 
+/// The per-leg part of [`SwapParams`]
+#[derive(Debug, Clone, Copy)]
+pub struct SwapLegParams {
+    pub swap_mode: SwapMode,
+    pub in_amount: u64,
+    pub out_amount: u64,
+    pub source_mint: Pubkey,
+    pub destination_mint: Pubkey,
+    pub source_token_account: Pubkey,
+    pub destination_token_account: Pubkey,
+    pub open_order_address: Option<Pubkey>,
+    /// For ExactOut, the buffer on top of in_amount the leg may consume to guarantee out_amount
+    pub exact_out_overshoot_bps: Option<u16>,
+    /// The source is native SOL held by the token transfer authority, the router skipped wrapping it
+    /// Only set for Amms whose [`NativeSolPolicy`] allows it
+    pub is_native_source: bool,
+    /// The destination is native SOL credited to the token transfer authority, the router skips unwrapping it
+    pub is_native_destination: bool,
+}
+
+/// The transaction-wide part of [`SwapParams`], shared by reference across all legs of a route
+#[derive(Debug, Clone, Copy)]
+pub struct SwapContext<'a, 'b> {
+    /// This can be the user or the program authority over the source_token_account.
+    pub token_transfer_authority: Pubkey,
+    /// The wallet of the user, for PDAs derived from it, e.g. per user state or referral accounts
+    /// Unlike `token_transfer_authority`, never a program authority, None if unknown
+    pub user: Option<Pubkey>,
+    pub quote_mint_to_referrer: Option<&'a QuoteMintToReferrer>,
+    pub jupiter_program_id: &'b Pubkey,
+    /// How dynamic accounts that cannot be found are handled
+    pub missing_account_policy: MissingAccountPolicy,
+    /// Kept for adapters not migrated yet, hosts should set it to `missing_account_policy == DefaultPubkey`
+    #[deprecated(note = "Use missing_account_policy instead")]
+    pub missing_dynamic_accounts_as_default: bool,
+    /// Provides the nonces of legs needing one, see [`SwapParams::next_bridge_stake_seed`]
+    pub nonce_provider: Option<&'a dyn SwapLegNonceProvider>,
+    /// Generates distinct placeholders per optional account slot, see [`SwapParams::placeholder_account_meta_for`]
+    /// Registries are scoped to a leg, set the one of the leg before calling `swap_params`
+    pub placeholder_registry: Option<&'a PlaceholderRegistry>,
+}
+
+impl<'a, 'b> SwapContext<'a, 'b> {
+    /// Combines the transaction-wide context with a leg into the flat [`SwapParams`]
+    pub fn swap_params(&self, leg: &SwapLegParams) -> SwapParams<'a, 'b> {
+        SwapParams {
+            swap_mode: leg.swap_mode,
+            in_amount: leg.in_amount,
+            out_amount: leg.out_amount,
+            source_mint: leg.source_mint,
+            destination_mint: leg.destination_mint,
+            source_token_account: leg.source_token_account,
+            destination_token_account: leg.destination_token_account,
+            token_transfer_authority: self.token_transfer_authority,
+            user: self.user,
+            open_order_address: leg.open_order_address,
+            quote_mint_to_referrer: self.quote_mint_to_referrer,
+            jupiter_program_id: self.jupiter_program_id,
+            missing_account_policy: self.missing_account_policy,
+            #[allow(deprecated)]
+            missing_dynamic_accounts_as_default: self.missing_dynamic_accounts_as_default,
+            nonce_provider: self.nonce_provider,
+            placeholder_registry: self.placeholder_registry,
+            exact_out_overshoot_bps: leg.exact_out_overshoot_bps,
+            is_native_source: leg.is_native_source,
+            is_native_destination: leg.is_native_destination,
+        }
+    }
+}
+
+/// Inline capacity of [`AccountMetas`], swap legs rarely need more accounts
+pub const ACCOUNT_METAS_INLINE_CAPACITY: usize = 24;
+
+/// The account metas of a swap leg, stored inline up to [`ACCOUNT_METAS_INLINE_CAPACITY`] to avoid a heap allocation per leg
+pub type AccountMetas = SmallVec<[AccountMeta; ACCOUNT_METAS_INLINE_CAPACITY]>;
+
 pub struct SwapAndAccountMetas {
     pub swap: Swap,
-    pub account_metas: Vec<AccountMeta>,
+    pub account_metas: AccountMetas,
+    /// The programs this swap invokes besides `Amm::program_id`, e.g. the venue a meta aggregator picked
+    /// Must be a subset of `Amm::inner_program_ids`
+    pub cpi_program_ids: Vec<Pubkey>,
+}
+
+impl SwapAndAccountMetas {
+    /// A swap only invoking `Amm::program_id`
+    pub fn new(swap: Swap, account_metas: AccountMetas) -> Self {
+        SwapAndAccountMetas {
+            swap,
+            account_metas,
+            cpi_program_ids: vec![],
+        }
+    }
+
+    pub fn with_cpi_program_ids(mut self, cpi_program_ids: Vec<Pubkey>) -> Self {
+        self.cpi_program_ids = cpi_program_ids;
+        self
+    }
+
+    /// The programs the swap invokes, `amm` program id first, for the host to enforce its program allowlist at build time
+    /// Fails if a CPI program is not declared in `Amm::inner_program_ids`
+    pub fn invoked_program_ids<A: Amm + ?Sized>(&self, amm: &A) -> Result<Vec<Pubkey>> {
+        let inner_program_ids = amm.inner_program_ids();
+        let mut invoked_program_ids = vec![amm.program_id()];
+        for cpi_program_id in &self.cpi_program_ids {
+            if !inner_program_ids.contains(cpi_program_id) {
+                return Err(anyhow!(
+                    "{} invokes {cpi_program_id} which is not one of its inner program ids",
+                    amm.label()
+                ));
+            }
+            if !invoked_program_ids.contains(cpi_program_id) {
+                invoked_program_ids.push(*cpi_program_id);
+            }
+        }
+        Ok(invoked_program_ids)
+    }
 }
 
 /// Amm might trigger a setup step for the user
@@ -94,27 +487,149 @@ pub enum AmmUserSetup {
     SerumDexOpenOrdersSetup { market: Pubkey, program_id: Pubkey },
 }
 
+/// When a pool was created, as far as it can be derived from its state
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct PoolCreationInfo {
+    pub slot: Option<u64>,
+    pub unix_timestamp: Option<i64>,
+}
+
+impl PoolCreationInfo {
+    pub fn age_in_slots(&self, clock_ref: &ClockRef) -> Option<u64> {
+        self.slot.map(|slot| {
+            clock_ref
+                .slot
+                .load(std::sync::atomic::Ordering::Relaxed)
+                .saturating_sub(slot)
+        })
+    }
+
+    pub fn age_in_seconds(&self, clock_ref: &ClockRef) -> Option<i64> {
+        self.unix_timestamp.map(|unix_timestamp| {
+            clock_ref
+                .unix_timestamp
+                .load(std::sync::atomic::Ordering::Relaxed)
+                .saturating_sub(unix_timestamp)
+        })
+    }
+}
+
+/// Why an Amm is or is not trading, so hosts can re-check paused markets instead of delisting them
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ActivityStatus {
+    Active,
+    /// Temporarily not trading, the market should be re-checked later
+    Paused {
+        reason: Option<String>,
+    },
+    /// Not trading from the unix timestamp on
+    Expired {
+        at: i64,
+    },
+    /// Trading until the slot, not after
+    SunsetAfterSlot(u64),
+}
+
+impl ActivityStatus {
+    pub fn is_active(&self, clock_ref: &ClockRef) -> bool {
+        match self {
+            ActivityStatus::Active => true,
+            ActivityStatus::Paused { .. } => false,
+            ActivityStatus::Expired { at } => {
+                clock_ref
+                    .unix_timestamp
+                    .load(std::sync::atomic::Ordering::Relaxed)
+                    < *at
+            }
+            ActivityStatus::SunsetAfterSlot(slot) => {
+                clock_ref.slot.load(std::sync::atomic::Ordering::Relaxed) <= *slot
+            }
+        }
+    }
+}
+
+/// How important refreshing an account is, hosts under RPC pressure refresh lower priorities less often
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum UpdatePriority {
+    /// Can be refreshed on demand, e.g. far tick arrays
+    Lazy,
+    #[default]
+    Normal,
+    /// Quotes are wrong without a fresh copy, e.g. the pool state
+    Critical,
+}
+
+/// What the host should do about a pool whose accounts could not be fetched, see `Amm::on_accounts_missing`
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum MissingAccountsAction {
+    /// The accounts might be missing transiently, e.g. an RPC lagging behind
+    #[default]
+    Retry,
+    /// The accounts were closed for good, the host should stop polling the pool
+    Deactivate,
+    /// The pool moved to another address, e.g. a graduated bonding curve or a migrated market
+    ReplaceWith(Pubkey),
+}
+
+/// A program the Amm depends on, with what test infrastructure needs to clone the right build
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProgramDependency {
+    pub program_id: Pubkey,
+    pub name: String,
+    pub min_version: Option<semver::Version>,
+    pub feature_flags: Vec<String>,
+}
+
+impl From<(Pubkey, String)> for ProgramDependency {
+    fn from((program_id, name): (Pubkey, String)) -> Self {
+        ProgramDependency {
+            program_id,
+            name,
+            min_version: None,
+            feature_flags: vec![],
+        }
+    }
+}
+
 pub type AccountMap = HashMap<Pubkey, Account, ahash::RandomState>;
 
-pub fn try_get_account_data<'a>(account_map: &'a AccountMap, address: &Pubkey) -> Result<&'a [u8]> {
+pub fn try_get_account<'a>(account_map: &'a AccountMap, address: &Pubkey) -> Result<&'a Account> {
     account_map
         .get(address)
-        .map(|account| account.data.as_slice())
         .with_context(|| format!("Could not find address: {address}"))
 }
 
+pub fn try_get_account_data<'a>(account_map: &'a AccountMap, address: &Pubkey) -> Result<&'a [u8]> {
+    try_get_account(account_map, address).map(|account| account.data.as_slice())
+}
+
 pub fn try_get_account_data_and_owner<'a>(
     account_map: &'a AccountMap,
     address: &Pubkey,
 ) -> Result<(&'a [u8], &'a Pubkey)> {
-    let account = account_map
-        .get(address)
-        .with_context(|| format!("Could not find address: {address}"))?;
+    let account = try_get_account(account_map, address)?;
     Ok((account.data.as_slice(), &account.owner))
 }
 
+#[derive(Default, Clone)]
 pub struct AmmContext {
     pub clock_ref: ClockRef,
+    /// Sysvars beyond the clock, so Amms do not fetch them through `get_accounts_to_update` for every pool
+    pub sysvar_cache: SysvarCache,
+    /// Accounts shared across pools, hydrated once per cycle by the host and read by Amms during `update`
+    pub shared_accounts: SharedAccountCache,
+    /// The decimals of the known mints, when the host has them
+    pub mint_decimals: Option<Arc<MintDecimalsMap>>,
+}
+
+impl AmmContext {
+    /// A context around the host clock, with empty caches and no mint decimals
+    pub fn new(clock_ref: ClockRef) -> Self {
+        AmmContext {
+            clock_ref,
+            ..AmmContext::default()
+        }
+    }
 }
 
 pub trait Amm {
@@ -123,8 +638,26 @@ pub trait Amm {
     where
         Self: Sized;
 
+    /// Constructs many pools in one pass, DEXes whose pools share config accounts can override it to resolve them once
+    fn from_keyed_accounts(
+        keyed_accounts: &[KeyedAccount],
+        amm_context: &AmmContext,
+    ) -> Result<Vec<Self>>
+    where
+        Self: Sized,
+    {
+        keyed_accounts
+            .iter()
+            .map(|keyed_account| Self::from_keyed_account(keyed_account, amm_context))
+            .collect()
+    }
+
     /// A human readable label of the underlying DEX
     fn label(&self) -> String;
+    /// A stable identifier of the underlying DEX, prefer it over `label` for matching
+    fn dex_id(&self) -> DexId {
+        DexId::Other(self.label().into())
+    }
     fn program_id(&self) -> Pubkey;
     /// The pool state or market state address
     fn key(&self) -> Pubkey;
@@ -132,15 +665,311 @@ pub trait Amm {
     fn get_reserve_mints(&self) -> Vec<Pubkey>;
     /// The accounts necessary to produce a quote
     fn get_accounts_to_update(&self) -> Vec<Pubkey>;
+    /// The accounts necessary to produce a quote, along with how important refreshing each of them is
+    fn get_accounts_to_update_with_priority(&self) -> Vec<(Pubkey, UpdatePriority)> {
+        self.get_accounts_to_update()
+            .into_iter()
+            .map(|address| (address, UpdatePriority::Normal))
+            .collect()
+    }
+    /// The accounts necessary to produce any quote, fetched every update cycle
+    /// Together with `get_dynamic_accounts` it covers `get_accounts_to_update`
+    fn get_static_accounts(&self) -> Vec<Pubkey> {
+        self.get_accounts_to_update()
+    }
+    /// The accounts only necessary for some quotes, e.g. tick arrays in the quoted direction
+    /// Without a hint, all the dynamic accounts that might be needed should be returned
+    fn get_dynamic_accounts(&self, _hint: Option<&QuoteParams>) -> Vec<Pubkey> {
+        vec![]
+    }
+    /// The dynamic accounts quoting any amount of `input_mint` within the range will need
+    /// Lets the host prefetch them instead of discovering them through failed quotes
+    fn predict_accounts_for_amount_range(
+        &self,
+        _input_mint: &Pubkey,
+        _min_amount: u64,
+        _max_amount: u64,
+    ) -> Vec<Pubkey> {
+        self.get_dynamic_accounts(None)
+    }
     /// Picks necessary accounts to update it's internal state
     /// Heavy deserialization and precomputation caching should be done in this function
     fn update(&mut self, account_map: &AccountMap) -> Result<()>;
 
+    /// Updates knowing which accounts changed since the previous update, skipping it when none of ours did
+    fn update_with_diff(&mut self, account_map: &AccountMap, diff: &AccountMapDiff) -> Result<()> {
+        if !diff.affects(&self.get_accounts_to_update()) {
+            return Ok(());
+        }
+        self.update(account_map)
+    }
+
+    /// The accounts to fetch for the next update, letting hosts pipeline fetching across Amms
+    /// The plan id defaults to `state_version`, so plans made before a later update are detected as stale
+    fn plan_update(&self) -> UpdatePlan {
+        UpdatePlan {
+            plan_id: self.state_version(),
+            accounts: self.get_accounts_to_update(),
+            deadline_slot: None,
+        }
+    }
+
+    /// Applies the accounts fetched for the plan `plan_id`, failing if the plan is stale
+    /// `fetched` can lack some accounts of the plan, see [`UpdatePlan::missing_accounts`]
+    fn apply_update(&mut self, fetched: &AccountMap, plan_id: u64) -> Result<()> {
+        let state_version = self.state_version();
+        if plan_id != state_version {
+            return Err(anyhow!(
+                "Update plan {plan_id} is stale, the state version is {state_version}"
+            ));
+        }
+        self.update(fetched)
+    }
+
+    /// Updates through a shared reference, for Amms keeping their state behind a lock
+    /// Lets hosts refresh the Amm without exclusive ownership, Amms implementing it declare `AmmCapabilities::SHARED_UPDATE`
+    fn update_shared(&self, _account_map: &AccountMap) -> Result<()> {
+        Err(anyhow!("{} does not support shared updates", self.label()))
+    }
+
+    /// Failures with a known reason should be returned as a [`QuoteError`]
     fn quote(&self, quote_params: &QuoteParams) -> Result<Quote>;
 
+    /// Quotes into `quote`, for hot loops laddering many amounts to reuse a single Quote
+    /// `quote` is left untouched on error
+    fn quote_into(&self, quote_params: &QuoteParams, quote: &mut Quote) -> Result<()> {
+        *quote = self.quote(quote_params)?;
+        Ok(())
+    }
+
+    /// Quotes with the trade capped by the user current balance of the input mint, e.g. to "swap max"
+    /// ExactIn amounts above the balance are quoted for the balance, ExactOut quotes needing more than the balance fail
+    /// with [`QuoteError::InsufficientBalance`], an unknown balance leaves the quote uncapped
+    fn quote_with_current_token_balance(
+        &self,
+        quote_params: &QuoteParams,
+        token_balances: &dyn TokenBalanceSource,
+    ) -> Result<Quote> {
+        let Some(balance) = token_balances.token_balance(&quote_params.input_mint) else {
+            return self.quote(quote_params);
+        };
+        match quote_params.swap_mode {
+            SwapMode::ExactIn => self.quote(&QuoteParams {
+                amount: quote_params.amount.min(balance),
+                ..quote_params.clone()
+            }),
+            SwapMode::ExactOut => {
+                let quote = self.quote(quote_params)?;
+                if quote.max_in_amount.unwrap_or(quote.in_amount) > balance {
+                    return Err(QuoteError::InsufficientBalance { balance }.into());
+                }
+                Ok(quote)
+            }
+        }
+    }
+
+    /// The smallest in amount of `input_mint` that can be traded, smaller amounts can be rejected without quoting
+    fn min_in_amount(&self, _input_mint: &Pubkey) -> Option<u64> {
+        None
+    }
+
+    /// The smallest out amount of `output_mint` that can be produced
+    fn min_out_amount(&self, _output_mint: &Pubkey) -> Option<u64> {
+        None
+    }
+
+    /// The largest in amount of `input_mint` the pool accepts, e.g. a vault deposit cap
+    /// Lets the splitter allocate flow across venues without quoting amounts bound to fail
+    fn max_in_amount(&self, _input_mint: &Pubkey) -> Option<u64> {
+        None
+    }
+
+    /// The largest out amount of `output_mint` the pool can produce, e.g. what is left on a launch curve
+    fn max_out_amount(&self, _output_mint: &Pubkey) -> Option<u64> {
+        None
+    }
+
+    /// A key identifying the quote for the current state, quotes with the same key and `state_version` are identical
+    /// None indicates the quote cannot be cached
+    fn quote_cache_key(&self, _quote_params: &QuoteParams) -> Option<u64> {
+        None
+    }
+
+    /// The slot of the state the last `update` applied, to tell stale state from math bugs behind a bad quote
+    /// See [`UpdateStamp`] to record it
+    fn last_update_slot(&self) -> Option<u64> {
+        None
+    }
+
+    /// Bumped by `update` whenever the state affecting quotes changed
+    fn state_version(&self) -> u64 {
+        0
+    }
+
+    /// The state derived by `update`, encoded deterministically so hosts can warm restart without fetching the accounts to update
+    fn snapshot_state(&self) -> Option<Vec<u8>> {
+        None
+    }
+
+    /// Restores a `snapshot_state` onto an Amm built by `from_keyed_account` from the same pool account
+    fn restore_state(&mut self, _bytes: &[u8]) -> Result<()> {
+        Err(anyhow!("{} does not support restoring state", self.label()))
+    }
+
+    /// The upcoming or current window during which the host should not route through the Amm
+    /// e.g. stake pool swaps invalid around epoch boundaries, see [`BlackoutWindow::around_epoch_end`]
+    fn quoting_blackout(&self, _clock_ref: &ClockRef) -> Option<BlackoutWindow> {
+        None
+    }
+
+    /// Indicates if quotes change with the clock alone, e.g. decaying fees or launch curves
+    /// Defaults to true so hosts do not reuse quotes of Amms not declaring it
+    fn quote_depends_on_clock(&self) -> bool {
+        true
+    }
+
+    /// How many slots a quote remains valid for without any account update, None if unbounded
+    fn max_quote_age_slots(&self) -> Option<u64> {
+        None
+    }
+
+    /// The rate `quote` may be called at, for Amms backed by external market makers, enforced by the host
+    fn quote_rate_limit(&self) -> Option<RateLimit> {
+        None
+    }
+
+    /// Guidance for the router on where and how often to use the Amm in routes
+    fn routing_hints(&self) -> RoutingHints {
+        RoutingHints::default()
+    }
+
+    /// The quote mints the venue settles against and the destination account it requires, None if unconstrained
+    fn settlement_constraints(&self) -> Option<SettlementConstraints> {
+        None
+    }
+
+    /// Requirements on how the swap is executed, e.g. bundles or last look quotes from market makers
+    fn execution_constraints(&self) -> ExecutionConstraints {
+        ExecutionConstraints {
+            max_quote_to_execution_slots: self.max_quote_age_slots(),
+            ..ExecutionConstraints::default()
+        }
+    }
+
     /// Indicates which Swap has to be performed along with all the necessary account metas
     fn get_swap_and_account_metas(&self, swap_params: &SwapParams) -> Result<SwapAndAccountMetas>;
 
+    /// The instructions the transaction builder has to place around the route instruction for this leg
+    fn get_adjacent_instructions(&self, _swap_params: &SwapParams) -> Result<AdjacentInstructions> {
+        Ok(AdjacentInstructions::default())
+    }
+
+    /// Same as `get_swap_and_account_metas` with the transaction-wide context split from the leg
+    fn get_swap_and_account_metas_v2(
+        &self,
+        leg: &SwapLegParams,
+        ctx: &SwapContext,
+    ) -> Result<SwapAndAccountMetas> {
+        self.get_swap_and_account_metas(&ctx.swap_params(leg))
+    }
+
+    /// The accounts the swap write locks, to estimate contention across candidate routes before building transactions
+    fn writable_accounts(&self, swap_params: &SwapParams) -> Result<Vec<Pubkey>> {
+        let mut writable_accounts: Vec<Pubkey> = Vec::new();
+        for account_meta in self.get_swap_and_account_metas(swap_params)?.account_metas {
+            if account_meta.is_writable && !writable_accounts.contains(&account_meta.pubkey) {
+                writable_accounts.push(account_meta.pubkey);
+            }
+        }
+        Ok(writable_accounts)
+    }
+
+    /// The role of each account meta of `get_swap_and_account_metas`, in the same order
+    /// Roles are positional, the Amm orders the vaults for the swap direction so `VaultIn` always receives the in amount
+    /// An empty vec indicates the Amm does not declare them
+    fn get_swap_account_roles(&self) -> Vec<AccountRole> {
+        vec![]
+    }
+
+    /// Quotes depositing or withdrawing a single token, e.g. for zap in and zap out products
+    fn quote_liquidity(&self, _liquidity_params: &LiquidityParams) -> Result<LiquidityQuote> {
+        Err(anyhow!("{} does not support liquidity legs", self.label()))
+    }
+
+    /// The liquidity leg as a swap from `mint` to the LP mint for a deposit, from the LP mint to `mint` for a withdrawal
+    /// e.g. `Swap::PerpsV2AddLiquidity` or `Swap::SanctumSAddLiquidity`
+    fn get_liquidity_ix_account_metas(
+        &self,
+        _liquidity_params: &LiquidityParams,
+        _swap_params: &SwapParams,
+    ) -> Result<SwapAndAccountMetas> {
+        Err(anyhow!("{} does not support liquidity legs", self.label()))
+    }
+
+    /// The alternative ways of filling the leg, e.g. different tick array windows
+    /// The transaction builder picks the one fitting its compute and size budgets
+    fn get_swap_and_account_metas_candidates(
+        &self,
+        swap_params: &SwapParams,
+    ) -> Result<Vec<SwapAndAccountMetas>> {
+        Ok(vec![self.get_swap_and_account_metas(swap_params)?])
+    }
+
+    /// The user token accounts the swap needs, so routers can create them ahead of the route
+    /// An empty vec indicates the Amm does not declare them
+    fn get_user_token_accounts(
+        &self,
+        _swap_params: &SwapParams,
+    ) -> Result<Vec<RequiredTokenAccount>> {
+        Ok(vec![])
+    }
+
+    /// Extra fields recorded on the spans of `tracing_utils`, e.g. the pool tick spacing or curve kind
+    fn tracing_fields(&self) -> Vec<(&'static str, String)> {
+        vec![]
+    }
+
+    /// The pool parameters in a normalized shape, for pool listings comparing pools across DEXes
+    fn get_pool_metadata(&self) -> Option<PoolMetadata> {
+        None
+    }
+
+    /// The fees of the pool, for fee comparison and route scoring without quoting
+    fn fee_schedule(&self) -> Option<FeeSchedule> {
+        None
+    }
+
+    /// The fee in bps a swap from `input_mint` pays now, for display and route scoring without quoting
+    /// Defaults to the base fee of a `fee_schedule` without dynamic fee, Amms with dynamic fees should override it
+    fn current_fee_bps(&self, _input_mint: &Pubkey, _amount_hint: Option<u64>) -> Option<Decimal> {
+        self.fee_schedule()
+            .filter(|fee_schedule| fee_schedule.dynamic_fee.is_none())
+            .map(|fee_schedule| Decimal::from(fee_schedule.base_fee_bps))
+    }
+
+    /// The mint fees of a swap from `input_mint` to `output_mint` are taken in, before quoting
+    /// Lets the host provision the referrer token account of `QuoteMintToReferrer`, None when unknown
+    fn fee_mint_for(&self, _input_mint: &Pubkey, _output_mint: &Pubkey) -> Option<Pubkey> {
+        None
+    }
+
+    /// How the swap instruction handles native SOL, i.e. if the router may skip wrapping and unwrapping it
+    fn accepts_native_sol(&self) -> NativeSolPolicy {
+        NativeSolPolicy::RequiresWsol
+    }
+
+    /// Indicates if the quote already nets Token-2022 transfer fees of the traded mints
+    /// Otherwise the host has to adjust the quote, see [`token2022::adjust_quote_for_transfer_fees`]
+    fn handles_token2022_fees(&self) -> bool {
+        false
+    }
+
+    /// The oracle accounts quotes depend on, the host prioritizes their freshness
+    /// They are expected to be part of get_accounts_to_update as well
+    fn get_oracle_requirements(&self) -> Vec<OracleRequirement> {
+        vec![]
+    }
+
     /// Indicates if get_accounts_to_update might return a non constant vec
     fn has_dynamic_accounts(&self) -> bool {
         false
@@ -160,18 +989,69 @@ pub trait Amm {
         None
     }
 
+    /// The setup steps and the user specific accounts swapping through the Amm needs for `user`
+    /// Defaults to the single step of `get_user_setup`
+    fn get_user_setup_v2(&self, _user: &Pubkey) -> UserSetupPlan {
+        self.get_user_setup()
+            .map(UserSetupPlan::from)
+            .unwrap_or_default()
+    }
+
     fn clone_amm(&self) -> Box<dyn Amm + Send + Sync>;
 
+    /// The interface version the adapter was compiled against, should not be overridden
+    fn interface_version(&self) -> u32 {
+        INTERFACE_VERSION
+    }
+
     /// It can only trade in one direction from its first mint to second mint, assuming it is a two mint AMM
     fn unidirectional(&self) -> bool {
         false
     }
 
+    /// The predicates of the Amm as flags, for hosts to cache instead of calling each predicate per routing cycle
+    /// Defaults to the individual predicates, `TOKEN_2022`, `SHARED_UPDATE` and `LIMIT_PRICE` have to be declared
+    fn capabilities(&self) -> AmmCapabilities {
+        let mut capabilities = AmmCapabilities::empty();
+        capabilities.set(AmmCapabilities::EXACT_OUT, self.supports_exact_out());
+        capabilities.set(
+            AmmCapabilities::DYNAMIC_ACCOUNTS,
+            self.has_dynamic_accounts(),
+        );
+        capabilities.set(AmmCapabilities::UNIDIRECTIONAL, self.unidirectional());
+        capabilities.set(
+            AmmCapabilities::NEEDS_USER_SETUP,
+            self.get_user_setup().is_some(),
+        );
+        capabilities.set(
+            AmmCapabilities::CLOCK_SENSITIVE,
+            self.quote_depends_on_clock(),
+        );
+        capabilities.set(AmmCapabilities::RFQ, self.execution_constraints().last_look);
+        capabilities
+    }
+
     /// For testing purposes, provide a mapping of dependency programs to function
+    #[deprecated(note = "Implement get_program_dependencies instead")]
     fn program_dependencies(&self) -> Vec<(Pubkey, String)> {
         vec![]
     }
 
+    /// The programs an Amm routing through other venues may invoke, empty when it only invokes `program_id`
+    /// Each swap names the ones it actually invokes in `SwapAndAccountMetas::cpi_program_ids`
+    fn inner_program_ids(&self) -> Vec<Pubkey> {
+        vec![]
+    }
+
+    /// For testing purposes, provide the dependency programs along with their version constraints
+    fn get_program_dependencies(&self) -> Vec<ProgramDependency> {
+        #[allow(deprecated)]
+        self.program_dependencies()
+            .into_iter()
+            .map(ProgramDependency::from)
+            .collect()
+    }
+
     fn get_accounts_len(&self) -> usize {
         32 // Default to a near whole legacy transaction to penalize no implementation
     }
@@ -185,11 +1065,52 @@ pub trait Amm {
         None
     }
 
+    /// The reserves of each mint held by the pool, None if the state does not track them
+    fn get_tvl_in_reserve_terms(&self) -> Option<Vec<(Pubkey, u64)>> {
+        None
+    }
+
+    /// The cumulative volume traded of each mint, for Amms whose state tracks volume counters
+    fn get_cumulative_volume(&self) -> Option<Vec<(Pubkey, u128)>> {
+        None
+    }
+
+    /// When the pool was created, None if it cannot be derived from the state
+    fn created_at(&self) -> Option<PoolCreationInfo> {
+        None
+    }
+
     /// Provides a shortcut to establish if the AMM can be used for trading
     /// If the market is active at all
     fn is_active(&self) -> bool {
         true
     }
+
+    /// Extends `is_active` with the reason the market is not trading
+    fn activity_status(&self) -> ActivityStatus {
+        if self.is_active() {
+            ActivityStatus::Active
+        } else {
+            ActivityStatus::Paused { reason: None }
+        }
+    }
+
+    /// How the host should handle consecutive `update` failures, the host default applies otherwise
+    fn update_failure_policy(&self) -> FailurePolicy {
+        FailurePolicy::default()
+    }
+
+    /// Called instead of `update` when some accounts to update could not be fetched
+    /// Amms able to tell that their accounts were closed should deactivate or point to their replacement
+    fn on_accounts_missing(&mut self, _missing: &[Pubkey]) -> MissingAccountsAction {
+        MissingAccountsAction::Retry
+    }
+
+    /// The pool this one graduated or migrated into, e.g. a bonding curve completing into an AMM pool
+    /// Hosts onboard the successor as soon as it is reported, the market params are those its Amm expects
+    fn successor_market(&self) -> Option<Market> {
+        None
+    }
 }
 
 impl Clone for Box<dyn Amm + Send + Sync> {
@@ -205,17 +1126,72 @@ pub struct KeyedAccount {
     pub params: Option<Value>,
 }
 
+impl KeyedAccount {
+    /// Decodes the params into `T`, e.g. [`TypedParams`] or an Amm specific struct
+    pub fn decode_params<T: DeserializeOwned>(&self) -> Result<T> {
+        decode_params(&self.key, self.params.as_ref())
+    }
+}
+
+fn decode_params<T: DeserializeOwned>(key: &Pubkey, params: Option<&Value>) -> Result<T> {
+    let params = params.with_context(|| format!("{key} has no params"))?;
+    T::deserialize(params).with_context(|| format!("Invalid params for {key}"))
+}
+
+fn market_version() -> u8 {
+    1
+}
+
+/// Any version is read as a `Market`, which then only holds the fields of version 1
+fn deserialize_market_version<'de, D: serde::Deserializer<'de>>(
+    deserializer: D,
+) -> Result<u8, D::Error> {
+    u8::deserialize(deserializer)?;
+    Ok(market_version())
+}
+
+fn market_v2_version() -> u8 {
+    2
+}
+
+/// A market of the market cache files, `version` defaults to 1 for files written before it existed
+///
+/// Newer versions can be read as a `Market`, dropping the fields it does not know about and normalizing `version` to 1
 #[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
 #[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub struct Market {
+    #[serde(
+        default = "market_version",
+        deserialize_with = "deserialize_market_version"
+    )]
+    pub version: u8,
     #[serde(with = "field_as_string")]
+    #[cfg_attr(feature = "schemars", schemars(with = "String"))]
     pub pubkey: Pubkey,
     #[serde(with = "field_as_string")]
+    #[cfg_attr(feature = "schemars", schemars(with = "String"))]
     pub owner: Pubkey,
     /// Additional data an Amm requires, Amm dependent and decoded in the Amm implementation
     pub params: Option<Value>,
 }
 
+impl Market {
+    pub fn new(pubkey: Pubkey, owner: Pubkey, params: Option<Value>) -> Self {
+        Market {
+            version: market_version(),
+            pubkey,
+            owner,
+            params,
+        }
+    }
+
+    /// Decodes the params into `T`, e.g. [`TypedParams`] or an Amm specific struct
+    pub fn decode_params<T: DeserializeOwned>(&self) -> Result<T> {
+        decode_params(&self.pubkey, self.params.as_ref())
+    }
+}
+
 impl From<KeyedAccount> for Market {
     fn from(
         KeyedAccount {
@@ -224,14 +1200,67 @@ impl From<KeyedAccount> for Market {
             params,
         }: KeyedAccount,
     ) -> Self {
+        Market::new(key, account.owner, params)
+    }
+}
+
+/// A [`Market`] also carrying its mints and their token programs, so hosts can resolve them without loading the Amm
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct MarketV2 {
+    #[serde(default = "market_v2_version")]
+    pub version: u8,
+    #[serde(with = "field_as_string")]
+    #[cfg_attr(feature = "schemars", schemars(with = "String"))]
+    pub pubkey: Pubkey,
+    #[serde(with = "field_as_string")]
+    #[cfg_attr(feature = "schemars", schemars(with = "String"))]
+    pub owner: Pubkey,
+    #[serde(default, with = "vec_field_as_string")]
+    #[cfg_attr(feature = "schemars", schemars(with = "Vec<String>"))]
+    pub mints: Vec<Pubkey>,
+    /// The token program of each mint, in the same order
+    #[serde(default, with = "vec_field_as_string")]
+    #[cfg_attr(feature = "schemars", schemars(with = "Vec<String>"))]
+    pub token_programs: Vec<Pubkey>,
+    /// Additional data an Amm requires, Amm dependent and decoded in the Amm implementation
+    pub params: Option<Value>,
+}
+
+impl From<Market> for MarketV2 {
+    fn from(
         Market {
-            pubkey: key,
-            owner: account.owner,
+            pubkey,
+            owner,
+            params,
+            ..
+        }: Market,
+    ) -> Self {
+        MarketV2 {
+            version: market_v2_version(),
+            pubkey,
+            owner,
+            mints: vec![],
+            token_programs: vec![],
             params,
         }
     }
 }
 
+impl From<MarketV2> for Market {
+    fn from(
+        MarketV2 {
+            pubkey,
+            owner,
+            params,
+            ..
+        }: MarketV2,
+    ) -> Self {
+        Market::new(pubkey, owner, params)
+    }
+}
+
 #[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
 pub struct KeyedUiAccount {
     pub pubkey: String,
@@ -241,14 +1270,18 @@ pub struct KeyedUiAccount {
     pub params: Option<Value>,
 }
 
-impl From<KeyedAccount> for KeyedUiAccount {
-    fn from(keyed_account: KeyedAccount) -> Self {
+impl KeyedUiAccount {
+    /// Encodes the account data with `encoding`, `UiAccountEncoding::Base64Zstd` allows compressed snapshot files
+    pub fn from_keyed_account_with_encoding(
+        keyed_account: KeyedAccount,
+        encoding: UiAccountEncoding,
+    ) -> Self {
         let KeyedAccount {
             key,
             account,
             params,
         } = keyed_account;
-        let ui_account = UiAccount::encode(&key, &account, UiAccountEncoding::Base64, None, None);
+        let ui_account = UiAccount::encode(&key, &account, encoding, None, None);
 
         KeyedUiAccount {
             pubkey: key.to_string(),
@@ -258,6 +1291,12 @@ impl From<KeyedAccount> for KeyedUiAccount {
     }
 }
 
+impl From<KeyedAccount> for KeyedUiAccount {
+    fn from(keyed_account: KeyedAccount) -> Self {
+        KeyedUiAccount::from_keyed_account_with_encoding(keyed_account, UiAccountEncoding::Base64)
+    }
+}
+
 impl TryFrom<KeyedUiAccount> for KeyedAccount {
     type Error = Error;
 
@@ -269,7 +1308,7 @@ impl TryFrom<KeyedUiAccount> for KeyedAccount {
         } = keyed_ui_account;
         let account = ui_account
             .decode()
-            .unwrap_or_else(|| panic!("Failed to decode ui_account for {}", pubkey));
+            .with_context(|| format!("Failed to decode ui_account for {pubkey}"))?;
 
         Ok(KeyedAccount {
             key: Pubkey::from_str(&pubkey)?,
@@ -279,6 +1318,10 @@ impl TryFrom<KeyedUiAccount> for KeyedAccount {
     }
 }
 
+/// The clock refreshed by the host and shared by every Amm, clones share the same clock
+///
+/// The host shared state, e.g. [`SysvarCache`], [`SharedAccountCache`] or [`RateLimiter`], is only ever written whole
+/// under its lock, so locks poisoned by a panicking thread are recovered rather than propagated
 #[derive(Default, Clone)]
 pub struct ClockRef {
     pub slot: Arc<AtomicU64>,
@@ -324,8 +1367,205 @@ impl From<Clock> for ClockRef {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use solana_account_decoder::UiAccountData;
     use solana_sdk::pubkey;
 
+    #[test]
+    fn test_quote_serde_roundtrip() {
+        let quote = Quote {
+            min_in_amount: None,
+            min_out_amount: Some(990),
+            max_in_amount: None,
+            in_amount: 1000,
+            out_amount: 995,
+            fee_amount: 3,
+            fee_mint: pubkey!("EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v"),
+            fee_pct: Decimal::new(3, 3),
+            fee_amount_signed: None,
+            swap_mode: SwapMode::ExactOut,
+            amount_specified: 990,
+            warnings: vec![QuoteWarning::StaleOracle { slots: 25 }],
+            reached_limit: true,
+            liquidity_source: Some(pubkey!("DezXAZ8z7PnrnRJjz3wXBoRgixCa6xjnB7YaB1pPB263")),
+        };
+        let json = serde_json::to_value(&quote).unwrap();
+        assert_eq!(json["minOutAmount"], "990");
+        assert_eq!(
+            json["warnings"],
+            serde_json::json!([{ "type": "staleOracle", "slots": 25 }])
+        );
+        assert_eq!(json["inAmount"], "1000");
+        assert_eq!(json["reachedLimit"], true);
+        assert_eq!(json["swapMode"], "ExactOut");
+        assert_eq!(json["amountSpecified"], "990");
+        assert_eq!(
+            json["liquiditySource"],
+            "DezXAZ8z7PnrnRJjz3wXBoRgixCa6xjnB7YaB1pPB263"
+        );
+        assert_eq!(
+            json["feeMint"],
+            "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v"
+        );
+        assert_eq!(serde_json::from_value::<Quote>(json).unwrap(), quote);
+    }
+
+    #[test]
+    fn test_quote_amount_computed() {
+        let quote = Quote {
+            in_amount: 1_000,
+            out_amount: 995,
+            swap_mode: SwapMode::ExactIn,
+            amount_specified: 1_000,
+            ..Quote::default()
+        };
+        assert_eq!(quote.amount_computed(), 995);
+        // The out amount of an ExactOut quote can overshoot the amount specified
+        let quote = Quote {
+            swap_mode: SwapMode::ExactOut,
+            amount_specified: 990,
+            ..quote
+        };
+        assert_eq!(quote.amount_computed(), 1_000);
+        assert!(quote.out_amount > quote.amount_specified);
+    }
+
+    #[test]
+    fn test_quote_params_eq_ignores_budget() {
+        let quote_params = QuoteParams {
+            amount: 1_000,
+            input_mint: Pubkey::new_unique(),
+            output_mint: Pubkey::new_unique(),
+            ..QuoteParams::default()
+        };
+        let budgeted_quote_params = QuoteParams {
+            budget: Some(QuoteBudget {
+                deadline: Some(std::time::Instant::now()),
+                ..QuoteBudget::default()
+            }),
+            ..quote_params.clone()
+        };
+        assert_eq!(quote_params, budgeted_quote_params);
+        let hasher = ahash::RandomState::new();
+        assert_eq!(
+            hasher.hash_one(&quote_params),
+            hasher.hash_one(&budgeted_quote_params)
+        );
+        assert_ne!(
+            quote_params,
+            QuoteParams {
+                limit_price: Some(Decimal::ONE),
+                ..quote_params.clone()
+            }
+        );
+    }
+
+    #[test]
+    fn test_invoked_program_ids() {
+        let venue_program_id = Pubkey::new_unique();
+        let amm = reference::MockAmm::new(Pubkey::new_unique(), vec![])
+            .with_inner_program_ids(vec![venue_program_id]);
+        let swap_and_account_metas = SwapAndAccountMetas::new(Swap::TokenSwap, AccountMetas::new());
+        assert_eq!(
+            swap_and_account_metas.invoked_program_ids(&amm).unwrap(),
+            vec![amm.program_id()]
+        );
+        let swap_and_account_metas =
+            swap_and_account_metas.with_cpi_program_ids(vec![venue_program_id]);
+        assert_eq!(
+            swap_and_account_metas.invoked_program_ids(&amm).unwrap(),
+            vec![amm.program_id(), venue_program_id]
+        );
+
+        let undeclared_swap_and_account_metas =
+            SwapAndAccountMetas::new(Swap::TokenSwap, AccountMetas::new())
+                .with_cpi_program_ids(vec![Pubkey::new_unique()]);
+        assert!(undeclared_swap_and_account_metas
+            .invoked_program_ids(&amm)
+            .is_err());
+    }
+
+    #[test]
+    fn test_quote_with_current_token_balance() {
+        let usdc = Pubkey::new_unique();
+        let sol = Pubkey::new_unique();
+        let amm = reference::MockAmm::new(Pubkey::new_unique(), vec![usdc, sol]).with_price(
+            sol,
+            usdc,
+            Decimal::new(1505, 1),
+        );
+        let token_balances = HashMap::from([(sol, 1_000)]);
+
+        let quote_params = QuoteParams {
+            amount: 2_000,
+            input_mint: sol,
+            output_mint: usdc,
+            swap_mode: SwapMode::ExactIn,
+            budget: None,
+            limit_price: None,
+        };
+        let quote = amm
+            .quote_with_current_token_balance(&quote_params, &token_balances)
+            .unwrap();
+        assert_eq!((quote.in_amount, quote.out_amount), (1_000, 150_500));
+        let quote = amm
+            .quote_with_current_token_balance(&quote_params, &HashMap::new())
+            .unwrap();
+        assert_eq!(quote.in_amount, 2_000);
+
+        let quote_params = QuoteParams {
+            amount: 301_000,
+            swap_mode: SwapMode::ExactOut,
+            ..quote_params
+        };
+        let error = amm
+            .quote_with_current_token_balance(&quote_params, &token_balances)
+            .unwrap_err();
+        assert_eq!(
+            error.downcast_ref::<QuoteError>(),
+            Some(&QuoteError::InsufficientBalance { balance: 1_000 })
+        );
+    }
+
+    #[test]
+    fn test_quote_price() {
+        // 2 SOL for 400 USDC
+        let quote = Quote {
+            in_amount: 2_000_000_000,
+            out_amount: 400_000_000,
+            ..Quote::default()
+        };
+        assert_eq!(quote.price(9, 6), Some(Decimal::from(200)));
+        assert_eq!(quote.inverse_price(9, 6), Some(Decimal::new(5, 3)));
+        assert_eq!(Quote::default().price(9, 6), None);
+    }
+
+    #[test]
+    fn test_keyed_ui_account_zstd_roundtrip() {
+        let keyed_account = KeyedAccount {
+            key: pubkey!("DezXAZ8z7PnrnRJjz3wXBoRgixCa6xjnB7YaB1pPB263"),
+            account: Account {
+                lamports: 1000,
+                data: vec![7; 512],
+                owner: pubkey!("EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v"),
+                executable: false,
+                rent_epoch: 0,
+            },
+            params: None,
+        };
+        let keyed_ui_account = KeyedUiAccount::from_keyed_account_with_encoding(
+            keyed_account.clone(),
+            UiAccountEncoding::Base64Zstd,
+        );
+        let decoded = KeyedAccount::try_from(keyed_ui_account).unwrap();
+        assert_eq!(decoded.key, keyed_account.key);
+        assert_eq!(decoded.account, keyed_account.account);
+
+        let mut invalid_ui_account = KeyedUiAccount::from(keyed_account);
+        invalid_ui_account.ui_account.data =
+            UiAccountData::Binary("not base64".to_string(), UiAccountEncoding::Base64);
+        assert!(KeyedAccount::try_from(invalid_ui_account).is_err());
+    }
+
     #[test]
     fn test_market_deserialization() {
         let json = r#"
@@ -346,5 +1586,47 @@ mod tests {
             market.pubkey,
             pubkey!("DezXAZ8z7PnrnRJjz3wXBoRgixCa6xjnB7YaB1pPB263")
         );
+        assert_eq!(market.version, 1);
+    }
+
+    #[test]
+    fn test_market_v2_read_by_older_readers() {
+        let market_v2 = MarketV2 {
+            mints: vec![pubkey!("EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v")],
+            token_programs: vec![SPL_TOKEN_PROGRAM_ID],
+            ..MarketV2::from(Market::new(
+                pubkey!("DezXAZ8z7PnrnRJjz3wXBoRgixCa6xjnB7YaB1pPB263"),
+                pubkey!("EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v"),
+                None,
+            ))
+        };
+        let json = serde_json::to_string(&market_v2).unwrap();
+        assert!(json.contains(r#""version":2"#));
+        assert!(json.contains(r#""tokenPrograms":["TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA"]"#));
+
+        let market: Market = serde_json::from_str(&json).unwrap();
+        assert_eq!(market, Market::from(market_v2.clone()));
+        assert!(serde_json::to_string(&market)
+            .unwrap()
+            .contains(r#""version":1"#));
+        assert_eq!(serde_json::from_str::<MarketV2>(&json).unwrap(), market_v2);
+    }
+
+    #[test]
+    fn test_quote_net_fee_amount() {
+        let quote = Quote {
+            fee_amount: 3,
+            ..Quote::default()
+        };
+        assert_eq!(quote.net_fee_amount(), 3);
+
+        let rebate_quote = Quote {
+            fee_amount_signed: Some(-5),
+            ..Quote::default()
+        };
+        assert_eq!(rebate_quote.net_fee_amount(), -5);
+        let json = serde_json::to_value(&rebate_quote).unwrap();
+        assert_eq!(json["feeAmountSigned"], "-5");
+        assert_eq!(serde_json::from_value::<Quote>(json).unwrap(), rebate_quote);
     }
 }
@@ -0,0 +1,28 @@
+use solana_sdk::pubkey::Pubkey;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LiquidityAction {
+    /// Deposits `amount` of `mint` for LP tokens
+    Deposit,
+    /// Burns `amount` LP tokens for `mint`
+    Withdraw,
+}
+
+/// A zap in or zap out of a single token, see `Amm::quote_liquidity`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct LiquidityParams {
+    pub action: LiquidityAction,
+    pub mint: Pubkey,
+    pub amount: u64,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct LiquidityQuote {
+    pub lp_mint: Pubkey,
+    /// `mint` for a deposit, LP tokens for a withdrawal
+    pub in_amount: u64,
+    /// LP tokens for a deposit, `mint` for a withdrawal
+    pub out_amount: u64,
+    pub fee_amount: u64,
+    pub fee_mint: Pubkey,
+}
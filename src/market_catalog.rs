@@ -0,0 +1,99 @@
+//! Market catalogs as zstd compressed JSON Lines, one [`Market`] per line
+use std::{
+    fs::File,
+    io::{BufRead, BufReader, BufWriter, Lines, Write},
+    path::Path,
+};
+
+use anyhow::{Context, Result};
+
+use crate::Market;
+
+const ZSTD_LEVEL: i32 = 3;
+
+pub fn write(path: impl AsRef<Path>, markets: &[Market]) -> Result<()> {
+    let path = path.as_ref();
+    let file =
+        File::create(path).with_context(|| format!("Failed to create {}", path.display()))?;
+    write_to(BufWriter::new(file), markets)
+}
+
+pub fn write_to(writer: impl Write, markets: &[Market]) -> Result<()> {
+    let mut encoder = zstd::Encoder::new(writer, ZSTD_LEVEL)?;
+    for market in markets {
+        serde_json::to_writer(&mut encoder, market)?;
+        encoder.write_all(b"\n")?;
+    }
+    encoder.finish()?.flush()?;
+    Ok(())
+}
+
+/// Streams the markets of the catalog at `path`, without loading the whole catalog in memory
+pub fn read(path: impl AsRef<Path>) -> Result<MarketCatalogReader<BufReader<File>>> {
+    let path = path.as_ref();
+    let file = File::open(path).with_context(|| format!("Failed to open {}", path.display()))?;
+    read_from(BufReader::new(file))
+}
+
+pub fn read_from<R: BufRead>(reader: R) -> Result<MarketCatalogReader<R>> {
+    Ok(MarketCatalogReader {
+        lines: BufReader::new(zstd::Decoder::with_buffer(reader)?).lines(),
+        line_number: 0,
+    })
+}
+
+/// An iterator over the markets of a catalog, see [`read`]
+pub struct MarketCatalogReader<R: BufRead> {
+    lines: Lines<BufReader<zstd::Decoder<'static, R>>>,
+    line_number: usize,
+}
+
+impl<R: BufRead> Iterator for MarketCatalogReader<R> {
+    type Item = Result<Market>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let line = self.lines.next()?;
+            self.line_number += 1;
+            let line_number = self.line_number;
+            match line {
+                Ok(line) if line.trim().is_empty() => continue,
+                Ok(line) => {
+                    return Some(
+                        serde_json::from_str(&line)
+                            .with_context(|| format!("Invalid market on line {line_number}")),
+                    )
+                }
+                Err(e) => return Some(Err(e.into())),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use solana_sdk::pubkey::Pubkey;
+
+    #[test]
+    fn test_market_catalog_roundtrip() {
+        let markets = (0..3)
+            .map(|index| {
+                Market::new(
+                    Pubkey::new_unique(),
+                    Pubkey::new_unique(),
+                    Some(json!({ "index": index })),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        let mut catalog = vec![];
+        write_to(&mut catalog, &markets).unwrap();
+        let read_markets = read_from(catalog.as_slice())
+            .unwrap()
+            .collect::<Result<Vec<_>>>()
+            .unwrap();
+        assert_eq!(read_markets, markets);
+    }
+}
@@ -0,0 +1,229 @@
+pub mod stableswap;
+
+use anyhow::{ensure, Result};
+use rust_decimal::Decimal;
+
+use crate::{Amm, Quote, QuoteBudget, QuoteParams, SwapMode};
+
+#[allow(
+    clippy::assign_op_pattern,
+    clippy::manual_div_ceil,
+    clippy::ptr_offset_with_cast,
+    clippy::manual_range_contains
+)]
+mod u256 {
+    uint::construct_uint! {
+        pub struct U256(4);
+    }
+}
+pub(crate) use u256::U256;
+
+/// `a * b / denominator` rounded down, None on division by zero or if the result does not fit in a u64
+pub fn mul_div_floor(a: u64, b: u64, denominator: u64) -> Option<u64> {
+    if denominator == 0 {
+        return None;
+    }
+    let result = u128::from(a) * u128::from(b) / u128::from(denominator);
+    u64::try_from(result).ok()
+}
+
+/// `a * b / denominator` rounded up, None on division by zero or if the result does not fit in a u64
+pub fn mul_div_ceil(a: u64, b: u64, denominator: u64) -> Option<u64> {
+    if denominator == 0 {
+        return None;
+    }
+    let result = (u128::from(a) * u128::from(b)).div_ceil(u128::from(denominator));
+    u64::try_from(result).ok()
+}
+
+/// `base` raised to `exponent` by squaring, None on overflow
+pub fn checked_pow_decimal(base: Decimal, mut exponent: u64) -> Option<Decimal> {
+    let mut result = Decimal::ONE;
+    let mut base = base;
+    while exponent > 0 {
+        if exponent & 1 == 1 {
+            result = result.checked_mul(base)?;
+        }
+        exponent >>= 1;
+        if exponent > 0 {
+            base = base.checked_mul(base)?;
+        }
+    }
+    Some(result)
+}
+
+/// The integer square root of `n`, rounded down
+pub fn sqrt_u128(n: u128) -> u128 {
+    if n < 2 {
+        return n;
+    }
+    // Newton iteration from an initial guess above the root, decreasing monotonically
+    let mut x = 1u128 << ((128 - n.leading_zeros()).div_ceil(2));
+    loop {
+        let y = (x + n / x) / 2;
+        if y >= x {
+            return x;
+        }
+        x = y;
+    }
+}
+
+/// Bounds the number of ExactIn quotes `exact_out_via_exact_in` performs, enough to search the whole u64 range
+const MAX_EXACT_OUT_ITERATIONS: u32 = 128;
+
+/// Quotes ExactOut on an Amm only supporting ExactIn, by searching the smallest in amount yielding the requested out amount
+///
+/// The search stops once the in amount is known within `tolerance`, the returned in amount always yields at least the requested out amount
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(skip_all, fields(amm.label = %amm.label(), amm.key = %amm.key(), quote.amount = quote_params.amount))
+)]
+pub fn exact_out_via_exact_in<A: Amm + ?Sized>(
+    amm: &A,
+    quote_params: &QuoteParams,
+    tolerance: u64,
+) -> Result<Quote> {
+    ensure!(
+        quote_params.swap_mode == SwapMode::ExactOut,
+        "Expected ExactOut quote params"
+    );
+    let quote_exact_in = |in_amount| {
+        amm.quote(&QuoteParams {
+            amount: in_amount,
+            swap_mode: SwapMode::ExactIn,
+            ..quote_params.clone()
+        })
+    };
+    let budget = quote_params.budget.unwrap_or_default();
+    let in_amount = search_min_in_amount(quote_params.amount, tolerance, &budget, |in_amount| {
+        quote_exact_in(in_amount).map(|quote| quote.out_amount)
+    })?;
+    Ok(Quote {
+        swap_mode: SwapMode::ExactOut,
+        amount_specified: quote_params.amount,
+        ..quote_exact_in(in_amount)?
+    })
+}
+
+/// Finds an in amount within `tolerance` of the smallest one for which `out_amount_for` reaches `out_amount`
+fn search_min_in_amount(
+    out_amount: u64,
+    tolerance: u64,
+    budget: &QuoteBudget,
+    mut out_amount_for: impl FnMut(u64) -> Result<u64>,
+) -> Result<u64> {
+    let mut iterations = 0;
+    let mut reaches = |in_amount| -> Result<bool> {
+        iterations += 1;
+        ensure!(
+            iterations <= MAX_EXACT_OUT_ITERATIONS,
+            "Exceeded {MAX_EXACT_OUT_ITERATIONS} iterations"
+        );
+        budget.check_iterations(iterations)?;
+        // An in amount the Amm fails to quote is treated as not reaching the out amount
+        Ok(out_amount_for(in_amount).is_ok_and(|amount| amount >= out_amount))
+    };
+
+    // Exponential search for an upper bound, then bisection
+    let mut low = 0;
+    let mut high = out_amount.max(1);
+    while !reaches(high)? {
+        ensure!(
+            high < u64::MAX,
+            "No in amount can produce out amount {out_amount}"
+        );
+        low = high;
+        high = high.saturating_mul(2);
+    }
+    while high - low > tolerance.max(1) {
+        let middle = low + (high - low) / 2;
+        if reaches(middle)? {
+            high = middle;
+        } else {
+            low = middle;
+        }
+    }
+    Ok(high)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::QuoteError;
+
+    #[test]
+    fn test_mul_div() {
+        assert_eq!(mul_div_floor(10, 10, 3), Some(33));
+        assert_eq!(mul_div_ceil(10, 10, 3), Some(34));
+        assert_eq!(mul_div_ceil(10, 9, 3), Some(30));
+        assert_eq!(mul_div_floor(u64::MAX, u64::MAX, u64::MAX), Some(u64::MAX));
+        assert_eq!(mul_div_ceil(u64::MAX, u64::MAX, u64::MAX), Some(u64::MAX));
+        assert_eq!(mul_div_floor(u64::MAX, 2, 1), None);
+        assert_eq!(mul_div_ceil(u64::MAX, u64::MAX, u64::MAX - 1), None);
+        assert_eq!(mul_div_floor(1, 1, 0), None);
+        assert_eq!(mul_div_ceil(1, 1, 0), None);
+        assert_eq!(mul_div_floor(0, u64::MAX, 1), Some(0));
+    }
+
+    #[test]
+    fn test_checked_pow_decimal() {
+        assert_eq!(
+            checked_pow_decimal(Decimal::new(15, 1), 0),
+            Some(Decimal::ONE)
+        );
+        assert_eq!(
+            checked_pow_decimal(Decimal::new(15, 1), 3),
+            Some(Decimal::new(3375, 3))
+        );
+        assert_eq!(
+            checked_pow_decimal(Decimal::TEN, 28),
+            Some(Decimal::from_i128_with_scale(10i128.pow(28), 0))
+        );
+        assert_eq!(checked_pow_decimal(Decimal::TEN, 29), None);
+        assert_eq!(checked_pow_decimal(Decimal::MAX, 2), None);
+    }
+
+    #[test]
+    fn test_sqrt_u128() {
+        for n in 0..10_000u128 {
+            let root = sqrt_u128(n);
+            assert!(root * root <= n && (root + 1) * (root + 1) > n);
+        }
+        assert_eq!(sqrt_u128(u128::MAX), u64::MAX as u128);
+        assert_eq!(
+            sqrt_u128(u128::from(u64::MAX) * u128::from(u64::MAX)),
+            u64::MAX as u128
+        );
+        assert_eq!(
+            sqrt_u128(u128::from(u64::MAX) * u128::from(u64::MAX) - 1),
+            u64::MAX as u128 - 1
+        );
+    }
+
+    #[test]
+    fn test_search_min_in_amount() {
+        // 0.3% fee and a 2:1 price
+        let out_amount_for = |in_amount: u64| Ok(in_amount * 2 * 997 / 1000);
+
+        let budget = QuoteBudget::default();
+        let in_amount = search_min_in_amount(1_000_000, 0, &budget, out_amount_for).unwrap();
+        assert!(out_amount_for(in_amount).unwrap() >= 1_000_000);
+        assert!(out_amount_for(in_amount - 1).unwrap() < 1_000_000);
+
+        let in_amount = search_min_in_amount(1_000_000, 100, &budget, out_amount_for).unwrap();
+        assert!(out_amount_for(in_amount).unwrap() >= 1_000_000);
+        assert!(out_amount_for(in_amount - 100).unwrap() < 1_000_000);
+
+        assert!(search_min_in_amount(1, 0, &budget, |_| Ok(0)).is_err());
+
+        let budget = QuoteBudget {
+            max_iterations: Some(4),
+            ..QuoteBudget::default()
+        };
+        let error = search_min_in_amount(1_000_000, 0, &budget, out_amount_for).unwrap_err();
+        assert_eq!(
+            error.downcast_ref::<QuoteError>(),
+            Some(&QuoteError::BudgetExhausted)
+        );
+    }
+}
@@ -0,0 +1,181 @@
+//! Curve StableSwap invariant solvers, see https://curve.fi/files/stableswap-paper.pdf
+//!
+//! `amp` is the amplification coefficient A, without precision multiplier
+use anyhow::{anyhow, ensure, Result};
+
+use super::U256;
+
+/// Newton iterations converge within a handful of steps for sane pools, bounded to fail instead of looping
+const MAX_ITERATIONS: usize = 256;
+
+fn abs_diff(a: U256, b: U256) -> U256 {
+    if a > b {
+        a - b
+    } else {
+        b - a
+    }
+}
+
+fn to_u128(value: U256) -> Result<u128> {
+    ensure!(value.bits() <= 128, "Overflow");
+    Ok(value.as_u128())
+}
+
+fn add(a: U256, b: U256) -> Result<U256> {
+    a.checked_add(b).ok_or_else(|| anyhow!("Overflow"))
+}
+
+fn sub(a: U256, b: U256) -> Result<U256> {
+    a.checked_sub(b).ok_or_else(|| anyhow!("Underflow"))
+}
+
+fn mul(a: U256, b: U256) -> Result<U256> {
+    a.checked_mul(b).ok_or_else(|| anyhow!("Overflow"))
+}
+
+fn div(a: U256, b: U256) -> Result<U256> {
+    a.checked_div(b).ok_or_else(|| anyhow!("Division by zero"))
+}
+
+/// `amp * n^n`, the amplification coefficient as used by the invariant
+fn ann(amp: u64, n_coins: U256) -> Result<U256> {
+    let n_pow_n = n_coins
+        .checked_pow(n_coins)
+        .ok_or_else(|| anyhow!("Overflow"))?;
+    mul(U256::from(amp), n_pow_n)
+}
+
+/// The invariant D of `balances`, all normalized to the same decimals
+pub fn compute_d(balances: &[u128], amp: u64) -> Result<u128> {
+    let n_coins = U256::from(balances.len());
+    ensure!(balances.len() >= 2, "At least two coins are required");
+    ensure!(amp > 0, "Amp must be positive");
+    let sum = balances
+        .iter()
+        .try_fold(U256::zero(), |sum, balance| add(sum, U256::from(*balance)))?;
+    if sum.is_zero() {
+        return Ok(0);
+    }
+    ensure!(
+        balances.iter().all(|balance| *balance > 0),
+        "Balances must all be positive"
+    );
+
+    let ann = ann(amp, n_coins)?;
+    let mut d = sum;
+    for _ in 0..MAX_ITERATIONS {
+        let mut d_p = d;
+        for balance in balances {
+            d_p = div(mul(d_p, d)?, mul(U256::from(*balance), n_coins)?)?;
+        }
+        let d_previous = d;
+        let numerator = mul(add(mul(ann, sum)?, mul(d_p, n_coins)?)?, d)?;
+        let denominator = add(
+            mul(sub(ann, U256::one())?, d)?,
+            mul(add(n_coins, U256::one())?, d_p)?,
+        )?;
+        d = div(numerator, denominator)?;
+        if abs_diff(d, d_previous) <= U256::one() {
+            return to_u128(d);
+        }
+    }
+    Err(anyhow!("D did not converge"))
+}
+
+/// The balance of coin `j` keeping the invariant once the balance of coin `i` is `x`
+///
+/// The amount out of a swap is the current balance of `j` minus the returned balance
+pub fn compute_y(i: usize, j: usize, x: u128, balances: &[u128], amp: u64) -> Result<u128> {
+    ensure!(i != j, "Coins must differ");
+    ensure!(
+        i < balances.len() && j < balances.len(),
+        "Coin out of range"
+    );
+    ensure!(x > 0, "Balance must be positive");
+    let n_coins = U256::from(balances.len());
+    let d = U256::from(compute_d(balances, amp)?);
+    let ann = ann(amp, n_coins)?;
+
+    let mut c = d;
+    let mut sum = U256::zero();
+    for (k, balance) in balances.iter().enumerate() {
+        let balance = if k == i {
+            U256::from(x)
+        } else if k != j {
+            U256::from(*balance)
+        } else {
+            continue;
+        };
+        sum = add(sum, balance)?;
+        c = div(mul(c, d)?, mul(balance, n_coins)?)?;
+    }
+    c = div(mul(c, d)?, mul(ann, n_coins)?)?;
+    let b = add(sum, div(d, ann)?)?;
+
+    let mut y = d;
+    for _ in 0..MAX_ITERATIONS {
+        let y_previous = y;
+        y = div(
+            add(mul(y, y)?, c)?,
+            sub(add(mul(U256::from(2), y)?, b)?, d)?,
+        )?;
+        if abs_diff(y, y_previous) <= U256::one() {
+            return to_u128(y);
+        }
+    }
+    Err(anyhow!("y did not converge"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Expected values are computed with a port of get_D and get_y of Curve's StableSwap3Pool.vy, which stores
+    // `amp` as A * n^(n-1) and uses `Ann = amp * n`, both equal to `amp * n^n` here
+
+    #[test]
+    fn test_compute_d() {
+        // A balanced pool has D equal to the sum of its balances
+        assert_eq!(
+            compute_d(&[1_000_000_000_000, 1_000_000_000_000], 100).unwrap(),
+            2_000_000_000_000
+        );
+        assert_eq!(
+            compute_d(&[1_000_000_000_000, 500_000_000_000], 100).unwrap(),
+            1_499_534_015_561
+        );
+        assert_eq!(
+            compute_d(
+                &[10u128.pow(18), 2 * 10u128.pow(18), 3 * 10u128.pow(18)],
+                2_000
+            )
+            .unwrap(),
+            5_999_962_965_934_798_528
+        );
+        assert_eq!(compute_d(&[100, 1_000_000_000], 10).unwrap(), 24_992_064);
+        assert_eq!(compute_d(&[0, 0], 100).unwrap(), 0);
+        assert!(compute_d(&[0, 100], 100).is_err());
+        // Overflows the intermediate products instead of panicking
+        assert!(compute_d(&[1 << 120, 1 << 120], 1_000_000).is_err());
+        assert!(compute_d(&[u128::MAX, u128::MAX], u64::MAX).is_err());
+    }
+
+    #[test]
+    fn test_compute_y() {
+        let balances = [1_000_000_000_000, 500_000_000_000];
+        assert_eq!(
+            compute_y(0, 1, balances[0] + 1_000_000_000, &balances, 100).unwrap(),
+            499_004_192_042
+        );
+        let balances = [10u128.pow(18), 2 * 10u128.pow(18), 3 * 10u128.pow(18)];
+        assert_eq!(
+            compute_y(2, 0, balances[2] + 10u128.pow(17), &balances, 2_000).unwrap(),
+            900_011_148_687_341_860
+        );
+        // Leaving the balances unchanged keeps the balance of j, within the Newton tolerance
+        let y = compute_y(0, 1, balances[0], &balances, 2_000).unwrap();
+        assert!(y.abs_diff(balances[1]) <= 1);
+
+        assert!(compute_y(0, 1, u128::MAX, &[1 << 120, 1 << 120], 1_000_000).is_err());
+    }
+}
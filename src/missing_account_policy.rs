@@ -0,0 +1,106 @@
+use anyhow::{anyhow, Result};
+use solana_sdk::pubkey::Pubkey;
+
+/// How `get_swap_and_account_metas` handles dynamic accounts it cannot find, e.g. uninitialized tick arrays
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum MissingAccountPolicy {
+    /// Return the relevant Err, for production swaps
+    #[default]
+    Error,
+    /// Replace the account with the default Pubkey, useful for crawling markets with no tick array
+    DefaultPubkey,
+    /// Leave the account out of the account metas, for simulators tolerating shorter account lists
+    /// Only applies to trailing accounts, positional accounts cannot be left out, see `resolve_positional`
+    Skip,
+    /// Replace the account with the given address, e.g. the jupiter program id as placeholder
+    Placeholder(Pubkey),
+}
+
+impl MissingAccountPolicy {
+    #[deprecated(note = "Use MissingAccountPolicy::DefaultPubkey or MissingAccountPolicy::Error")]
+    pub fn from_missing_dynamic_accounts_as_default(
+        missing_dynamic_accounts_as_default: bool,
+    ) -> Self {
+        if missing_dynamic_accounts_as_default {
+            MissingAccountPolicy::DefaultPubkey
+        } else {
+            MissingAccountPolicy::Error
+        }
+    }
+
+    /// The address to use for a trailing dynamic account, None when it is to be skipped
+    pub fn resolve(&self, address: Option<Pubkey>, description: &str) -> Result<Option<Pubkey>> {
+        if address.is_some() {
+            return Ok(address);
+        }
+        match self {
+            MissingAccountPolicy::Error => Err(anyhow!("Missing {description}")),
+            MissingAccountPolicy::DefaultPubkey => Ok(Some(Pubkey::default())),
+            MissingAccountPolicy::Skip => Ok(None),
+            MissingAccountPolicy::Placeholder(placeholder) => Ok(Some(*placeholder)),
+        }
+    }
+
+    /// The address to use for a dynamic account at a fixed position, skipping it would shift the following accounts
+    pub fn resolve_positional(&self, address: Option<Pubkey>, description: &str) -> Result<Pubkey> {
+        self.resolve(address, description)?
+            .ok_or_else(|| anyhow!("Missing {description}, positional accounts cannot be skipped"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve() {
+        let address = Pubkey::new_unique();
+        let placeholder = Pubkey::new_unique();
+        for policy in [
+            MissingAccountPolicy::Error,
+            MissingAccountPolicy::DefaultPubkey,
+            MissingAccountPolicy::Skip,
+            MissingAccountPolicy::Placeholder(placeholder),
+        ] {
+            assert_eq!(
+                policy.resolve(Some(address), "tick array").unwrap(),
+                Some(address)
+            );
+            assert_eq!(
+                policy
+                    .resolve_positional(Some(address), "tick array")
+                    .unwrap(),
+                address
+            );
+        }
+
+        assert_eq!(
+            MissingAccountPolicy::Error
+                .resolve(None, "tick array")
+                .unwrap_err()
+                .to_string(),
+            "Missing tick array"
+        );
+        assert_eq!(
+            MissingAccountPolicy::DefaultPubkey
+                .resolve(None, "tick array")
+                .unwrap(),
+            Some(Pubkey::default())
+        );
+        assert_eq!(
+            MissingAccountPolicy::Skip
+                .resolve(None, "tick array")
+                .unwrap(),
+            None
+        );
+        assert!(MissingAccountPolicy::Skip
+            .resolve_positional(None, "tick array")
+            .is_err());
+        assert_eq!(
+            MissingAccountPolicy::Placeholder(placeholder)
+                .resolve_positional(None, "tick array")
+                .unwrap(),
+            placeholder
+        );
+    }
+}
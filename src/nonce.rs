@@ -0,0 +1,31 @@
+use std::{
+    fmt::Debug,
+    sync::atomic::{AtomicU32, Ordering},
+};
+
+/// The channel through which adapters request the nonces of swap legs, e.g. `bridge_stake_seed` of StakeDex swaps
+///
+/// Hosts must not hand out the same nonce to two legs of a transaction
+pub trait SwapLegNonceProvider: Debug {
+    fn next_bridge_stake_seed(&self) -> u32;
+}
+
+/// Hands out consecutive nonces, deterministic for tests
+#[derive(Debug, Default)]
+pub struct SequentialNonceProvider {
+    next: AtomicU32,
+}
+
+impl SequentialNonceProvider {
+    pub fn new(start: u32) -> Self {
+        SequentialNonceProvider {
+            next: AtomicU32::new(start),
+        }
+    }
+}
+
+impl SwapLegNonceProvider for SequentialNonceProvider {
+    fn next_bridge_stake_seed(&self) -> u32 {
+        self.next.fetch_add(1, Ordering::Relaxed)
+    }
+}
@@ -0,0 +1,28 @@
+use solana_sdk::pubkey::Pubkey;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OracleKind {
+    Pyth,
+    PythPull,
+    Switchboard,
+    SwitchboardOnDemand,
+    /// An oracle specific to the DEX, named by the adapter
+    Custom(String),
+}
+
+/// An oracle account quotes depend on
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OracleRequirement {
+    pub account: Pubkey,
+    pub kind: OracleKind,
+    /// Quotes are stale once the oracle was last updated this many slots ago
+    pub max_staleness_slots: Option<u64>,
+}
+
+impl OracleRequirement {
+    pub fn is_stale(&self, oracle_update_slot: u64, current_slot: u64) -> bool {
+        self.max_staleness_slots.is_some_and(|max_staleness_slots| {
+            current_slot.saturating_sub(oracle_update_slot) > max_staleness_slots
+        })
+    }
+}
@@ -0,0 +1,299 @@
+//! Walking an L2 order book for Amms backed by a central limit order book
+//!
+//! Prices are in quote atoms per base atom, sizes in base atoms and the taker fee is charged on the quote notional
+use std::cmp::Reverse;
+
+use anyhow::{anyhow, ensure, Result};
+use rust_decimal::{prelude::ToPrimitive, Decimal};
+use solana_sdk::pubkey::Pubkey;
+
+use crate::{Quote, QuoteError, Side, SwapMode};
+
+const BPS_DENOMINATOR: u64 = 10_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Level {
+    pub price: Decimal,
+    pub size: u64,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct L2Book {
+    /// Sorted from the highest price
+    pub bids: Vec<Level>,
+    /// Sorted from the lowest price
+    pub asks: Vec<Level>,
+}
+
+/// The amounts of a taker order walking the book, `Side::Bid` buys base with quote and `Side::Ask` sells base for quote
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Fill {
+    pub in_amount: u64,
+    pub out_amount: u64,
+    /// Always in quote atoms
+    pub fee_amount: u64,
+}
+
+impl L2Book {
+    pub fn new(mut bids: Vec<Level>, mut asks: Vec<Level>) -> Self {
+        bids.sort_by_key(|level| Reverse(level.price));
+        asks.sort_by_key(|level| level.price);
+        L2Book { bids, asks }
+    }
+
+    fn depth(&self, side: Side) -> &[Level] {
+        match side {
+            Side::Bid => &self.asks,
+            Side::Ask => &self.bids,
+        }
+    }
+
+    /// The quote notional and base taken from the book until `amount` is exhausted, None if the book is exhausted first
+    ///
+    /// `amount` is in base atoms when `walk_base`, in quote atoms otherwise
+    fn walk(&self, side: Side, amount: u64, walk_base: bool, round_up: bool) -> Option<(u64, u64)> {
+        let mut remaining = Decimal::from(amount);
+        let mut base = Decimal::ZERO;
+        let mut notional = Decimal::ZERO;
+        for level in self.depth(side) {
+            if remaining.is_zero() {
+                break;
+            }
+            let size = Decimal::from(level.size);
+            let level_notional = level.price.checked_mul(size)?;
+            let (taken_base, taken_notional) = if walk_base {
+                let taken_base = remaining.min(size);
+                (taken_base, level.price.checked_mul(taken_base)?)
+            } else if remaining >= level_notional {
+                (size, level_notional)
+            } else {
+                let taken_base = remaining.checked_div(level.price)?;
+                let taken_base = if round_up {
+                    taken_base.ceil()
+                } else {
+                    taken_base.floor()
+                };
+                (taken_base, remaining)
+            };
+            remaining -= if walk_base {
+                taken_base
+            } else {
+                taken_notional
+            };
+            base += taken_base;
+            notional += taken_notional;
+        }
+        if !remaining.is_zero() {
+            return None;
+        }
+        let notional = if round_up {
+            notional.ceil()
+        } else {
+            notional.floor()
+        };
+        Some((notional.to_u64()?, base.to_u64()?))
+    }
+
+    /// Walks the book with `in_amount`, base for `Side::Ask` and quote for `Side::Bid`
+    pub fn fill_exact_in(&self, side: Side, in_amount: u64, taker_fee_bps: u16) -> Result<Fill> {
+        let fee_bps = checked_fee_bps(taker_fee_bps)?;
+        match side {
+            Side::Bid => {
+                let notional = u128::from(in_amount) * u128::from(BPS_DENOMINATOR)
+                    / u128::from(BPS_DENOMINATOR + fee_bps);
+                let notional = notional as u64;
+                let (_, out_amount) = self
+                    .walk(side, notional, false, false)
+                    .ok_or(QuoteError::InsufficientLiquidity { max_in: None })?;
+                Ok(Fill {
+                    in_amount,
+                    out_amount,
+                    fee_amount: in_amount - notional,
+                })
+            }
+            Side::Ask => {
+                let (notional, _) = self
+                    .walk(side, in_amount, true, false)
+                    .ok_or(QuoteError::InsufficientLiquidity { max_in: None })?;
+                let fee_amount = fee_of(notional, fee_bps);
+                Ok(Fill {
+                    in_amount,
+                    out_amount: notional - fee_amount,
+                    fee_amount,
+                })
+            }
+        }
+    }
+
+    /// Walks the book until `out_amount` is produced, quote for `Side::Ask` and base for `Side::Bid`
+    pub fn fill_exact_out(&self, side: Side, out_amount: u64, taker_fee_bps: u16) -> Result<Fill> {
+        let fee_bps = checked_fee_bps(taker_fee_bps)?;
+        match side {
+            Side::Bid => {
+                let (notional, _) = self
+                    .walk(side, out_amount, true, true)
+                    .ok_or(QuoteError::InsufficientLiquidity { max_in: None })?;
+                let fee_amount = fee_of(notional, fee_bps);
+                Ok(Fill {
+                    in_amount: notional
+                        .checked_add(fee_amount)
+                        .ok_or_else(|| anyhow!("In amount overflow"))?,
+                    out_amount,
+                    fee_amount,
+                })
+            }
+            Side::Ask => {
+                let notional = (u128::from(out_amount) * u128::from(BPS_DENOMINATOR))
+                    .div_ceil(u128::from(BPS_DENOMINATOR - fee_bps));
+                let notional = u64::try_from(notional)?;
+                let (_, in_amount) = self
+                    .walk(side, notional, false, true)
+                    .ok_or(QuoteError::InsufficientLiquidity { max_in: None })?;
+                Ok(Fill {
+                    in_amount,
+                    out_amount,
+                    fee_amount: notional - out_amount,
+                })
+            }
+        }
+    }
+}
+
+/// The taker fee has to leave part of the notional, otherwise no amount can be produced
+fn checked_fee_bps(taker_fee_bps: u16) -> Result<u64> {
+    let fee_bps = u64::from(taker_fee_bps);
+    ensure!(
+        fee_bps < BPS_DENOMINATOR,
+        "Taker fee of {taker_fee_bps} bps is not below {BPS_DENOMINATOR} bps"
+    );
+    Ok(fee_bps)
+}
+
+fn fee_of(notional: u64, fee_bps: u64) -> u64 {
+    (u128::from(notional) * u128::from(fee_bps)).div_ceil(u128::from(BPS_DENOMINATOR)) as u64
+}
+
+impl Fill {
+    /// The quote of the fill, `amount_specified` being the amount the book was walked with
+    pub fn to_quote(
+        &self,
+        swap_mode: SwapMode,
+        amount_specified: u64,
+        quote_mint: Pubkey,
+        taker_fee_bps: u16,
+    ) -> Quote {
+        Quote {
+            in_amount: self.in_amount,
+            out_amount: self.out_amount,
+            fee_amount: self.fee_amount,
+            fee_mint: quote_mint,
+            fee_pct: Decimal::new(i64::from(taker_fee_bps), 4),
+            swap_mode,
+            amount_specified,
+            ..Quote::default()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn book() -> L2Book {
+        L2Book::new(
+            vec![
+                Level {
+                    price: Decimal::from(99),
+                    size: 10,
+                },
+                Level {
+                    price: Decimal::from(100),
+                    size: 5,
+                },
+            ],
+            vec![
+                Level {
+                    price: Decimal::from(102),
+                    size: 10,
+                },
+                Level {
+                    price: Decimal::from(101),
+                    size: 5,
+                },
+            ],
+        )
+    }
+
+    #[test]
+    fn test_fill_exact_in() {
+        let book = book();
+        // 5 at 101 and 2 at 102, plus a 10 bps fee
+        assert_eq!(
+            book.fill_exact_in(Side::Bid, 709, 10).unwrap(),
+            Fill {
+                in_amount: 709,
+                out_amount: 6,
+                fee_amount: 1,
+            }
+        );
+        // 5 at 100 and 2 at 99
+        assert_eq!(
+            book.fill_exact_in(Side::Ask, 7, 0).unwrap(),
+            Fill {
+                in_amount: 7,
+                out_amount: 698,
+                fee_amount: 0,
+            }
+        );
+        assert_eq!(
+            book.fill_exact_in(Side::Ask, 16, 0)
+                .unwrap_err()
+                .downcast_ref::<QuoteError>(),
+            Some(&QuoteError::InsufficientLiquidity { max_in: None })
+        );
+    }
+
+    #[test]
+    fn test_fill_rejects_taker_fee_above_notional() {
+        let book = book();
+        assert!(book.fill_exact_in(Side::Ask, 7, 10_000).is_err());
+        assert!(book.fill_exact_in(Side::Bid, 1_000, u16::MAX).is_err());
+        assert!(book.fill_exact_out(Side::Ask, 700, 10_000).is_err());
+    }
+
+    #[test]
+    fn test_fill_exact_out() {
+        let book = book();
+        // 5 at 101 and 2 at 102, plus a 10 bps fee rounded up
+        let fill = book.fill_exact_out(Side::Bid, 7, 10).unwrap();
+        assert_eq!(
+            fill,
+            Fill {
+                in_amount: 710,
+                out_amount: 7,
+                fee_amount: 1,
+            }
+        );
+        let quote_mint = Pubkey::new_unique();
+        let quote = fill.to_quote(SwapMode::ExactOut, 7, quote_mint, 10);
+        assert_eq!(
+            (
+                quote.swap_mode,
+                quote.amount_specified,
+                quote.amount_computed()
+            ),
+            (SwapMode::ExactOut, 7, 710)
+        );
+        assert_eq!(quote.fee_mint, quote_mint);
+        // 5 at 100 and 2 at 99, the last base atom is partially needed
+        assert_eq!(
+            book.fill_exact_out(Side::Ask, 650, 0).unwrap(),
+            Fill {
+                in_amount: 7,
+                out_amount: 650,
+                fee_amount: 0,
+            }
+        );
+        assert!(book.fill_exact_out(Side::Bid, 16, 0).is_err());
+    }
+}
@@ -0,0 +1,158 @@
+//! A deterministic ordering of token pairs, for pool dedup, routing graph keys and grouping liquidity by pair
+use std::fmt;
+
+use anyhow::{ensure, Result};
+use serde::{Deserialize, Serialize};
+use solana_sdk::{hash::hashv, pubkey::Pubkey};
+
+use crate::{field_as_string, QuoteParams};
+
+/// Two mints ordered by their bytes, the pair of A and B is the pair of B and A
+///
+/// Fields are private so the order always holds, deserializing pairs out of order fails
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[serde(rename_all = "camelCase", try_from = "UncheckedPair")]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+pub struct CanonicalPair {
+    #[serde(with = "field_as_string")]
+    #[cfg_attr(feature = "schemars", schemars(with = "String"))]
+    mint_a: Pubkey,
+    #[serde(with = "field_as_string")]
+    #[cfg_attr(feature = "schemars", schemars(with = "String"))]
+    mint_b: Pubkey,
+}
+
+/// The serialized shape of a [`CanonicalPair`], before the order is checked
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct UncheckedPair {
+    #[serde(with = "field_as_string")]
+    mint_a: Pubkey,
+    #[serde(with = "field_as_string")]
+    mint_b: Pubkey,
+}
+
+impl TryFrom<UncheckedPair> for CanonicalPair {
+    type Error = anyhow::Error;
+
+    fn try_from(UncheckedPair { mint_a, mint_b }: UncheckedPair) -> Result<Self> {
+        ensure!(
+            mint_a <= mint_b,
+            "Pair {mint_a}-{mint_b} is not in canonical order"
+        );
+        Ok(CanonicalPair { mint_a, mint_b })
+    }
+}
+
+impl CanonicalPair {
+    pub fn new(mint_x: Pubkey, mint_y: Pubkey) -> Self {
+        CanonicalPair {
+            mint_a: mint_x.min(mint_y),
+            mint_b: mint_x.max(mint_y),
+        }
+    }
+
+    /// The smaller mint
+    pub fn mint_a(&self) -> Pubkey {
+        self.mint_a
+    }
+
+    /// The larger mint
+    pub fn mint_b(&self) -> Pubkey {
+        self.mint_b
+    }
+
+    /// Every pair of distinct reserve mints, e.g. from `Amm::get_reserve_mints`
+    pub fn from_reserve_mints(reserve_mints: &[Pubkey]) -> Vec<CanonicalPair> {
+        let mut pairs = vec![];
+        for (i, mint_x) in reserve_mints.iter().enumerate() {
+            for mint_y in &reserve_mints[i + 1..] {
+                if mint_x != mint_y {
+                    pairs.push(CanonicalPair::new(*mint_x, *mint_y));
+                }
+            }
+        }
+        pairs.sort();
+        pairs.dedup();
+        pairs
+    }
+
+    pub fn contains(&self, mint: &Pubkey) -> bool {
+        self.mint_a == *mint || self.mint_b == *mint
+    }
+
+    /// The mint paired with `mint`, None if `mint` is not in the pair
+    pub fn other(&self, mint: &Pubkey) -> Option<Pubkey> {
+        if self.mint_a == *mint {
+            Some(self.mint_b)
+        } else if self.mint_b == *mint {
+            Some(self.mint_a)
+        } else {
+            None
+        }
+    }
+
+    /// Indicates if swapping from `input_mint` goes from `mint_b` to `mint_a`
+    pub fn is_reversed(&self, input_mint: &Pubkey) -> bool {
+        self.mint_b == *input_mint
+    }
+
+    /// A hash stable across processes and versions, unlike the `Hash` impl with a random state
+    pub fn hash_u64(&self) -> u64 {
+        let hash = hashv(&[self.mint_a.as_ref(), self.mint_b.as_ref()]);
+        u64::from_le_bytes(hash.to_bytes()[..8].try_into().unwrap())
+    }
+}
+
+impl From<&QuoteParams> for CanonicalPair {
+    fn from(quote_params: &QuoteParams) -> Self {
+        CanonicalPair::new(quote_params.input_mint, quote_params.output_mint)
+    }
+}
+
+impl From<(Pubkey, Pubkey)> for CanonicalPair {
+    fn from((mint_x, mint_y): (Pubkey, Pubkey)) -> Self {
+        CanonicalPair::new(mint_x, mint_y)
+    }
+}
+
+impl fmt::Display for CanonicalPair {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}-{}", self.mint_a, self.mint_b)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_canonical_pair() {
+        let (sol, usdc, bonk) = (
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+        );
+        let pair = CanonicalPair::new(usdc, sol);
+        assert_eq!(pair, CanonicalPair::new(sol, usdc));
+        assert_eq!(pair.hash_u64(), CanonicalPair::from((sol, usdc)).hash_u64());
+        assert_eq!(pair.other(&sol), Some(usdc));
+        assert_eq!(pair.other(&bonk), None);
+        assert!(pair.is_reversed(&usdc) != pair.is_reversed(&sol));
+
+        let pairs = CanonicalPair::from_reserve_mints(&[sol, usdc, bonk, sol]);
+        assert_eq!(pairs.len(), 3);
+        assert!(pairs.contains(&CanonicalPair::new(bonk, usdc)));
+
+        let json = serde_json::to_value(pair).unwrap();
+        assert_eq!(
+            serde_json::from_value::<CanonicalPair>(json.clone()).unwrap(),
+            pair
+        );
+        let reversed_json = serde_json::json!({
+            "mintA": json["mintB"],
+            "mintB": json["mintA"],
+        });
+        assert!(serde_json::from_value::<CanonicalPair>(reversed_json).is_err());
+    }
+}
@@ -0,0 +1,102 @@
+use std::{
+    collections::HashMap,
+    sync::{PoisonError, RwLock},
+};
+
+use solana_sdk::{hash::hashv, instruction::AccountMeta, pubkey::Pubkey};
+
+/// The first bytes of every placeholder key, so placeholders are recognizable without the registry
+const PLACEHOLDER_PREFIX: &[u8; 8] = b"jupplhdr";
+
+/// Indicates if `pubkey` was generated by a [`PlaceholderRegistry`]
+pub fn is_placeholder(pubkey: &Pubkey) -> bool {
+    pubkey.as_ref().starts_with(PLACEHOLDER_PREFIX)
+}
+
+/// Generates a distinct placeholder key per optional account slot, so slots can be told apart when post processing metas
+///
+/// Scoped to one leg, hosts create a registry per leg and resolve the account metas of that leg with it
+/// Placeholders must be replaced by the jupiter program id with `resolve` before the instruction is sent
+#[derive(Debug, Default)]
+pub struct PlaceholderRegistry {
+    slots: RwLock<HashMap<Pubkey, String>>,
+}
+
+impl PlaceholderRegistry {
+    /// The placeholder of `slot`, the same key for the same slot name
+    pub fn placeholder(&self, slot: &str) -> Pubkey {
+        let hash = hashv(&[PLACEHOLDER_PREFIX, slot.as_bytes()]).to_bytes();
+        let mut bytes = [0; 32];
+        bytes[..8].copy_from_slice(PLACEHOLDER_PREFIX);
+        bytes[8..].copy_from_slice(&hash[..24]);
+        let pubkey = Pubkey::new_from_array(bytes);
+
+        self.slots
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .entry(pubkey)
+            .or_insert_with(|| slot.to_string());
+        pubkey
+    }
+
+    pub fn is_placeholder(&self, pubkey: &Pubkey) -> bool {
+        is_placeholder(pubkey)
+            && self
+                .slots
+                .read()
+                .unwrap_or_else(PoisonError::into_inner)
+                .contains_key(pubkey)
+    }
+
+    /// The slot name `pubkey` is the placeholder of
+    pub fn slot(&self, pubkey: &Pubkey) -> Option<String> {
+        self.slots
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(pubkey)
+            .cloned()
+    }
+
+    /// Replaces the placeholders generated by this registry with the jupiter program id, the placeholder the program expects
+    pub fn resolve(&self, account_metas: &mut [AccountMeta], jupiter_program_id: &Pubkey) {
+        let slots = self.slots.read().unwrap_or_else(PoisonError::into_inner);
+        for account_meta in account_metas {
+            if slots.contains_key(&account_meta.pubkey) {
+                account_meta.pubkey = *jupiter_program_id;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_placeholder_registry() {
+        let registry = PlaceholderRegistry::default();
+        let host_fee = registry.placeholder("host_fee");
+        let referrer = registry.placeholder("referrer");
+        assert_ne!(host_fee, referrer);
+        assert_eq!(registry.placeholder("host_fee"), host_fee);
+        assert!(registry.is_placeholder(&host_fee));
+        assert!(!is_placeholder(&Pubkey::new_unique()));
+        assert_eq!(registry.slot(&referrer).as_deref(), Some("referrer"));
+
+        let jupiter_program_id = Pubkey::new_unique();
+        let pool = Pubkey::new_unique();
+        let mut account_metas = vec![
+            AccountMeta::new(pool, false),
+            AccountMeta::new_readonly(host_fee, false),
+        ];
+        // Placeholders of another leg are left to the registry of that leg
+        let other_leg_registry = PlaceholderRegistry::default();
+        other_leg_registry.placeholder("referrer");
+        other_leg_registry.resolve(&mut account_metas, &jupiter_program_id);
+        assert_eq!(account_metas[1].pubkey, host_fee);
+
+        registry.resolve(&mut account_metas, &jupiter_program_id);
+        assert_eq!(account_metas[0].pubkey, pool);
+        assert_eq!(account_metas[1].pubkey, jupiter_program_id);
+    }
+}
@@ -0,0 +1,42 @@
+use serde::{Deserialize, Serialize};
+
+/// The pricing model of a pool, normalized across DEXes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+#[non_exhaustive]
+pub enum CurveType {
+    ConstantProduct,
+    StableSwap,
+    WeightedProduct,
+    ConcentratedLiquidity,
+    /// Discrete price bins, e.g. DLMM
+    LiquidityBook,
+    OrderBook,
+    /// Priced from an oracle by a market maker program
+    Oracle,
+    BondingCurve,
+    Other,
+}
+
+/// Comparable pool parameters for pool listings, fields not applying to the curve type are None
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PoolMetadata {
+    pub curve_type: CurveType,
+    pub fee_tier_bps: Option<u16>,
+    pub tick_spacing: Option<u16>,
+    pub bin_step: Option<u16>,
+    pub amp_factor: Option<u64>,
+}
+
+impl PoolMetadata {
+    pub fn new(curve_type: CurveType) -> Self {
+        PoolMetadata {
+            curve_type,
+            fee_tier_bps: None,
+            tick_spacing: None,
+            bin_step: None,
+            amp_factor: None,
+        }
+    }
+}
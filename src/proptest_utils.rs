@@ -0,0 +1,187 @@
+use std::ops::RangeInclusive;
+
+use proptest::{
+    prelude::*,
+    test_runner::{Config, TestCaseError, TestRunner},
+};
+use rust_decimal::{prelude::ToPrimitive, Decimal};
+use solana_sdk::pubkey::Pubkey;
+
+use crate::{Amm, Quote, QuoteParams, SwapMode};
+
+pub fn swap_mode_strategy() -> impl Strategy<Value = SwapMode> {
+    prop_oneof![Just(SwapMode::ExactIn), Just(SwapMode::ExactOut)]
+}
+
+/// Quote params between two distinct mints of `reserve_mints`, in both swap modes
+pub fn quote_params_strategy(
+    reserve_mints: Vec<Pubkey>,
+    amounts: RangeInclusive<u64>,
+    swap_modes: impl Strategy<Value = SwapMode>,
+) -> impl Strategy<Value = QuoteParams> {
+    assert!(
+        reserve_mints.len() >= 2,
+        "At least two reserve mints are required"
+    );
+    let mints_len = reserve_mints.len();
+    (0..mints_len, 1..mints_len, amounts, swap_modes).prop_map(
+        move |(input_index, offset, amount, swap_mode)| QuoteParams {
+            amount,
+            input_mint: reserve_mints[input_index],
+            output_mint: reserve_mints[(input_index + offset) % mints_len],
+            swap_mode,
+            budget: None,
+            limit_price: None,
+        },
+    )
+}
+
+/// The fee does not exceed `fee_pct` of the amount it is taken from, the in amount or the out amount before fees
+fn check_fee_bound(quote_params: &QuoteParams, quote: &Quote) -> Result<(), TestCaseError> {
+    let fee_mint_amount = if quote.fee_mint == quote_params.input_mint {
+        Some(quote.in_amount)
+    } else if quote.fee_mint == quote_params.output_mint {
+        Some(quote.out_amount.saturating_add(quote.fee_amount))
+    } else {
+        None
+    };
+    if let Some(fee_mint_amount) = fee_mint_amount {
+        prop_assert!(
+            quote.fee_amount <= fee_mint_amount,
+            "Fee {} exceeds the amount {fee_mint_amount} it is taken from",
+            quote.fee_amount
+        );
+        if let Some(max_fee_amount) = quote
+            .fee_pct
+            .checked_mul(Decimal::from(fee_mint_amount))
+            .and_then(|max_fee_amount| max_fee_amount.ceil().to_u64())
+        {
+            prop_assert!(
+                quote.fee_amount <= max_fee_amount,
+                "Fee {} exceeds {} of {fee_mint_amount}",
+                quote.fee_amount,
+                quote.fee_pct
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Checks the invariants any quote has to uphold, quotes failing altogether are not considered violations
+///
+/// - out amount is non decreasing with the in amount
+/// - the fee does not exceed `fee_pct` of the amount it is taken from, rounded up
+/// - quoting ExactOut for the out amount of an ExactIn quote does not require more than its in amount
+pub fn check_quote_invariants<A: Amm + ?Sized>(
+    amm: &A,
+    quote_params: &QuoteParams,
+) -> Result<(), TestCaseError> {
+    let Ok(quote) = amm.quote(quote_params) else {
+        return Ok(());
+    };
+    check_fee_bound(quote_params, &quote)?;
+
+    if quote_params.swap_mode == SwapMode::ExactIn {
+        let larger_amount = quote_params
+            .amount
+            .saturating_add(quote_params.amount / 10 + 1);
+        if let Ok(larger_quote) = amm.quote(&QuoteParams {
+            amount: larger_amount,
+            ..quote_params.clone()
+        }) {
+            prop_assert!(
+                larger_quote.out_amount >= quote.out_amount,
+                "Out amount decreased from {} to {} when increasing the in amount from {} to {larger_amount}",
+                quote.out_amount,
+                larger_quote.out_amount,
+                quote_params.amount
+            );
+        }
+
+        if amm.supports_exact_out() && quote.out_amount > 0 {
+            if let Ok(exact_out_quote) = amm.quote(&QuoteParams {
+                amount: quote.out_amount,
+                swap_mode: SwapMode::ExactOut,
+                ..quote_params.clone()
+            }) {
+                prop_assert!(
+                    exact_out_quote.in_amount <= quote.in_amount,
+                    "ExactOut of {} requires {} while ExactIn of {} produces it",
+                    quote.out_amount,
+                    exact_out_quote.in_amount,
+                    quote.in_amount
+                );
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Fuzzes the quote invariants of the Amm over its reserve mints and `amounts`, panicking on a violation
+pub fn assert_quote_invariants<A: Amm + ?Sized>(amm: &A, amounts: RangeInclusive<u64>) {
+    let strategy = quote_params_strategy(amm.get_reserve_mints(), amounts, swap_mode_strategy());
+    TestRunner::new(Config::default())
+        .run(&strategy, |quote_params| {
+            check_quote_invariants(amm, &quote_params)
+        })
+        .unwrap();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::reference::MockAmm;
+
+    #[test]
+    fn test_mock_amm_quote_invariants() {
+        let (usdc, sol) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let amm = MockAmm::new(Pubkey::new_unique(), vec![usdc, sol])
+            .with_price(sol, usdc, Decimal::new(1505, 1))
+            .with_price(usdc, sol, Decimal::new(66, 4))
+            .with_fee_pct(Decimal::new(3, 3));
+        assert_quote_invariants(&amm, 1..=1_000_000_000_000);
+    }
+
+    #[test]
+    fn test_check_fee_bound() {
+        let (usdc, sol) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let quote_params = QuoteParams {
+            amount: 1_000_000,
+            input_mint: sol,
+            output_mint: usdc,
+            ..QuoteParams::default()
+        };
+        let quote = Quote {
+            in_amount: 1_000_000,
+            out_amount: 149_550_000,
+            fee_amount: 3_000,
+            fee_mint: sol,
+            fee_pct: Decimal::new(3, 3),
+            ..Quote::default()
+        };
+        assert!(check_fee_bound(&quote_params, &quote).is_ok());
+        assert!(check_fee_bound(
+            &quote_params,
+            &Quote {
+                fee_amount: 3_001,
+                ..quote.clone()
+            }
+        )
+        .is_err());
+        // Taken from the out amount before fees
+        let quote = Quote {
+            fee_amount: 450_000,
+            fee_mint: usdc,
+            ..quote
+        };
+        assert!(check_fee_bound(&quote_params, &quote).is_ok());
+        assert!(check_fee_bound(
+            &quote_params,
+            &Quote {
+                fee_amount: 460_000,
+                ..quote
+            }
+        )
+        .is_err());
+    }
+}
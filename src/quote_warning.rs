@@ -0,0 +1,17 @@
+use serde::{Deserialize, Serialize};
+
+/// A soft condition of a quote, the quote is usable but routers can down-rank it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", tag = "type")]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[non_exhaustive]
+pub enum QuoteWarning {
+    /// An oracle the quote depends on was last updated `slots` ago
+    StaleOracle { slots: u64 },
+    /// The quote moves the price significantly
+    HighPriceImpact,
+    /// The amount is close to what the pool can fill
+    NearCapacity,
+    /// Some dynamic accounts were missing, the quote was computed from fallback accounts or defaults
+    UsedFallbackAccounts,
+}
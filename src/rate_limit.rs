@@ -0,0 +1,109 @@
+use std::{
+    sync::{Arc, Mutex, MutexGuard, PoisonError},
+    time::{Duration, Instant},
+};
+
+/// The quote rate an Amm backed by an external market maker may be called at
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimit {
+    pub max_quotes_per_second: u32,
+    /// Quotes allowed at once after being idle
+    pub burst: u32,
+}
+
+/// A token bucket enforcing a [`RateLimit`], clones share the bucket so hosts can hand one to every caller of the Amm
+#[derive(Debug, Clone)]
+pub struct RateLimiter {
+    rate_limit: RateLimit,
+    bucket: Arc<Mutex<Bucket>>,
+}
+
+#[derive(Debug)]
+struct Bucket {
+    tokens: f64,
+    last_refill: Instant,
+}
+
+impl RateLimiter {
+    pub fn new(rate_limit: RateLimit, now: Instant) -> Self {
+        RateLimiter {
+            rate_limit,
+            bucket: Arc::new(Mutex::new(Bucket {
+                tokens: f64::from(rate_limit.burst),
+                last_refill: now,
+            })),
+        }
+    }
+
+    /// The bucket refilled up to `now`
+    fn refilled_bucket(&self, now: Instant) -> MutexGuard<'_, Bucket> {
+        let mut bucket = self.bucket.lock().unwrap_or_else(PoisonError::into_inner);
+        let elapsed = now.saturating_duration_since(bucket.last_refill);
+        bucket.tokens = (bucket.tokens
+            + elapsed.as_secs_f64() * f64::from(self.rate_limit.max_quotes_per_second))
+        .min(f64::from(self.rate_limit.burst));
+        bucket.last_refill = bucket.last_refill.max(now);
+        bucket
+    }
+
+    /// Takes a token if one is available at `now`
+    pub fn try_acquire(&self, now: Instant) -> bool {
+        let mut bucket = self.refilled_bucket(now);
+        if bucket.tokens >= 1.0 {
+            bucket.tokens -= 1.0;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// The wait from `now` until a token is available, None if the rate is zero
+    pub fn time_until_available(&self, now: Instant) -> Option<Duration> {
+        let bucket = self.refilled_bucket(now);
+        if bucket.tokens >= 1.0 {
+            return Some(Duration::ZERO);
+        }
+        if self.rate_limit.max_quotes_per_second == 0 {
+            return None;
+        }
+        Some(Duration::from_secs_f64(
+            (1.0 - bucket.tokens) / f64::from(self.rate_limit.max_quotes_per_second),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rate_limiter() {
+        let now = Instant::now();
+        let rate_limiter = RateLimiter::new(
+            RateLimit {
+                max_quotes_per_second: 10,
+                burst: 2,
+            },
+            now,
+        );
+        // Clones share the bucket
+        assert!(rate_limiter.try_acquire(now));
+        assert!(rate_limiter.clone().try_acquire(now));
+        assert!(!rate_limiter.try_acquire(now));
+        assert_eq!(
+            rate_limiter.time_until_available(now),
+            Some(Duration::from_millis(100))
+        );
+        assert_eq!(
+            rate_limiter.time_until_available(now + Duration::from_millis(100)),
+            Some(Duration::ZERO)
+        );
+
+        assert!(rate_limiter.try_acquire(now + Duration::from_millis(100)));
+        // The bucket never holds more than the burst
+        let later = now + Duration::from_secs(10);
+        assert!(rate_limiter.try_acquire(later));
+        assert!(rate_limiter.try_acquire(later));
+        assert!(!rate_limiter.try_acquire(later));
+    }
+}
@@ -0,0 +1,810 @@
+use anyhow::{anyhow, ensure, Context, Result};
+use borsh::{BorshDeserialize, BorshSerialize};
+use rust_decimal::Decimal;
+use smallvec::smallvec;
+use solana_sdk::{instruction::AccountMeta, pubkey::Pubkey};
+
+use crate::{
+    math::{exact_out_via_exact_in, mul_div_floor},
+    try_get_account_data_and_owner, AccountMap, AccountRole, Amm, AmmCapabilities, AmmContext,
+    CurveType, DexId, FeeSchedule, KeyedAccount, MissingAccountsAction, PoolMetadata, Quote,
+    QuoteError, QuoteParams, Swap, SwapAndAccountMetas, SwapMode, SwapParams,
+    SPL_TOKEN_2022_PROGRAM_ID, SPL_TOKEN_PROGRAM_ID,
+};
+
+const SWAP_ACCOUNT_LEN: usize = 324;
+const TOKEN_ACCOUNT_AMOUNT_OFFSET: usize = 64;
+const CURVE_TYPE_OFFSET: usize = 291;
+const CONSTANT_PRODUCT_CURVE_TYPE: u8 = 0;
+
+fn read_pubkey(data: &[u8], offset: usize) -> Pubkey {
+    Pubkey::new_from_array(data[offset..offset + 32].try_into().unwrap())
+}
+
+fn read_u64(data: &[u8], offset: usize) -> u64 {
+    u64::from_le_bytes(data[offset..offset + 8].try_into().unwrap())
+}
+
+/// A fee as a fraction, mirroring the SPL Token-Swap fee fields
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Fee {
+    pub numerator: u64,
+    pub denominator: u64,
+}
+
+impl Fee {
+    /// Rounds up to at least 1 on a non zero fee so trades cannot dodge fees with dust amounts
+    fn calculate(&self, amount: u64) -> Option<u64> {
+        if self.numerator == 0 || amount == 0 {
+            return Some(0);
+        }
+        Some(mul_div_floor(amount, self.numerator, self.denominator)?.max(1))
+    }
+
+    fn bps(&self) -> Option<u64> {
+        mul_div_floor(10_000, self.numerator, self.denominator)
+    }
+
+    fn rate(&self) -> Option<Decimal> {
+        if self.numerator == 0 {
+            return Some(Decimal::ZERO);
+        }
+        Decimal::from(self.numerator).checked_div(Decimal::from(self.denominator))
+    }
+}
+
+/// A constant product pool stored in an SPL Token-Swap account
+#[derive(Debug, Clone)]
+pub struct ConstantProductAmm {
+    key: Pubkey,
+    program_id: Pubkey,
+    authority: Pubkey,
+    token_program: Pubkey,
+    token_a: Pubkey,
+    token_b: Pubkey,
+    pool_mint: Pubkey,
+    token_a_mint: Pubkey,
+    token_b_mint: Pubkey,
+    pool_fee_account: Pubkey,
+    trade_fee: Fee,
+    owner_trade_fee: Fee,
+    reserve_a: u64,
+    reserve_b: u64,
+    state_version: u64,
+}
+
+impl ConstantProductAmm {
+    fn reserves(&self, input_mint: &Pubkey) -> Result<(u64, u64)> {
+        if *input_mint == self.token_a_mint {
+            Ok((self.reserve_a, self.reserve_b))
+        } else if *input_mint == self.token_b_mint {
+            Ok((self.reserve_b, self.reserve_a))
+        } else {
+            Err(anyhow!("{input_mint} is not a reserve mint"))
+        }
+    }
+
+    /// The out and fee amounts of swapping `in_amount`, None on overflow or if the fees exceed the in amount
+    fn swap_amounts(
+        &self,
+        source_reserve: u64,
+        destination_reserve: u64,
+        in_amount: u64,
+    ) -> Option<(u64, u64)> {
+        let fee_amount = self
+            .trade_fee
+            .calculate(in_amount)?
+            .checked_add(self.owner_trade_fee.calculate(in_amount)?)?;
+        let in_amount_less_fees = in_amount.checked_sub(fee_amount)?;
+        let new_source_reserve = u128::from(source_reserve) + u128::from(in_amount_less_fees);
+        let out_amount = u128::from(destination_reserve) * u128::from(in_amount_less_fees)
+            / new_source_reserve.max(1);
+        Some((u64::try_from(out_amount).ok()?, fee_amount))
+    }
+
+    /// The marginal price, in destination atoms per source atom net of fees, once `in_amount` is swapped
+    fn marginal_price_after(
+        &self,
+        source_reserve: u64,
+        destination_reserve: u64,
+        in_amount: u64,
+    ) -> Option<Decimal> {
+        let (out_amount, fee_amount) =
+            self.swap_amounts(source_reserve, destination_reserve, in_amount)?;
+        let new_source_reserve = source_reserve.checked_add(in_amount - fee_amount)?;
+        let new_destination_reserve = destination_reserve.checked_sub(out_amount)?;
+        Decimal::from(new_destination_reserve)
+            .checked_div(Decimal::from(new_source_reserve))?
+            .checked_mul(Decimal::ONE - self.trade_fee.rate()? - self.owner_trade_fee.rate()?)
+    }
+
+    /// The largest in amount up to `in_amount` keeping the marginal price at or above `limit_price`
+    fn max_in_amount_at_limit(
+        &self,
+        source_reserve: u64,
+        destination_reserve: u64,
+        in_amount: u64,
+        limit_price: Decimal,
+    ) -> u64 {
+        let within_limit = |in_amount| {
+            self.marginal_price_after(source_reserve, destination_reserve, in_amount)
+                .is_some_and(|marginal_price| marginal_price >= limit_price)
+        };
+        if within_limit(in_amount) {
+            return in_amount;
+        }
+        if !within_limit(0) {
+            return 0;
+        }
+        // The marginal price decreases with the in amount
+        let (mut low, mut high) = (0, in_amount);
+        while high - low > 1 {
+            let middle = low + (high - low) / 2;
+            if within_limit(middle) {
+                low = middle;
+            } else {
+                high = middle;
+            }
+        }
+        low
+    }
+
+    fn quote_exact_in(&self, quote_params: &QuoteParams) -> Result<Quote> {
+        let (source_reserve, destination_reserve) = self.reserves(&quote_params.input_mint)?;
+        let in_amount = match quote_params.limit_price {
+            Some(limit_price) => self.max_in_amount_at_limit(
+                source_reserve,
+                destination_reserve,
+                quote_params.amount,
+                limit_price,
+            ),
+            None => quote_params.amount,
+        };
+        if in_amount == 0 && quote_params.amount > 0 {
+            return Err(QuoteError::InsufficientLiquidity { max_in: Some(0) }.into());
+        }
+        let (out_amount, fee_amount) = self
+            .swap_amounts(source_reserve, destination_reserve, in_amount)
+            .ok_or(QuoteError::AmountTooSmall { min_in: None })?;
+        if out_amount == 0 {
+            return Err(QuoteError::AmountTooSmall { min_in: None }.into());
+        }
+
+        Ok(Quote {
+            in_amount,
+            out_amount,
+            fee_amount,
+            fee_mint: quote_params.input_mint,
+            fee_pct: Decimal::from(fee_amount) / Decimal::from(in_amount),
+            swap_mode: SwapMode::ExactIn,
+            amount_specified: quote_params.amount,
+            reached_limit: in_amount < quote_params.amount,
+            ..Quote::default()
+        })
+    }
+
+    /// Quotes ExactOut by searching ExactIn, failing if the fill would cross `limit_price` since it cannot be partial
+    fn quote_exact_out(&self, quote_params: &QuoteParams) -> Result<Quote> {
+        let (source_reserve, destination_reserve) = self.reserves(&quote_params.input_mint)?;
+        if quote_params.amount >= destination_reserve {
+            return Err(QuoteError::InsufficientLiquidity { max_in: None }.into());
+        }
+        let quote = exact_out_via_exact_in(
+            self,
+            &QuoteParams {
+                limit_price: None,
+                ..quote_params.clone()
+            },
+            0,
+        )?;
+        if let Some(limit_price) = quote_params.limit_price {
+            let max_in = self.max_in_amount_at_limit(
+                source_reserve,
+                destination_reserve,
+                quote.in_amount,
+                limit_price,
+            );
+            if max_in < quote.in_amount {
+                return Err(QuoteError::InsufficientLiquidity {
+                    max_in: Some(max_in),
+                }
+                .into());
+            }
+        }
+        Ok(quote)
+    }
+}
+
+impl Amm for ConstantProductAmm {
+    fn from_keyed_account(keyed_account: &KeyedAccount, _amm_context: &AmmContext) -> Result<Self> {
+        let data = keyed_account.account.data.as_slice();
+        ensure!(
+            data.len() == SWAP_ACCOUNT_LEN,
+            "Invalid token swap account length {}",
+            data.len()
+        );
+        ensure!(data[1] == 1, "Token swap account is not initialized");
+        ensure!(
+            data[CURVE_TYPE_OFFSET] == CONSTANT_PRODUCT_CURVE_TYPE,
+            "Unsupported token swap curve type {}",
+            data[CURVE_TYPE_OFFSET]
+        );
+        let key = keyed_account.key;
+        let program_id = keyed_account.account.owner;
+        let authority = Pubkey::create_program_address(&[key.as_ref(), &[data[2]]], &program_id)?;
+
+        Ok(ConstantProductAmm {
+            key,
+            program_id,
+            authority,
+            token_program: read_pubkey(data, 3),
+            token_a: read_pubkey(data, 35),
+            token_b: read_pubkey(data, 67),
+            pool_mint: read_pubkey(data, 99),
+            token_a_mint: read_pubkey(data, 131),
+            token_b_mint: read_pubkey(data, 163),
+            pool_fee_account: read_pubkey(data, 195),
+            trade_fee: Fee {
+                numerator: read_u64(data, 227),
+                denominator: read_u64(data, 235),
+            },
+            owner_trade_fee: Fee {
+                numerator: read_u64(data, 243),
+                denominator: read_u64(data, 251),
+            },
+            reserve_a: 0,
+            reserve_b: 0,
+            state_version: 0,
+        })
+    }
+
+    fn label(&self) -> String {
+        "TokenSwap".to_string()
+    }
+
+    fn dex_id(&self) -> DexId {
+        DexId::TokenSwap
+    }
+
+    fn program_id(&self) -> Pubkey {
+        self.program_id
+    }
+
+    fn key(&self) -> Pubkey {
+        self.key
+    }
+
+    fn get_reserve_mints(&self) -> Vec<Pubkey> {
+        vec![self.token_a_mint, self.token_b_mint]
+    }
+
+    fn get_accounts_to_update(&self) -> Vec<Pubkey> {
+        vec![self.token_a, self.token_b]
+    }
+
+    fn update(&mut self, account_map: &AccountMap) -> Result<()> {
+        let read_amount = |address| -> Result<u64> {
+            let (data, owner) = try_get_account_data_and_owner(account_map, address)?;
+            ensure!(
+                *owner == SPL_TOKEN_PROGRAM_ID || *owner == SPL_TOKEN_2022_PROGRAM_ID,
+                "{address} is not a token account"
+            );
+            ensure!(
+                data.len() >= TOKEN_ACCOUNT_AMOUNT_OFFSET + 8,
+                "{address} is not a token account"
+            );
+            Ok(read_u64(data, TOKEN_ACCOUNT_AMOUNT_OFFSET))
+        };
+        let reserve_a = read_amount(&self.token_a)?;
+        let reserve_b = read_amount(&self.token_b)?;
+
+        if (reserve_a, reserve_b) != (self.reserve_a, self.reserve_b) {
+            self.reserve_a = reserve_a;
+            self.reserve_b = reserve_b;
+            self.state_version += 1;
+        }
+        Ok(())
+    }
+
+    fn quote(&self, quote_params: &QuoteParams) -> Result<Quote> {
+        match quote_params.swap_mode {
+            SwapMode::ExactIn => self.quote_exact_in(quote_params),
+            SwapMode::ExactOut => self.quote_exact_out(quote_params),
+        }
+    }
+
+    fn max_out_amount(&self, output_mint: &Pubkey) -> Option<u64> {
+        // The invariant keeps at least one unit in the destination reserve
+        let (destination_reserve, _) = self.reserves(output_mint).ok()?;
+        Some(destination_reserve.saturating_sub(1))
+    }
+
+    fn state_version(&self) -> u64 {
+        self.state_version
+    }
+
+    fn capabilities(&self) -> AmmCapabilities {
+        AmmCapabilities::EXACT_OUT | AmmCapabilities::LIMIT_PRICE
+    }
+
+    fn get_pool_metadata(&self) -> Option<PoolMetadata> {
+        Some(PoolMetadata {
+            fee_tier_bps: self
+                .fee_schedule()
+                .map(|fee_schedule| fee_schedule.base_fee_bps),
+            ..PoolMetadata::new(CurveType::ConstantProduct)
+        })
+    }
+
+    fn fee_schedule(&self) -> Option<FeeSchedule> {
+        let trade_fee_bps = self.trade_fee.bps()?;
+        let owner_trade_fee_bps = self.owner_trade_fee.bps()?;
+        let base_fee_bps = trade_fee_bps + owner_trade_fee_bps;
+        let protocol_fee_share_bps = (owner_trade_fee_bps * 10_000)
+            .checked_div(base_fee_bps)
+            .unwrap_or_default();
+        Some(FeeSchedule {
+            base_fee_bps: u16::try_from(base_fee_bps).ok()?,
+            dynamic_fee: None,
+            protocol_fee_share_bps: u16::try_from(protocol_fee_share_bps).ok()?,
+        })
+    }
+
+    fn fee_mint_for(&self, input_mint: &Pubkey, _output_mint: &Pubkey) -> Option<Pubkey> {
+        Some(*input_mint)
+    }
+
+    fn snapshot_state(&self) -> Option<Vec<u8>> {
+        (self.reserve_a, self.reserve_b, self.state_version)
+            .try_to_vec()
+            .ok()
+    }
+
+    fn restore_state(&mut self, bytes: &[u8]) -> Result<()> {
+        (self.reserve_a, self.reserve_b, self.state_version) =
+            <(u64, u64, u64)>::try_from_slice(bytes).context("Invalid token swap snapshot")?;
+        Ok(())
+    }
+
+    fn quote_depends_on_clock(&self) -> bool {
+        false
+    }
+
+    fn on_accounts_missing(&mut self, missing: &[Pubkey]) -> MissingAccountsAction {
+        // Only the pool authority can close the reserves, a missing reserve means the pool is gone
+        if missing.contains(&self.token_a) || missing.contains(&self.token_b) {
+            MissingAccountsAction::Deactivate
+        } else {
+            MissingAccountsAction::Retry
+        }
+    }
+
+    fn get_swap_and_account_metas(&self, swap_params: &SwapParams) -> Result<SwapAndAccountMetas> {
+        let (swap_source, swap_destination) = if swap_params.source_mint == self.token_a_mint {
+            (self.token_a, self.token_b)
+        } else if swap_params.source_mint == self.token_b_mint {
+            (self.token_b, self.token_a)
+        } else {
+            return Err(anyhow!("{} is not a reserve mint", swap_params.source_mint));
+        };
+
+        Ok(SwapAndAccountMetas::new(
+            Swap::TokenSwap,
+            smallvec![
+                AccountMeta::new_readonly(self.program_id, false),
+                AccountMeta::new_readonly(self.token_program, false),
+                AccountMeta::new_readonly(self.key, false),
+                AccountMeta::new_readonly(self.authority, false),
+                AccountMeta::new_readonly(swap_params.token_transfer_authority, true),
+                AccountMeta::new(swap_params.source_token_account, false),
+                AccountMeta::new(swap_source, false),
+                AccountMeta::new(swap_destination, false),
+                AccountMeta::new(swap_params.destination_token_account, false),
+                AccountMeta::new(self.pool_mint, false),
+                AccountMeta::new(self.pool_fee_account, false),
+                swap_params.placeholder_account_meta_for("host_fee_account"),
+            ],
+        ))
+    }
+
+    fn get_swap_account_roles(&self) -> Vec<AccountRole> {
+        vec![
+            AccountRole::Program,
+            AccountRole::TokenProgram,
+            AccountRole::Pool,
+            AccountRole::PoolAuthority,
+            AccountRole::UserAuthority,
+            AccountRole::UserSource,
+            AccountRole::VaultIn,
+            AccountRole::VaultOut,
+            AccountRole::UserDestination,
+            AccountRole::Other("pool_mint".into()),
+            AccountRole::FeeAccount,
+            AccountRole::Placeholder,
+        ]
+    }
+
+    fn supports_exact_out(&self) -> bool {
+        true
+    }
+
+    fn clone_amm(&self) -> Box<dyn Amm + Send + Sync> {
+        Box::new(self.clone())
+    }
+
+    fn get_accounts_len(&self) -> usize {
+        12
+    }
+
+    fn get_tvl_in_reserve_terms(&self) -> Option<Vec<(Pubkey, u64)>> {
+        Some(vec![
+            (self.token_a_mint, self.reserve_a),
+            (self.token_b_mint, self.reserve_b),
+        ])
+    }
+}
+
+/// An initialized SPL Token-Swap pool with 0.25% trade and 0.05% owner trade fees, for tests and benches
+#[cfg(any(test, feature = "bench"))]
+pub struct TokenSwapFixture {
+    pub keyed_account: KeyedAccount,
+    /// The reserve token accounts
+    pub account_map: AccountMap,
+    pub token_a_mint: Pubkey,
+    pub token_b_mint: Pubkey,
+}
+
+#[cfg(any(test, feature = "bench"))]
+impl TokenSwapFixture {
+    pub fn new(reserve_a: u64, reserve_b: u64) -> Self {
+        use solana_sdk::account::Account;
+
+        let token_account = |mint: &Pubkey, amount: u64| {
+            let mut data = vec![0; 165];
+            data[..32].copy_from_slice(mint.as_ref());
+            data[TOKEN_ACCOUNT_AMOUNT_OFFSET..TOKEN_ACCOUNT_AMOUNT_OFFSET + 8]
+                .copy_from_slice(&amount.to_le_bytes());
+            Account {
+                data,
+                owner: SPL_TOKEN_PROGRAM_ID,
+                ..Account::default()
+            }
+        };
+
+        let program_id = Pubkey::new_unique();
+        let (key, bump) = loop {
+            let key = Pubkey::new_unique();
+            if let Some((_, bump)) = Pubkey::try_find_program_address(&[key.as_ref()], &program_id)
+            {
+                break (key, bump);
+            }
+        };
+        let token_a = Pubkey::new_unique();
+        let token_b = Pubkey::new_unique();
+        let token_a_mint = Pubkey::new_unique();
+        let token_b_mint = Pubkey::new_unique();
+
+        let mut data = vec![0; SWAP_ACCOUNT_LEN];
+        data[0] = 1;
+        data[1] = 1;
+        data[2] = bump;
+        for (offset, pubkey) in [
+            (3, SPL_TOKEN_PROGRAM_ID),
+            (35, token_a),
+            (67, token_b),
+            (99, Pubkey::new_unique()),
+            (131, token_a_mint),
+            (163, token_b_mint),
+            (195, Pubkey::new_unique()),
+        ] {
+            data[offset..offset + 32].copy_from_slice(pubkey.as_ref());
+        }
+        for (offset, value) in [(227, 25u64), (235, 10_000), (243, 5), (251, 10_000)] {
+            data[offset..offset + 8].copy_from_slice(&value.to_le_bytes());
+        }
+
+        let mut account_map = AccountMap::default();
+        account_map.insert(token_a, token_account(&token_a_mint, reserve_a));
+        account_map.insert(token_b, token_account(&token_b_mint, reserve_b));
+
+        TokenSwapFixture {
+            keyed_account: KeyedAccount {
+                key,
+                account: Account {
+                    data,
+                    owner: program_id,
+                    ..Account::default()
+                },
+                params: None,
+            },
+            account_map,
+            token_a_mint,
+            token_b_mint,
+        }
+    }
+
+    /// Overrides the curve type, 0 being constant product
+    pub fn with_curve_type(mut self, curve_type: u8) -> Self {
+        self.keyed_account.account.data[CURVE_TYPE_OFFSET] = curve_type;
+        self
+    }
+
+    /// Loads the pool and updates it from the reserve token accounts
+    pub fn amm(&self) -> Result<ConstantProductAmm> {
+        let mut amm =
+            ConstantProductAmm::from_keyed_account(&self.keyed_account, &AmmContext::default())?;
+        amm.update(&self.account_map)?;
+        Ok(amm)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MissingAccountPolicy;
+
+    #[test]
+    fn test_from_keyed_account_rejects_other_curves() {
+        assert!(TokenSwapFixture::new(1_000, 2_000).amm().is_ok());
+        // Constant price, stable and offset curves share the account layout
+        for curve_type in 1..=3 {
+            let error = TokenSwapFixture::new(1_000, 2_000)
+                .with_curve_type(curve_type)
+                .amm()
+                .unwrap_err();
+            assert_eq!(
+                error.to_string(),
+                format!("Unsupported token swap curve type {curve_type}")
+            );
+        }
+    }
+
+    #[test]
+    fn test_snapshot_state() {
+        let fixture = TokenSwapFixture::new(1_000_000_000, 2_000_000_000);
+        let amm = fixture.amm().unwrap();
+        let snapshot = amm.snapshot_state().unwrap();
+
+        let mut restored_amm =
+            ConstantProductAmm::from_keyed_account(&fixture.keyed_account, &AmmContext::default())
+                .unwrap();
+        restored_amm.restore_state(&snapshot).unwrap();
+        assert_eq!(restored_amm.state_version(), amm.state_version());
+        assert_eq!(
+            restored_amm.get_tvl_in_reserve_terms(),
+            amm.get_tvl_in_reserve_terms()
+        );
+        assert!(restored_amm.restore_state(&snapshot[1..]).is_err());
+    }
+
+    #[test]
+    fn test_quote_exact_in() {
+        let fixture = TokenSwapFixture::new(1_000_000_000, 2_000_000_000);
+        let amm = fixture.amm().unwrap();
+        assert_eq!(amm.state_version(), 1);
+        assert_eq!(
+            amm.fee_schedule(),
+            Some(FeeSchedule {
+                base_fee_bps: 30,
+                dynamic_fee: None,
+                protocol_fee_share_bps: 1_666,
+            })
+        );
+        assert_eq!(amm.get_pool_metadata().unwrap().fee_tier_bps, Some(30));
+        assert_eq!(
+            amm.capabilities(),
+            AmmCapabilities::EXACT_OUT | AmmCapabilities::LIMIT_PRICE
+        );
+        assert_eq!(
+            amm.current_fee_bps(&fixture.token_a_mint, None),
+            Some(Decimal::from(30))
+        );
+        assert_eq!(
+            amm.fee_mint_for(&fixture.token_a_mint, &fixture.token_b_mint),
+            Some(fixture.token_a_mint)
+        );
+
+        let quote = amm
+            .quote(&QuoteParams {
+                amount: 1_000_000,
+                input_mint: fixture.token_a_mint,
+                output_mint: fixture.token_b_mint,
+                swap_mode: SwapMode::ExactIn,
+                budget: None,
+                limit_price: None,
+            })
+            .unwrap();
+        assert_eq!(quote.fee_amount, 3_000);
+        // 2_000_000_000 * 997_000 / 1_000_997_000
+        assert_eq!(quote.out_amount, 1_992_013);
+        assert_eq!(quote.fee_pct, Decimal::new(3, 3));
+
+        let reverse_quote = amm
+            .quote(&QuoteParams {
+                amount: quote.out_amount,
+                input_mint: fixture.token_b_mint,
+                output_mint: fixture.token_a_mint,
+                swap_mode: SwapMode::ExactIn,
+                budget: None,
+                limit_price: None,
+            })
+            .unwrap();
+        assert!(reverse_quote.out_amount < quote.in_amount);
+
+        let dust_quote = amm.quote(&QuoteParams {
+            amount: 1,
+            input_mint: fixture.token_a_mint,
+            output_mint: fixture.token_b_mint,
+            swap_mode: SwapMode::ExactIn,
+            budget: None,
+            limit_price: None,
+        });
+        assert_eq!(
+            dust_quote.unwrap_err().downcast_ref::<QuoteError>(),
+            Some(&QuoteError::AmountTooSmall { min_in: None })
+        );
+    }
+
+    #[test]
+    fn test_quote_exact_out() {
+        let fixture = TokenSwapFixture::new(1_000_000_000, 2_000_000_000);
+        let amm = fixture.amm().unwrap();
+
+        let quote_params = QuoteParams {
+            amount: 1_992_013,
+            input_mint: fixture.token_a_mint,
+            output_mint: fixture.token_b_mint,
+            swap_mode: SwapMode::ExactOut,
+            budget: None,
+            limit_price: None,
+        };
+        let quote = amm.quote(&quote_params).unwrap();
+        assert!(quote.out_amount >= quote_params.amount);
+        assert!(quote.in_amount <= 1_000_000);
+        assert_eq!(quote.amount_specified, quote_params.amount);
+        assert_eq!(quote.amount_computed(), quote.in_amount);
+
+        assert_eq!(
+            amm.max_out_amount(&fixture.token_b_mint),
+            Some(1_999_999_999)
+        );
+        assert!(amm
+            .quote(&QuoteParams {
+                amount: 2_000_000_000,
+                ..quote_params
+            })
+            .is_err());
+    }
+
+    #[test]
+    fn test_quote_limit_price() {
+        let fixture = TokenSwapFixture::new(1_000_000_000, 2_000_000_000);
+        let amm = fixture.amm().unwrap();
+        let (source_reserve, destination_reserve) = (1_000_000_000, 2_000_000_000);
+        let quote_params = QuoteParams {
+            amount: 100_000_000,
+            input_mint: fixture.token_a_mint,
+            output_mint: fixture.token_b_mint,
+            swap_mode: SwapMode::ExactIn,
+            budget: None,
+            limit_price: None,
+        };
+        let full_quote = amm.quote(&quote_params).unwrap();
+        assert!(!full_quote.reached_limit);
+
+        // The spot price is 2 * 0.997, the full fill moves it below 1.9
+        let limit_price = Decimal::new(19, 1);
+        let quote = amm
+            .quote(&QuoteParams {
+                limit_price: Some(limit_price),
+                ..quote_params.clone()
+            })
+            .unwrap();
+        assert!(quote.reached_limit);
+        assert_eq!(quote.amount_specified, quote_params.amount);
+        assert!(quote.in_amount < full_quote.in_amount);
+        assert!(quote.out_amount < full_quote.out_amount);
+        let marginal_price_after = |in_amount| {
+            amm.marginal_price_after(source_reserve, destination_reserve, in_amount)
+                .unwrap()
+        };
+        assert!(marginal_price_after(quote.in_amount) >= limit_price);
+        assert!(marginal_price_after(quote.in_amount + 1) < limit_price);
+
+        // A limit the full fill stays within does not cap it
+        let quote = amm
+            .quote(&QuoteParams {
+                limit_price: Some(Decimal::ONE),
+                ..quote_params.clone()
+            })
+            .unwrap();
+        assert_eq!(quote, full_quote);
+
+        // A limit above the spot price cannot be filled at all
+        let error = amm
+            .quote(&QuoteParams {
+                limit_price: Some(Decimal::from(2)),
+                ..quote_params.clone()
+            })
+            .unwrap_err();
+        assert_eq!(
+            error.downcast_ref::<QuoteError>(),
+            Some(&QuoteError::InsufficientLiquidity { max_in: Some(0) })
+        );
+
+        // ExactOut cannot be partially filled
+        let error = amm
+            .quote(&QuoteParams {
+                amount: full_quote.out_amount,
+                swap_mode: SwapMode::ExactOut,
+                limit_price: Some(limit_price),
+                ..quote_params
+            })
+            .unwrap_err();
+        assert!(matches!(
+            error.downcast_ref::<QuoteError>(),
+            Some(QuoteError::InsufficientLiquidity { max_in: Some(_) })
+        ));
+    }
+
+    #[test]
+    fn test_get_swap_and_account_metas() {
+        let fixture = TokenSwapFixture::new(1_000_000_000, 2_000_000_000);
+        let amm = fixture.amm().unwrap();
+        let jupiter_program_id = Pubkey::new_unique();
+
+        let swap_params = SwapParams {
+            swap_mode: SwapMode::ExactIn,
+            in_amount: 1_000_000,
+            out_amount: 1_992_013,
+            source_mint: fixture.token_b_mint,
+            destination_mint: fixture.token_a_mint,
+            source_token_account: Pubkey::new_unique(),
+            destination_token_account: Pubkey::new_unique(),
+            token_transfer_authority: Pubkey::new_unique(),
+            user: None,
+            open_order_address: None,
+            quote_mint_to_referrer: None,
+            jupiter_program_id: &jupiter_program_id,
+            missing_account_policy: MissingAccountPolicy::Error,
+            #[allow(deprecated)]
+            missing_dynamic_accounts_as_default: false,
+            nonce_provider: None,
+            placeholder_registry: None,
+            exact_out_overshoot_bps: None,
+            is_native_source: false,
+            is_native_destination: false,
+        };
+        let SwapAndAccountMetas {
+            swap,
+            account_metas,
+            ..
+        } = amm.get_swap_and_account_metas(&swap_params).unwrap();
+        assert_eq!(swap, Swap::TokenSwap);
+        assert_eq!(account_metas.len(), amm.get_accounts_len());
+        assert_eq!(amm.get_swap_account_roles().len(), amm.get_accounts_len());
+        // Swapping from token b, the swap source is the token b reserve
+        assert_eq!(account_metas[6].pubkey, amm.token_b);
+        assert_eq!(account_metas[7].pubkey, amm.token_a);
+
+        assert_eq!(
+            amm.writable_accounts(&swap_params).unwrap(),
+            vec![
+                swap_params.source_token_account,
+                amm.token_b,
+                amm.token_a,
+                swap_params.destination_token_account,
+                amm.pool_mint,
+                amm.pool_fee_account,
+            ]
+        );
+
+        // A mint outside of the pool does not build a reversed swap
+        assert!(amm
+            .get_swap_and_account_metas(&SwapParams {
+                source_mint: Pubkey::new_unique(),
+                ..swap_params
+            })
+            .is_err());
+    }
+}
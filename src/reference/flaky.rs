@@ -0,0 +1,236 @@
+use std::{thread, time::Duration};
+
+use anyhow::{anyhow, Result};
+use solana_sdk::pubkey::Pubkey;
+
+use crate::{
+    forward::forward_amm_methods, try_get_account_data_and_owner, AccountMap, AccountMapDiff, Amm,
+    AmmContext, KeyedAccount, LiquidityParams, LiquidityQuote, Quote, QuoteParams,
+    TokenBalanceSource, UpdatePlan, UpdatePriority,
+};
+
+/// Wraps an Amm to misbehave like a faulty third party adapter, for testing host isolation and timeouts
+///
+/// Without any failure mode configured, every call is delegated to the wrapped Amm
+#[derive(Clone)]
+pub struct FlakyAmm {
+    inner: Box<dyn Amm + Send + Sync>,
+    panic_on_quote: bool,
+    update_delay: Option<Duration>,
+    accounts_len: Option<usize>,
+    missing_accounts: Vec<Pubkey>,
+}
+
+impl FlakyAmm {
+    pub fn new(inner: Box<dyn Amm + Send + Sync>) -> Self {
+        FlakyAmm {
+            inner,
+            panic_on_quote: false,
+            update_delay: None,
+            accounts_len: None,
+            missing_accounts: vec![],
+        }
+    }
+
+    /// Panics in the quote methods instead of returning an error
+    pub fn with_panic_on_quote(mut self) -> Self {
+        self.panic_on_quote = true;
+        self
+    }
+
+    /// Blocks the update methods for `update_delay` before delegating it
+    pub fn with_slow_update(mut self, update_delay: Duration) -> Self {
+        self.update_delay = Some(update_delay);
+        self
+    }
+
+    /// Reports `accounts_len` from `get_accounts_len`, mismatching the account metas
+    pub fn with_wrong_accounts_len(mut self, accounts_len: usize) -> Self {
+        self.accounts_len = Some(accounts_len);
+        self
+    }
+
+    /// Requests accounts that do not exist, failing the update methods since they cannot be fetched
+    pub fn with_missing_accounts(mut self, missing_accounts: Vec<Pubkey>) -> Self {
+        self.missing_accounts = missing_accounts;
+        self
+    }
+
+    fn before_update(&self, account_map: &AccountMap) -> Result<()> {
+        if let Some(update_delay) = self.update_delay {
+            thread::sleep(update_delay);
+        }
+        for address in &self.missing_accounts {
+            try_get_account_data_and_owner(account_map, address)?;
+        }
+        Ok(())
+    }
+
+    fn before_quote(&self) {
+        if self.panic_on_quote {
+            panic!("FlakyAmm {} panicked while quoting", self.key());
+        }
+    }
+}
+
+impl Amm for FlakyAmm {
+    fn from_keyed_account(
+        _keyed_account: &KeyedAccount,
+        _amm_context: &AmmContext,
+    ) -> Result<Self> {
+        Err(anyhow!("FlakyAmm can only wrap an existing Amm"))
+    }
+
+    forward_amm_methods!(
+        inner =>
+        label, dex_id, program_id, key, get_reserve_mints, get_dynamic_accounts,
+        predict_accounts_for_amount_range, min_in_amount, min_out_amount, max_in_amount,
+        max_out_amount, quote_cache_key, last_update_slot, state_version, snapshot_state,
+        restore_state, quoting_blackout, quote_depends_on_clock, max_quote_age_slots,
+        quote_rate_limit, routing_hints, settlement_constraints, execution_constraints,
+        get_swap_and_account_metas, get_adjacent_instructions, get_swap_and_account_metas_v2,
+        writable_accounts, get_swap_account_roles, get_liquidity_ix_account_metas,
+        get_swap_and_account_metas_candidates, get_user_token_accounts, tracing_fields,
+        get_pool_metadata, fee_schedule, current_fee_bps, fee_mint_for, accepts_native_sol,
+        handles_token2022_fees, get_oracle_requirements, has_dynamic_accounts,
+        requires_update_for_reserve_mints, supports_exact_out, get_user_setup, get_user_setup_v2,
+        clone_amm, interface_version, unidirectional, capabilities, inner_program_ids,
+        get_program_dependencies, underlying_liquidities, get_tvl_in_reserve_terms,
+        get_cumulative_volume, created_at, is_active, activity_status, update_failure_policy,
+        on_accounts_missing, successor_market,
+    );
+
+    fn get_accounts_to_update(&self) -> Vec<Pubkey> {
+        let mut accounts_to_update = self.inner.get_accounts_to_update();
+        accounts_to_update.extend(&self.missing_accounts);
+        accounts_to_update
+    }
+
+    fn get_accounts_to_update_with_priority(&self) -> Vec<(Pubkey, UpdatePriority)> {
+        let mut accounts_to_update = self.inner.get_accounts_to_update_with_priority();
+        accounts_to_update.extend(
+            self.missing_accounts
+                .iter()
+                .map(|address| (*address, UpdatePriority::Critical)),
+        );
+        accounts_to_update
+    }
+
+    fn get_static_accounts(&self) -> Vec<Pubkey> {
+        let mut static_accounts = self.inner.get_static_accounts();
+        static_accounts.extend(&self.missing_accounts);
+        static_accounts
+    }
+
+    fn update(&mut self, account_map: &AccountMap) -> Result<()> {
+        self.before_update(account_map)?;
+        self.inner.update(account_map)
+    }
+
+    fn update_with_diff(&mut self, account_map: &AccountMap, diff: &AccountMapDiff) -> Result<()> {
+        self.before_update(account_map)?;
+        self.inner.update_with_diff(account_map, diff)
+    }
+
+    fn plan_update(&self) -> UpdatePlan {
+        let mut update_plan = self.inner.plan_update();
+        update_plan.accounts.extend(&self.missing_accounts);
+        update_plan
+    }
+
+    fn apply_update(&mut self, fetched: &AccountMap, plan_id: u64) -> Result<()> {
+        self.before_update(fetched)?;
+        self.inner.apply_update(fetched, plan_id)
+    }
+
+    fn update_shared(&self, account_map: &AccountMap) -> Result<()> {
+        self.before_update(account_map)?;
+        self.inner.update_shared(account_map)
+    }
+
+    fn quote(&self, quote_params: &QuoteParams) -> Result<Quote> {
+        self.before_quote();
+        self.inner.quote(quote_params)
+    }
+
+    fn quote_into(&self, quote_params: &QuoteParams, quote: &mut Quote) -> Result<()> {
+        self.before_quote();
+        self.inner.quote_into(quote_params, quote)
+    }
+
+    fn quote_with_current_token_balance(
+        &self,
+        quote_params: &QuoteParams,
+        token_balances: &dyn TokenBalanceSource,
+    ) -> Result<Quote> {
+        self.before_quote();
+        self.inner
+            .quote_with_current_token_balance(quote_params, token_balances)
+    }
+
+    fn quote_liquidity(&self, liquidity_params: &LiquidityParams) -> Result<LiquidityQuote> {
+        self.before_quote();
+        self.inner.quote_liquidity(liquidity_params)
+    }
+
+    fn get_accounts_len(&self) -> usize {
+        self.accounts_len
+            .unwrap_or_else(|| self.inner.get_accounts_len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{reference::MockAmm, SwapMode};
+    use rust_decimal::Decimal;
+    use std::panic::{catch_unwind, AssertUnwindSafe};
+
+    #[test]
+    fn test_flaky_amm_failure_modes() {
+        let (sol, usdc) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let mock_amm = MockAmm::new(Pubkey::new_unique(), vec![sol, usdc]).with_price(
+            sol,
+            usdc,
+            Decimal::from(150),
+        );
+        let quote_params = QuoteParams {
+            amount: 1_000,
+            input_mint: sol,
+            output_mint: usdc,
+            swap_mode: SwapMode::ExactIn,
+            budget: None,
+            limit_price: None,
+        };
+
+        let amm = FlakyAmm::new(mock_amm.clone_amm());
+        assert_eq!(amm.quote(&quote_params).unwrap().out_amount, 150_000);
+
+        let amm = FlakyAmm::new(mock_amm.clone_amm()).with_panic_on_quote();
+        assert!(catch_unwind(AssertUnwindSafe(|| amm.quote(&quote_params))).is_err());
+        assert!(catch_unwind(AssertUnwindSafe(|| {
+            amm.quote_into(&quote_params, &mut Quote::default())
+        }))
+        .is_err());
+
+        let missing_account = Pubkey::new_unique();
+        let mut amm =
+            FlakyAmm::new(mock_amm.clone_amm()).with_missing_accounts(vec![missing_account]);
+        assert!(amm.get_accounts_to_update().contains(&missing_account));
+        assert!(amm.get_static_accounts().contains(&missing_account));
+        let update_plan = amm.plan_update();
+        assert!(update_plan.accounts.contains(&missing_account));
+        assert!(amm.update(&AccountMap::default()).is_err());
+        assert!(amm
+            .apply_update(&AccountMap::default(), update_plan.plan_id)
+            .is_err());
+
+        let amm = FlakyAmm::new(mock_amm.clone_amm()).with_wrong_accounts_len(3);
+        assert_ne!(amm.get_accounts_len(), mock_amm.get_accounts_len());
+        assert_eq!(amm.capabilities(), mock_amm.capabilities());
+        assert_eq!(
+            amm.fee_mint_for(&sol, &usdc),
+            mock_amm.fee_mint_for(&sol, &usdc)
+        );
+    }
+}
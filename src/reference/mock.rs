@@ -0,0 +1,387 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+use anyhow::{ensure, Context, Result};
+use rust_decimal::{prelude::ToPrimitive, Decimal};
+use serde::Deserialize;
+use smallvec::smallvec;
+use solana_sdk::{instruction::AccountMeta, pubkey::Pubkey};
+
+use crate::{
+    field_as_string, AccountMap, Amm, AmmCapabilities, AmmContext, KeyedAccount, Quote, QuoteError,
+    QuoteParams, Swap, SwapAndAccountMetas, SwapMode, SwapParams,
+};
+
+/// A call made to a [`MockAmm`]
+#[derive(Debug, Clone, PartialEq)]
+pub enum MockAmmCall {
+    Update {
+        addresses: Vec<Pubkey>,
+    },
+    Quote(QuoteParams),
+    GetSwapAndAccountMetas {
+        source_mint: Pubkey,
+        destination_mint: Pubkey,
+        in_amount: u64,
+        out_amount: u64,
+    },
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct MockPrice {
+    #[serde(with = "field_as_string")]
+    input_mint: Pubkey,
+    #[serde(with = "field_as_string")]
+    output_mint: Pubkey,
+    price: Decimal,
+}
+
+/// The `KeyedAccount` params `MockAmm::from_keyed_account` expects
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct MockAmmParams {
+    reserve_mints: Vec<String>,
+    #[serde(default)]
+    prices: Vec<MockPrice>,
+}
+
+/// An Amm quoting from a price table and recording every call, for host side tests
+///
+/// Clones share the call log, so calls made through `clone_amm` remain observable
+#[derive(Debug, Clone)]
+pub struct MockAmm {
+    key: Pubkey,
+    program_id: Pubkey,
+    reserve_mints: Vec<Pubkey>,
+    accounts_to_update: Vec<Pubkey>,
+    /// The out amount per unit of in amount, keyed by input and output mint
+    prices: HashMap<(Pubkey, Pubkey), Decimal>,
+    fee_pct: Decimal,
+    swap: Swap,
+    inner_program_ids: Vec<Pubkey>,
+    calls: Arc<Mutex<Vec<MockAmmCall>>>,
+}
+
+impl MockAmm {
+    pub fn new(key: Pubkey, reserve_mints: Vec<Pubkey>) -> Self {
+        MockAmm {
+            key,
+            program_id: Pubkey::default(),
+            reserve_mints,
+            accounts_to_update: vec![],
+            prices: HashMap::new(),
+            fee_pct: Decimal::ZERO,
+            swap: Swap::TokenSwap,
+            inner_program_ids: vec![],
+            calls: Arc::default(),
+        }
+    }
+
+    /// Quotes `price` units of `output_mint` per unit of `input_mint`
+    pub fn with_price(mut self, input_mint: Pubkey, output_mint: Pubkey, price: Decimal) -> Self {
+        self.prices.insert((input_mint, output_mint), price);
+        self
+    }
+
+    /// The fee taken out of the in amount, as a fraction
+    pub fn with_fee_pct(mut self, fee_pct: Decimal) -> Self {
+        self.fee_pct = fee_pct;
+        self
+    }
+
+    pub fn with_accounts_to_update(mut self, accounts_to_update: Vec<Pubkey>) -> Self {
+        self.accounts_to_update = accounts_to_update;
+        self
+    }
+
+    pub fn with_swap(mut self, swap: Swap) -> Self {
+        self.swap = swap;
+        self
+    }
+
+    /// Routes through `inner_program_ids`, every swap invoking all of them
+    pub fn with_inner_program_ids(mut self, inner_program_ids: Vec<Pubkey>) -> Self {
+        self.inner_program_ids = inner_program_ids;
+        self
+    }
+
+    pub fn calls(&self) -> Vec<MockAmmCall> {
+        self.calls.lock().unwrap().clone()
+    }
+
+    pub fn clear_calls(&self) {
+        self.calls.lock().unwrap().clear();
+    }
+
+    fn record(&self, call: MockAmmCall) {
+        self.calls.lock().unwrap().push(call);
+    }
+}
+
+impl Amm for MockAmm {
+    fn from_keyed_account(keyed_account: &KeyedAccount, _amm_context: &AmmContext) -> Result<Self> {
+        let params: MockAmmParams = keyed_account.decode_params()?;
+        let reserve_mints = params
+            .reserve_mints
+            .iter()
+            .map(|mint| mint.parse())
+            .collect::<Result<_, _>>()?;
+
+        let mut mock_amm = MockAmm::new(keyed_account.key, reserve_mints);
+        mock_amm.program_id = keyed_account.account.owner;
+        for MockPrice {
+            input_mint,
+            output_mint,
+            price,
+        } in params.prices
+        {
+            mock_amm = mock_amm.with_price(input_mint, output_mint, price);
+        }
+        Ok(mock_amm)
+    }
+
+    fn label(&self) -> String {
+        "Mock".to_string()
+    }
+
+    fn program_id(&self) -> Pubkey {
+        self.program_id
+    }
+
+    fn key(&self) -> Pubkey {
+        self.key
+    }
+
+    fn get_reserve_mints(&self) -> Vec<Pubkey> {
+        self.reserve_mints.clone()
+    }
+
+    fn get_accounts_to_update(&self) -> Vec<Pubkey> {
+        self.accounts_to_update.clone()
+    }
+
+    fn update(&mut self, account_map: &AccountMap) -> Result<()> {
+        self.update_shared(account_map)
+    }
+
+    /// The mock state lives behind the shared call log, so updates only need a shared reference
+    fn update_shared(&self, account_map: &AccountMap) -> Result<()> {
+        let mut addresses = account_map.keys().copied().collect::<Vec<_>>();
+        addresses.sort();
+        self.record(MockAmmCall::Update { addresses });
+        Ok(())
+    }
+
+    fn quote(&self, quote_params: &QuoteParams) -> Result<Quote> {
+        self.record(MockAmmCall::Quote(quote_params.clone()));
+
+        let price = *self
+            .prices
+            .get(&(quote_params.input_mint, quote_params.output_mint))
+            .with_context(|| {
+                format!(
+                    "No price from {} to {}",
+                    quote_params.input_mint, quote_params.output_mint
+                )
+            })?;
+        ensure!(
+            price > Decimal::ZERO,
+            "Price from {} to {} is not positive",
+            quote_params.input_mint,
+            quote_params.output_mint
+        );
+        ensure!(
+            (Decimal::ZERO..Decimal::ONE).contains(&self.fee_pct),
+            "Fee pct {} is not within [0, 1)",
+            self.fee_pct
+        );
+        let overflow = QuoteError::ExceedsMaxTradeSize { max_in: None };
+        let amount = Decimal::from(quote_params.amount);
+        let net_pct = Decimal::ONE - self.fee_pct;
+        let (in_amount, out_amount) = match quote_params.swap_mode {
+            SwapMode::ExactIn => (
+                amount,
+                amount
+                    .checked_mul(net_pct)
+                    .and_then(|amount| amount.checked_mul(price))
+                    .ok_or(overflow.clone())?
+                    .floor(),
+            ),
+            SwapMode::ExactOut => (
+                amount
+                    .checked_div(price)
+                    .and_then(|amount| amount.checked_div(net_pct))
+                    .ok_or(overflow.clone())?
+                    .ceil(),
+                amount,
+            ),
+        };
+        let fee_amount = in_amount.checked_mul(self.fee_pct).ok_or(overflow)?.ceil();
+
+        let to_u64 = |amount: Decimal| {
+            amount
+                .to_u64()
+                .ok_or(QuoteError::ExceedsMaxTradeSize { max_in: None })
+        };
+        Ok(Quote {
+            in_amount: to_u64(in_amount)?,
+            out_amount: to_u64(out_amount)?,
+            fee_amount: to_u64(fee_amount)?,
+            fee_mint: quote_params.input_mint,
+            fee_pct: self.fee_pct,
+            swap_mode: quote_params.swap_mode,
+            amount_specified: quote_params.amount,
+            ..Quote::default()
+        })
+    }
+
+    fn get_swap_and_account_metas(&self, swap_params: &SwapParams) -> Result<SwapAndAccountMetas> {
+        self.record(MockAmmCall::GetSwapAndAccountMetas {
+            source_mint: swap_params.source_mint,
+            destination_mint: swap_params.destination_mint,
+            in_amount: swap_params.in_amount,
+            out_amount: swap_params.out_amount,
+        });
+
+        Ok(SwapAndAccountMetas::new(
+            self.swap.clone(),
+            smallvec![
+                AccountMeta::new_readonly(self.program_id, false),
+                AccountMeta::new(self.key, false),
+                AccountMeta::new_readonly(swap_params.token_transfer_authority, true),
+                AccountMeta::new(swap_params.source_token_account, false),
+                AccountMeta::new(swap_params.destination_token_account, false),
+            ],
+        )
+        .with_cpi_program_ids(self.inner_program_ids.clone()))
+    }
+
+    fn supports_exact_out(&self) -> bool {
+        true
+    }
+
+    fn capabilities(&self) -> AmmCapabilities {
+        AmmCapabilities::EXACT_OUT | AmmCapabilities::SHARED_UPDATE
+    }
+
+    fn inner_program_ids(&self) -> Vec<Pubkey> {
+        self.inner_program_ids.clone()
+    }
+
+    fn clone_amm(&self) -> Box<dyn Amm + Send + Sync> {
+        Box::new(self.clone())
+    }
+
+    fn get_accounts_len(&self) -> usize {
+        5
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ClockRef;
+    use serde_json::json;
+    use solana_sdk::account::Account;
+
+    #[test]
+    fn test_mock_amm_quotes_and_records_calls() {
+        let usdc = Pubkey::new_unique();
+        let sol = Pubkey::new_unique();
+        let keyed_account = KeyedAccount {
+            key: Pubkey::new_unique(),
+            account: Account::default(),
+            params: Some(json!({
+                "reserveMints": [usdc.to_string(), sol.to_string()],
+                "prices": [{
+                    "inputMint": sol.to_string(),
+                    "outputMint": usdc.to_string(),
+                    "price": "150.5",
+                }],
+            })),
+        };
+        let amm_context = AmmContext::new(ClockRef::default());
+        let mock_amm = MockAmm::from_keyed_account(&keyed_account, &amm_context).unwrap();
+        assert_eq!(mock_amm.get_reserve_mints(), vec![usdc, sol]);
+
+        let amm = mock_amm.clone_amm();
+        let quote_params = QuoteParams {
+            amount: 2_000,
+            input_mint: sol,
+            output_mint: usdc,
+            swap_mode: SwapMode::ExactIn,
+            budget: None,
+            limit_price: None,
+        };
+        assert_eq!(amm.quote(&quote_params).unwrap().out_amount, 301_000);
+        let reverse_quote_params = QuoteParams {
+            amount: 1_000,
+            input_mint: usdc,
+            output_mint: sol,
+            swap_mode: SwapMode::ExactIn,
+            budget: None,
+            limit_price: None,
+        };
+        assert!(amm.quote(&reverse_quote_params).is_err());
+
+        assert_eq!(
+            mock_amm.calls(),
+            vec![
+                MockAmmCall::Quote(quote_params),
+                MockAmmCall::Quote(reverse_quote_params)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_update_shared() {
+        let mock_amm = MockAmm::new(Pubkey::new_unique(), vec![]);
+        let amm: &dyn Amm = &mock_amm;
+        assert!(amm.capabilities().contains(AmmCapabilities::SHARED_UPDATE));
+
+        let address = Pubkey::new_unique();
+        let mut account_map = AccountMap::default();
+        account_map.insert(address, Account::default());
+        amm.update_shared(&account_map).unwrap();
+        assert_eq!(
+            mock_amm.calls(),
+            vec![MockAmmCall::Update {
+                addresses: vec![address]
+            }]
+        );
+    }
+
+    #[test]
+    fn test_quote_rejects_invalid_prices_and_fees() {
+        let (usdc, sol) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let quote_params = QuoteParams {
+            amount: 1_000,
+            input_mint: sol,
+            output_mint: usdc,
+            swap_mode: SwapMode::ExactOut,
+            ..QuoteParams::default()
+        };
+        let mock_amm = MockAmm::new(Pubkey::new_unique(), vec![usdc, sol]);
+        assert!(mock_amm
+            .clone()
+            .with_price(sol, usdc, Decimal::ZERO)
+            .quote(&quote_params)
+            .is_err());
+        let mock_amm = mock_amm.with_price(sol, usdc, Decimal::from(150));
+        assert!(mock_amm
+            .clone()
+            .with_fee_pct(Decimal::ONE)
+            .quote(&quote_params)
+            .is_err());
+        assert!(mock_amm
+            .with_price(sol, usdc, Decimal::new(1, 28))
+            .quote(&QuoteParams {
+                amount: u64::MAX,
+                ..quote_params
+            })
+            .is_err());
+    }
+}
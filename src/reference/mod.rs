@@ -0,0 +1,12 @@
+//! Reference Amm implementations, serving as executable documentation of the trait contract
+mod constant_product;
+mod flaky;
+mod mock;
+mod spl_wrap;
+
+pub use constant_product::ConstantProductAmm;
+#[cfg(any(test, feature = "bench"))]
+pub use constant_product::TokenSwapFixture;
+pub use flaky::FlakyAmm;
+pub use mock::{MockAmm, MockAmmCall};
+pub use spl_wrap::{SplWrapAmm, NATIVE_SOL_PSEUDO_MINT};
@@ -0,0 +1,275 @@
+use anyhow::{anyhow, Result};
+use rust_decimal::Decimal;
+use solana_sdk::{
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+    system_instruction, system_program,
+};
+
+use crate::{
+    AccountMap, AdjacentInstructions, Amm, AmmContext, FeeSchedule, KeyedAccount, Quote,
+    QuoteParams, RoutingHints, SwapAndAccountMetas, SwapParams, SPL_TOKEN_PROGRAM_ID, WSOL_MINT,
+};
+
+const SYNC_NATIVE_INSTRUCTION: u8 = 17;
+const CLOSE_ACCOUNT_INSTRUCTION: u8 = 9;
+
+/// Stands for the lamports of the user in routing graphs, as opposed to wSOL held in a token account
+pub const NATIVE_SOL_PSEUDO_MINT: Pubkey = system_program::ID;
+
+/// Wrapping SOL into wSOL and unwrapping it as a zero fee pseudo pool, so routing graphs treat it as an edge
+///
+/// Quotes are 1:1. The onchain aggregator has no Swap variant for wrapping, so the edge runs as adjacent instructions
+/// around the route instruction instead of a route leg, hosts skip any `RoutingHints::adjacent_only` leg in the route plan
+/// rather than special casing wrapped SOL
+#[derive(Debug, Clone)]
+pub struct SplWrapAmm {
+    key: Pubkey,
+}
+
+impl Default for SplWrapAmm {
+    fn default() -> Self {
+        SplWrapAmm { key: WSOL_MINT }
+    }
+}
+
+impl Amm for SplWrapAmm {
+    fn from_keyed_account(keyed_account: &KeyedAccount, _amm_context: &AmmContext) -> Result<Self> {
+        Ok(SplWrapAmm {
+            key: keyed_account.key,
+        })
+    }
+
+    fn label(&self) -> String {
+        "SplWrap".to_string()
+    }
+
+    fn program_id(&self) -> Pubkey {
+        SPL_TOKEN_PROGRAM_ID
+    }
+
+    fn key(&self) -> Pubkey {
+        self.key
+    }
+
+    fn get_reserve_mints(&self) -> Vec<Pubkey> {
+        vec![NATIVE_SOL_PSEUDO_MINT, WSOL_MINT]
+    }
+
+    fn get_accounts_to_update(&self) -> Vec<Pubkey> {
+        vec![]
+    }
+
+    fn update(&mut self, _account_map: &AccountMap) -> Result<()> {
+        Ok(())
+    }
+
+    fn quote(&self, quote_params: &QuoteParams) -> Result<Quote> {
+        let reserve_mints = self.get_reserve_mints();
+        if !reserve_mints.contains(&quote_params.input_mint)
+            || !reserve_mints.contains(&quote_params.output_mint)
+            || quote_params.input_mint == quote_params.output_mint
+        {
+            return Err(anyhow!(
+                "{} does not trade {} for {}",
+                self.label(),
+                quote_params.input_mint,
+                quote_params.output_mint
+            ));
+        }
+
+        Ok(Quote {
+            in_amount: quote_params.amount,
+            out_amount: quote_params.amount,
+            fee_mint: quote_params.input_mint,
+            fee_pct: Decimal::ZERO,
+            swap_mode: quote_params.swap_mode,
+            amount_specified: quote_params.amount,
+            ..Quote::default()
+        })
+    }
+
+    fn quote_depends_on_clock(&self) -> bool {
+        false
+    }
+
+    fn fee_schedule(&self) -> Option<FeeSchedule> {
+        Some(FeeSchedule::flat(0))
+    }
+
+    fn routing_hints(&self) -> RoutingHints {
+        RoutingHints {
+            adjacent_only: true,
+            ..RoutingHints::default()
+        }
+    }
+
+    /// Always fails, there is no route leg to build, see `get_adjacent_instructions`
+    fn get_swap_and_account_metas(&self, _swap_params: &SwapParams) -> Result<SwapAndAccountMetas> {
+        Err(anyhow!(
+            "{} is not a route leg, see get_adjacent_instructions",
+            self.label()
+        ))
+    }
+
+    /// Wrapping transfers the lamports to the wSOL account and syncs it, unwrapping closes the wSOL account
+    ///
+    /// Closing unwraps the whole balance of `source_token_account` and its rent to `token_transfer_authority`,
+    /// not only `in_amount`, so the source has to be a temporary wSOL account dedicated to the route
+    fn get_adjacent_instructions(&self, swap_params: &SwapParams) -> Result<AdjacentInstructions> {
+        match (swap_params.source_mint, swap_params.destination_mint) {
+            (NATIVE_SOL_PSEUDO_MINT, WSOL_MINT) => Ok(AdjacentInstructions {
+                pre: vec![
+                    system_instruction::transfer(
+                        &swap_params.token_transfer_authority,
+                        &swap_params.destination_token_account,
+                        swap_params.in_amount,
+                    ),
+                    Instruction {
+                        program_id: SPL_TOKEN_PROGRAM_ID,
+                        accounts: vec![AccountMeta::new(
+                            swap_params.destination_token_account,
+                            false,
+                        )],
+                        data: vec![SYNC_NATIVE_INSTRUCTION],
+                    },
+                ],
+                post: vec![],
+            }),
+            (WSOL_MINT, NATIVE_SOL_PSEUDO_MINT) => Ok(AdjacentInstructions {
+                pre: vec![],
+                post: vec![Instruction {
+                    program_id: SPL_TOKEN_PROGRAM_ID,
+                    accounts: vec![
+                        AccountMeta::new(swap_params.source_token_account, false),
+                        AccountMeta::new(swap_params.token_transfer_authority, false),
+                        AccountMeta::new_readonly(swap_params.token_transfer_authority, true),
+                    ],
+                    data: vec![CLOSE_ACCOUNT_INSTRUCTION],
+                }],
+            }),
+            (source_mint, destination_mint) => Err(anyhow!(
+                "{} does not trade {source_mint} for {destination_mint}",
+                self.label()
+            )),
+        }
+    }
+
+    fn supports_exact_out(&self) -> bool {
+        true
+    }
+
+    fn clone_amm(&self) -> Box<dyn Amm + Send + Sync> {
+        Box::new(self.clone())
+    }
+
+    fn get_accounts_len(&self) -> usize {
+        0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{graph, MissingAccountPolicy, SwapMode};
+
+    #[test]
+    fn test_spl_wrap_amm() {
+        let amm = SplWrapAmm::default();
+        let quote = amm
+            .quote(&QuoteParams {
+                amount: 1_000_000_000,
+                input_mint: NATIVE_SOL_PSEUDO_MINT,
+                output_mint: WSOL_MINT,
+                swap_mode: SwapMode::ExactOut,
+                budget: None,
+                limit_price: None,
+            })
+            .unwrap();
+        assert_eq!(
+            (quote.in_amount, quote.out_amount),
+            (1_000_000_000, 1_000_000_000)
+        );
+        assert_eq!(quote.fee_amount, 0);
+
+        let jupiter_program_id = Pubkey::new_unique();
+        let wsol_token_account = Pubkey::new_unique();
+        let user = Pubkey::new_unique();
+        let swap_params = SwapParams {
+            swap_mode: SwapMode::ExactIn,
+            in_amount: 1_000_000_000,
+            out_amount: 1_000_000_000,
+            source_mint: NATIVE_SOL_PSEUDO_MINT,
+            destination_mint: WSOL_MINT,
+            source_token_account: user,
+            destination_token_account: wsol_token_account,
+            token_transfer_authority: user,
+            user: Some(user),
+            open_order_address: None,
+            quote_mint_to_referrer: None,
+            jupiter_program_id: &jupiter_program_id,
+            missing_account_policy: MissingAccountPolicy::Error,
+            #[allow(deprecated)]
+            missing_dynamic_accounts_as_default: false,
+            nonce_provider: None,
+            placeholder_registry: None,
+            exact_out_overshoot_bps: None,
+            is_native_source: true,
+            is_native_destination: false,
+        };
+        let adjacent_instructions = amm.get_adjacent_instructions(&swap_params).unwrap();
+        assert_eq!(adjacent_instructions.pre.len(), 2);
+        assert_eq!(
+            adjacent_instructions.pre[1].data,
+            vec![SYNC_NATIVE_INSTRUCTION]
+        );
+        assert!(adjacent_instructions.post.is_empty());
+        assert!(amm.get_swap_and_account_metas(&swap_params).is_err());
+        assert!(amm.routing_hints().adjacent_only);
+
+        let unwrap_swap_params = SwapParams {
+            source_mint: WSOL_MINT,
+            destination_mint: NATIVE_SOL_PSEUDO_MINT,
+            source_token_account: wsol_token_account,
+            destination_token_account: user,
+            is_native_source: false,
+            is_native_destination: true,
+            ..swap_params
+        };
+        let adjacent_instructions = amm.get_adjacent_instructions(&unwrap_swap_params).unwrap();
+        assert!(adjacent_instructions.pre.is_empty());
+        assert_eq!(
+            adjacent_instructions.post[0].data,
+            vec![CLOSE_ACCOUNT_INSTRUCTION]
+        );
+        assert_eq!(
+            adjacent_instructions.post[0].accounts[1].pubkey,
+            swap_params.token_transfer_authority
+        );
+
+        // Both mints are checked, e.g. a wSOL to wSOL swap is neither a wrap nor an unwrap
+        for (source_mint, destination_mint) in [
+            (WSOL_MINT, WSOL_MINT),
+            (Pubkey::new_unique(), WSOL_MINT),
+            (WSOL_MINT, Pubkey::new_unique()),
+        ] {
+            assert!(amm
+                .get_adjacent_instructions(&SwapParams {
+                    source_mint,
+                    destination_mint,
+                    ..swap_params
+                })
+                .is_err());
+        }
+
+        let token_graph = graph::build(&[amm.clone_amm()]);
+        assert_eq!(
+            token_graph.neighbors(&WSOL_MINT),
+            vec![NATIVE_SOL_PSEUDO_MINT]
+        );
+        assert_eq!(
+            token_graph.neighbors(&NATIVE_SOL_PSEUDO_MINT),
+            vec![WSOL_MINT]
+        );
+    }
+}
@@ -0,0 +1,198 @@
+//! Validation of routes stitched from the quotes of several Amms
+use std::collections::HashMap;
+
+use anyhow::{anyhow, ensure, Result};
+use solana_sdk::{address_lookup_table::AddressLookupTableAccount, pubkey::Pubkey};
+
+use crate::{
+    tx_size::{self, TxSizeEstimate},
+    Amm, Quote, QuoteParams, SwapAndAccountMetas,
+};
+
+/// The account locks routers allow per transaction
+pub const MAX_ROUTE_ACCOUNTS: usize = 64;
+
+/// The aggregate of a validated route
+#[derive(Debug, Clone, PartialEq)]
+pub struct RouteSummary {
+    pub input_mint: Pubkey,
+    pub output_mint: Pubkey,
+    pub in_amount: u64,
+    pub out_amount: u64,
+    /// Net fee amounts summed per fee mint, negative when rebates outweigh fees
+    pub fees: HashMap<Pubkey, i64>,
+    /// The sum of `get_accounts_len` over the legs
+    pub accounts_len: usize,
+    /// The v0 transaction of the route instruction of `program_id`, using the lookup tables
+    pub tx_size: TxSizeEstimate,
+}
+
+/// Checks that the legs chain into a route whose instruction of `program_id` fits in a v0 transaction using `alts`
+///
+/// `swap_and_account_metas` holds the built swap of each leg, in the same order
+pub fn validate(
+    program_id: &Pubkey,
+    legs: &[(&dyn Amm, QuoteParams, Quote)],
+    swap_and_account_metas: &[SwapAndAccountMetas],
+    alts: &[AddressLookupTableAccount],
+) -> Result<RouteSummary> {
+    let ((_, first_quote_params, first_quote), (_, last_quote_params, last_quote)) = legs
+        .first()
+        .zip(legs.last())
+        .ok_or_else(|| anyhow!("Route has no legs"))?;
+    ensure!(
+        swap_and_account_metas.len() == legs.len(),
+        "Route has {} legs but {} swaps",
+        legs.len(),
+        swap_and_account_metas.len()
+    );
+
+    for window in legs.windows(2) {
+        let (_, quote_params, quote) = &window[0];
+        let (next_amm, next_quote_params, next_quote) = &window[1];
+        ensure!(
+            quote_params.output_mint == next_quote_params.input_mint,
+            "Leg outputs {} but {} expects {}",
+            quote_params.output_mint,
+            next_amm.label(),
+            next_quote_params.input_mint
+        );
+        ensure!(
+            next_quote.in_amount <= quote.out_amount,
+            "{} consumes {} but the previous leg only produces {}",
+            next_amm.label(),
+            next_quote.in_amount,
+            quote.out_amount
+        );
+    }
+
+    let mut fees = HashMap::new();
+    let mut accounts_len = 0;
+    for (amm, quote_params, quote) in legs {
+        ensure!(
+            amm.get_reserve_mints().contains(&quote_params.input_mint)
+                && amm.get_reserve_mints().contains(&quote_params.output_mint),
+            "{} does not trade {} for {}",
+            amm.label(),
+            quote_params.input_mint,
+            quote_params.output_mint
+        );
+        let fee = fees.entry(quote.fee_mint).or_insert(0i64);
+        *fee = fee.saturating_add(quote.net_fee_amount());
+        accounts_len += amm.get_accounts_len();
+    }
+    ensure!(
+        accounts_len <= MAX_ROUTE_ACCOUNTS,
+        "Route needs {accounts_len} accounts, more than {MAX_ROUTE_ACCOUNTS}"
+    );
+
+    let tx_size = tx_size::estimate(program_id, swap_and_account_metas, alts);
+    ensure!(
+        tx_size.fits(),
+        "Route transaction takes an estimated {} bytes, more than a packet",
+        tx_size.size
+    );
+
+    Ok(RouteSummary {
+        input_mint: first_quote_params.input_mint,
+        output_mint: last_quote_params.output_mint,
+        in_amount: first_quote.in_amount,
+        out_amount: last_quote.out_amount,
+        fees,
+        accounts_len,
+        tx_size,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{reference::MockAmm, Swap, SwapMode};
+    use rust_decimal::Decimal;
+    use smallvec::smallvec;
+    use solana_sdk::instruction::AccountMeta;
+
+    #[test]
+    fn test_validate_route() {
+        let (sol, usdc, bonk) = (
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+        );
+        let sol_usdc = MockAmm::new(Pubkey::new_unique(), vec![sol, usdc])
+            .with_price(sol, usdc, Decimal::from(150))
+            .with_fee_pct(Decimal::new(3, 3));
+        let usdc_bonk = MockAmm::new(Pubkey::new_unique(), vec![usdc, bonk]).with_price(
+            usdc,
+            bonk,
+            Decimal::from(40_000),
+        );
+        let leg = |amm: &MockAmm, input_mint, output_mint, amount| {
+            let quote_params = QuoteParams {
+                amount,
+                input_mint,
+                output_mint,
+                swap_mode: SwapMode::ExactIn,
+                budget: None,
+                limit_price: None,
+            };
+            let quote = amm.quote(&quote_params).unwrap();
+            (quote_params, quote)
+        };
+        let swap_and_account_metas = |amm: &MockAmm| {
+            SwapAndAccountMetas::new(
+                Swap::TokenSwap,
+                smallvec![
+                    AccountMeta::new_readonly(amm.program_id(), false),
+                    AccountMeta::new(amm.key(), false),
+                ],
+            )
+        };
+        let (sol_usdc_params, sol_usdc_quote) = leg(&sol_usdc, sol, usdc, 1_000);
+        let (usdc_bonk_params, usdc_bonk_quote) = leg(&usdc_bonk, usdc, bonk, 149_000);
+        let usdc_bonk_quote = Quote {
+            fee_amount_signed: Some(-5),
+            ..usdc_bonk_quote
+        };
+
+        let program_id = Pubkey::new_unique();
+        let swaps = [
+            swap_and_account_metas(&sol_usdc),
+            swap_and_account_metas(&usdc_bonk),
+        ];
+        let route_summary = validate(
+            &program_id,
+            &[
+                (&sol_usdc, sol_usdc_params.clone(), sol_usdc_quote.clone()),
+                (
+                    &usdc_bonk,
+                    usdc_bonk_params.clone(),
+                    usdc_bonk_quote.clone(),
+                ),
+            ],
+            &swaps,
+            &[],
+        )
+        .unwrap();
+        assert_eq!(
+            (route_summary.input_mint, route_summary.output_mint),
+            (sol, bonk)
+        );
+        assert_eq!(route_summary.out_amount, 5_960_000_000);
+        assert_eq!(route_summary.fees, HashMap::from([(sol, 3), (usdc, -5)]));
+        assert_eq!(route_summary.accounts_len, 10);
+        assert!(route_summary.tx_size.fits());
+
+        // Legs out of order break mint continuity
+        assert!(validate(
+            &program_id,
+            &[
+                (&usdc_bonk, usdc_bonk_params, usdc_bonk_quote),
+                (&sol_usdc, sol_usdc_params, sol_usdc_quote),
+            ],
+            &swaps,
+            &[],
+        )
+        .is_err());
+    }
+}
@@ -0,0 +1,52 @@
+use std::time::Duration;
+
+/// Venue specific routing guidance, see `Amm::routing_hints`
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct RoutingHints {
+    /// The pool is deep enough to be preferred as an intermediate hop
+    pub prefer_as_intermediate: bool,
+    /// The last leg index, starting at 0, the Amm can be used at
+    pub max_route_position: Option<u8>,
+    /// The Amm can only be the last leg of a route, e.g. RFQ venues
+    pub last_hop_only: bool,
+    /// How long to skip the Amm after a failed swap
+    pub cooldown_after_failure: Option<Duration>,
+    /// The swap is entirely done by `Amm::get_adjacent_instructions`, the leg has no route instruction accounts
+    pub adjacent_only: bool,
+}
+
+impl RoutingHints {
+    /// Indicates if the Amm can be leg `position` of a route of `route_len` legs
+    pub fn allows_position(&self, position: usize, route_len: usize) -> bool {
+        if self.last_hop_only && position + 1 != route_len {
+            return false;
+        }
+        match self.max_route_position {
+            Some(max_route_position) => position <= usize::from(max_route_position),
+            None => true,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_allows_position() {
+        let routing_hints = RoutingHints {
+            last_hop_only: true,
+            ..RoutingHints::default()
+        };
+        assert!(routing_hints.allows_position(0, 1));
+        assert!(!routing_hints.allows_position(0, 2));
+        assert!(routing_hints.allows_position(1, 2));
+
+        let routing_hints = RoutingHints {
+            max_route_position: Some(0),
+            ..RoutingHints::default()
+        };
+        assert!(routing_hints.allows_position(0, 2));
+        assert!(!routing_hints.allows_position(1, 2));
+    }
+}
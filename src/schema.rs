@@ -0,0 +1,42 @@
+//! The JSON schema of the serde types, for non Rust services to generate clients from
+use schemars::gen::SchemaGenerator;
+use serde_json::{json, Value};
+
+use crate::{pair::CanonicalPair, Market, MarketV2, Quote, QuoteParams, QuoteWarning, Side, Swap};
+
+/// A draft-07 schema whose `definitions` hold every exported type by name
+pub fn export_all() -> Value {
+    let mut generator = SchemaGenerator::default();
+    generator.subschema_for::<QuoteParams>();
+    generator.subschema_for::<Quote>();
+    generator.subschema_for::<QuoteWarning>();
+    generator.subschema_for::<Market>();
+    generator.subschema_for::<MarketV2>();
+    generator.subschema_for::<Swap>();
+    generator.subschema_for::<Side>();
+    generator.subschema_for::<CanonicalPair>();
+
+    json!({
+        "$schema": "http://json-schema.org/draft-07/schema#",
+        "definitions": generator.take_definitions(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_export_all() {
+        let schema = export_all();
+        let definitions = schema["definitions"].as_object().unwrap();
+        for name in ["Quote", "QuoteParams", "SwapMode", "Market", "Swap", "Side"] {
+            assert!(definitions.contains_key(name), "Missing {name}");
+        }
+        // Pubkeys and amounts are strings on the wire
+        assert_eq!(
+            definitions["Quote"]["properties"]["inAmount"]["type"],
+            "string"
+        );
+    }
+}
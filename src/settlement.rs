@@ -0,0 +1,19 @@
+use solana_sdk::pubkey::Pubkey;
+
+/// What a venue settles against, beyond the mints it shows, for transaction builders to check before simulating
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct SettlementConstraints {
+    /// One side of every swap has to be one of these mints, e.g. WSOL or USDC, empty if any
+    pub required_quote_mints: Vec<Pubkey>,
+    /// The destination token account has to be the associated token account of the user
+    pub destination_must_be_ata: bool,
+}
+
+impl SettlementConstraints {
+    /// Indicates if a swap from `input_mint` to `output_mint` settles against a required quote mint
+    pub fn allows_pair(&self, input_mint: &Pubkey, output_mint: &Pubkey) -> bool {
+        self.required_quote_mints.is_empty()
+            || self.required_quote_mints.contains(input_mint)
+            || self.required_quote_mints.contains(output_mint)
+    }
+}
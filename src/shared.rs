@@ -0,0 +1,132 @@
+//! Quoting from many threads while a single updater refreshes the Amm, see [`SharedAmm`]
+use std::sync::{Arc, RwLock, RwLockReadGuard};
+
+use anyhow::{anyhow, Result};
+use solana_sdk::pubkey::Pubkey;
+
+use crate::{AccountMap, Amm, Quote, QuoteParams, SwapAndAccountMetas, SwapParams};
+
+/// The `&self` half of [`Amm`], safe to call from many threads while another one updates
+pub trait AmmQuote {
+    fn key(&self) -> Pubkey;
+    fn quote(&self, quote_params: &QuoteParams) -> Result<Quote>;
+    fn get_swap_and_account_metas(&self, swap_params: &SwapParams) -> Result<SwapAndAccountMetas>;
+}
+
+/// The `&mut self` half of [`Amm`], driven by a single updater
+pub trait AmmState {
+    fn get_accounts_to_update(&self) -> Vec<Pubkey>;
+    fn update(&mut self, account_map: &AccountMap) -> Result<()>;
+}
+
+/// Both halves of [`Amm`]
+pub trait AmmQuoteAndState: AmmQuote + AmmState {}
+
+impl<T: Amm + ?Sized> AmmQuote for T {
+    fn key(&self) -> Pubkey {
+        Amm::key(self)
+    }
+
+    fn quote(&self, quote_params: &QuoteParams) -> Result<Quote> {
+        Amm::quote(self, quote_params)
+    }
+
+    fn get_swap_and_account_metas(&self, swap_params: &SwapParams) -> Result<SwapAndAccountMetas> {
+        Amm::get_swap_and_account_metas(self, swap_params)
+    }
+}
+
+impl<T: Amm + ?Sized> AmmState for T {
+    fn get_accounts_to_update(&self) -> Vec<Pubkey> {
+        Amm::get_accounts_to_update(self)
+    }
+
+    fn update(&mut self, account_map: &AccountMap) -> Result<()> {
+        Amm::update(self, account_map)
+    }
+}
+
+impl<T: AmmQuote + AmmState + ?Sized> AmmQuoteAndState for T {}
+
+/// An Amm shared between quoting threads and an updater thread, cloning it only clones the handle
+pub struct SharedAmm<A: AmmQuoteAndState + ?Sized = dyn Amm + Send + Sync> {
+    inner: Arc<RwLock<Box<A>>>,
+}
+
+impl<A: AmmQuoteAndState + ?Sized> SharedAmm<A> {
+    pub fn new(amm: Box<A>) -> Self {
+        SharedAmm {
+            inner: Arc::new(RwLock::new(amm)),
+        }
+    }
+
+    /// Read access to the Amm, holding off updates until the guard is dropped
+    pub fn read(&self) -> Result<RwLockReadGuard<'_, Box<A>>> {
+        self.inner.read().map_err(|_| anyhow!("Amm lock poisoned"))
+    }
+
+    pub fn quote(&self, quote_params: &QuoteParams) -> Result<Quote> {
+        self.read()?.quote(quote_params)
+    }
+
+    pub fn get_swap_and_account_metas(
+        &self,
+        swap_params: &SwapParams,
+    ) -> Result<SwapAndAccountMetas> {
+        self.read()?.get_swap_and_account_metas(swap_params)
+    }
+
+    pub fn get_accounts_to_update(&self) -> Result<Vec<Pubkey>> {
+        Ok(self.read()?.get_accounts_to_update())
+    }
+
+    /// Updates the Amm in place under the write lock, quotes wait for the update to complete
+    ///
+    /// The lock is left poisoned if the update panics, as the Amm may be half updated
+    pub fn update(&self, account_map: &AccountMap) -> Result<()> {
+        self.inner
+            .write()
+            .map_err(|_| anyhow!("Amm lock poisoned"))?
+            .update(account_map)
+    }
+}
+
+impl<A: AmmQuoteAndState + ?Sized> Clone for SharedAmm<A> {
+    fn clone(&self) -> Self {
+        SharedAmm {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{reference::TokenSwapFixture, SwapMode};
+
+    #[test]
+    fn test_shared_amm_update_is_seen_by_clones() {
+        let fixture = TokenSwapFixture::new(1_000_000, 1_000_000);
+        let shared_amm: SharedAmm =
+            SharedAmm::new(Box::new(fixture.amm().unwrap()) as Box<dyn Amm + Send + Sync>);
+        let quoting_amm = shared_amm.clone();
+        let quote_params = QuoteParams {
+            amount: 1_000,
+            input_mint: fixture.token_a_mint,
+            output_mint: fixture.token_b_mint,
+            swap_mode: SwapMode::ExactIn,
+            ..QuoteParams::default()
+        };
+        let out_amount = quoting_amm.quote(&quote_params).unwrap().out_amount;
+
+        let mut account_map = fixture.account_map.clone();
+        for account in account_map.values_mut() {
+            if account.data[..32] == fixture.token_b_mint.to_bytes() {
+                account.data[64..72].copy_from_slice(&2_000_000u64.to_le_bytes());
+            }
+        }
+        shared_amm.update(&account_map).unwrap();
+        assert!(quoting_amm.quote(&quote_params).unwrap().out_amount > out_amount);
+        assert!(shared_amm.update(&AccountMap::default()).is_err());
+    }
+}
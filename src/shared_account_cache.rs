@@ -0,0 +1,126 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, PoisonError, RwLock},
+};
+
+use solana_sdk::{account::Account, clock::Slot, pubkey::Pubkey};
+
+use crate::AccountMap;
+
+/// An account of the [`SharedAccountCache`] along with the slot it was fetched at
+#[derive(Debug, Clone, PartialEq)]
+pub struct SlotAccount {
+    pub slot: Slot,
+    pub account: Arc<Account>,
+}
+
+/// Accounts shared by many pools, e.g. DEX configs or oracles, hydrated once per cycle by the host
+///
+/// Clones share the same cache, adapters read from it during `update`
+#[derive(Debug, Default, Clone)]
+pub struct SharedAccountCache {
+    accounts: Arc<RwLock<HashMap<Pubkey, SlotAccount, ahash::RandomState>>>,
+}
+
+impl SharedAccountCache {
+    /// Inserts the account unless a more recent slot of it is already cached, returns if it was inserted
+    pub fn insert(&self, address: Pubkey, account: Account, slot: Slot) -> bool {
+        let mut accounts = self
+            .accounts
+            .write()
+            .unwrap_or_else(PoisonError::into_inner);
+        if accounts
+            .get(&address)
+            .is_some_and(|slot_account| slot_account.slot > slot)
+        {
+            return false;
+        }
+        accounts.insert(
+            address,
+            SlotAccount {
+                slot,
+                account: Arc::new(account),
+            },
+        );
+        true
+    }
+
+    pub fn get(&self, address: &Pubkey) -> Option<SlotAccount> {
+        self.accounts
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(address)
+            .cloned()
+    }
+
+    /// The account if it was fetched at `min_slot` or later
+    pub fn get_since(&self, address: &Pubkey, min_slot: Slot) -> Option<SlotAccount> {
+        self.get(address)
+            .filter(|slot_account| slot_account.slot >= min_slot)
+    }
+
+    pub fn remove(&self, address: &Pubkey) -> Option<SlotAccount> {
+        self.accounts
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .remove(address)
+    }
+
+    /// Copies the cached accounts among `addresses` into `account_map`, returning the ones not cached
+    pub fn fill_account_map(
+        &self,
+        addresses: &[Pubkey],
+        account_map: &mut AccountMap,
+    ) -> Vec<Pubkey> {
+        let accounts = self.accounts.read().unwrap_or_else(PoisonError::into_inner);
+        addresses
+            .iter()
+            .filter(|address| match accounts.get(address) {
+                Some(slot_account) => {
+                    account_map.insert(**address, Account::clone(&slot_account.account));
+                    false
+                }
+                None => true,
+            })
+            .copied()
+            .collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.accounts
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shared_account_cache_keeps_most_recent_slot() {
+        let shared_accounts = SharedAccountCache::default();
+        let address = Pubkey::new_unique();
+        let account = |lamports| Account {
+            lamports,
+            ..Account::default()
+        };
+        assert!(shared_accounts.insert(address, account(1), 10));
+        assert!(!shared_accounts.clone().insert(address, account(2), 9));
+        assert_eq!(shared_accounts.get(&address).unwrap().account.lamports, 1);
+        assert!(shared_accounts.get_since(&address, 11).is_none());
+
+        let missing = Pubkey::new_unique();
+        let mut account_map = AccountMap::default();
+        assert_eq!(
+            shared_accounts.fill_account_map(&[address, missing], &mut account_map),
+            vec![missing]
+        );
+        assert_eq!(account_map[&address].lamports, 1);
+    }
+}
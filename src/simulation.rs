@@ -0,0 +1,108 @@
+use anyhow::{ensure, Context, Result};
+
+use crate::{Quote, Slippage, ThresholdAmounts};
+
+/// Token balances of the source and destination token accounts around a simulated swap
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct SwapBalances {
+    pub source: u64,
+    pub destination: u64,
+}
+
+/// Thresholds a simulated swap has to satisfy to be considered consistent with its quote
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct SimulationExpectation {
+    /// The most the source token account is allowed to be debited
+    pub max_in_amount: u64,
+    /// The least the destination token account has to be credited
+    pub min_out_amount: u64,
+}
+
+impl SimulationExpectation {
+    /// Derives the thresholds from a quote, applying the slippage only to the side computed by the quote swap mode
+    /// The ExactOut in amount padded is the quote max in amount when the Amm declared an overshoot
+    pub fn from_quote(quote: &Quote, slippage: Slippage) -> Self {
+        let quote = Quote {
+            in_amount: quote.max_in_amount.unwrap_or(quote.in_amount),
+            ..quote.clone()
+        };
+        let ThresholdAmounts {
+            max_in_amount,
+            min_out_amount,
+        } = slippage.apply_to_quote(&quote, quote.swap_mode);
+
+        SimulationExpectation {
+            max_in_amount,
+            min_out_amount,
+        }
+    }
+
+    /// Checks the balance changes observed in a simulation against the thresholds
+    pub fn verify(&self, pre_balances: &SwapBalances, post_balances: &SwapBalances) -> Result<()> {
+        let in_amount = pre_balances
+            .source
+            .checked_sub(post_balances.source)
+            .context("Source balance increased during the swap")?;
+        let out_amount = post_balances
+            .destination
+            .checked_sub(pre_balances.destination)
+            .context("Destination balance decreased during the swap")?;
+
+        ensure!(
+            in_amount <= self.max_in_amount,
+            "Simulated in amount {in_amount} exceeds max in amount {}",
+            self.max_in_amount
+        );
+        ensure!(
+            out_amount >= self.min_out_amount,
+            "Simulated out amount {out_amount} is below min out amount {}",
+            self.min_out_amount
+        );
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::SwapMode;
+
+    #[test]
+    fn test_verify_simulated_balances() {
+        let quote = Quote {
+            in_amount: 1_000_000,
+            out_amount: 2_000_000,
+            ..Quote::default()
+        };
+        let expectation = SimulationExpectation::from_quote(&quote, Slippage::Bps(50));
+        assert_eq!(expectation.max_in_amount, 1_000_000);
+        assert_eq!(expectation.min_out_amount, 1_990_000);
+
+        let exact_out_expectation = SimulationExpectation::from_quote(
+            &Quote {
+                max_in_amount: Some(1_000_001),
+                swap_mode: SwapMode::ExactOut,
+                ..quote
+            },
+            Slippage::Bps(50),
+        );
+        assert_eq!(exact_out_expectation.max_in_amount, 1_005_002);
+        assert_eq!(exact_out_expectation.min_out_amount, 2_000_000);
+
+        let pre_balances = SwapBalances {
+            source: 5_000_000,
+            destination: 0,
+        };
+        let within_slippage = SwapBalances {
+            source: 4_000_000,
+            destination: 1_995_000,
+        };
+        assert!(expectation.verify(&pre_balances, &within_slippage).is_ok());
+
+        let beyond_slippage = SwapBalances {
+            source: 4_000_000,
+            destination: 1_900_000,
+        };
+        assert!(expectation.verify(&pre_balances, &beyond_slippage).is_err());
+    }
+}
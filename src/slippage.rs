@@ -0,0 +1,88 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    math::{mul_div_ceil, mul_div_floor},
+    Quote, SwapMode,
+};
+
+const BPS_DENOMINATOR: u64 = 10_000;
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum Slippage {
+    Bps(u16),
+    /// The slippage is decided at execution, thresholds are derived from the maximum
+    #[serde(rename_all = "camelCase")]
+    Dynamic {
+        max_bps: u16,
+    },
+}
+
+/// The amounts a swap has to respect, the side fixed by the swap mode is left as quoted
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct ThresholdAmounts {
+    pub max_in_amount: u64,
+    pub min_out_amount: u64,
+}
+
+impl Slippage {
+    pub fn max_bps(&self) -> u16 {
+        match self {
+            Slippage::Bps(bps) => *bps,
+            Slippage::Dynamic { max_bps } => *max_bps,
+        }
+    }
+
+    /// Rounds against the user, down for the min out amount and up for the max in amount
+    pub fn apply_to_quote(&self, quote: &Quote, swap_mode: SwapMode) -> ThresholdAmounts {
+        let bps = u64::from(self.max_bps()).min(BPS_DENOMINATOR);
+        match swap_mode {
+            SwapMode::ExactIn => ThresholdAmounts {
+                max_in_amount: quote.in_amount,
+                min_out_amount: mul_div_floor(
+                    quote.out_amount,
+                    BPS_DENOMINATOR - bps,
+                    BPS_DENOMINATOR,
+                )
+                .unwrap_or_default(),
+            },
+            SwapMode::ExactOut => ThresholdAmounts {
+                max_in_amount: mul_div_ceil(
+                    quote.in_amount,
+                    BPS_DENOMINATOR + bps,
+                    BPS_DENOMINATOR,
+                )
+                .unwrap_or(u64::MAX),
+                min_out_amount: quote.out_amount,
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_apply_to_quote_rounding() {
+        let quote = Quote {
+            in_amount: 1_001,
+            out_amount: 1_001,
+            ..Quote::default()
+        };
+        assert_eq!(
+            Slippage::Bps(100).apply_to_quote(&quote, SwapMode::ExactIn),
+            ThresholdAmounts {
+                max_in_amount: 1_001,
+                min_out_amount: 990,
+            }
+        );
+        assert_eq!(
+            Slippage::Dynamic { max_bps: 100 }.apply_to_quote(&quote, SwapMode::ExactOut),
+            ThresholdAmounts {
+                max_in_amount: 1_012,
+                min_out_amount: 1_001,
+            }
+        );
+    }
+}
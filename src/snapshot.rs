@@ -0,0 +1,128 @@
+use std::convert::TryFrom;
+
+use anyhow::{ensure, Result};
+use serde::{Deserialize, Serialize};
+use solana_account_decoder::UiAccountEncoding;
+use solana_sdk::{account::Account, pubkey::Pubkey};
+
+use crate::{AccountMap, Amm, AmmContext, KeyedAccount, KeyedUiAccount};
+
+/// The pool account along with the accounts its Amm needs, captured to reproduce quotes in regression tests
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub struct AmmSnapshot {
+    pub pool: KeyedUiAccount,
+    pub accounts: Vec<KeyedUiAccount>,
+}
+
+impl AmmSnapshot {
+    /// Captures the accounts to update of the Amm built from `keyed_account`, `fetch_accounts` returns the accounts in the requested order
+    pub fn capture<T: Amm>(
+        keyed_account: KeyedAccount,
+        amm_context: &AmmContext,
+        mut fetch_accounts: impl FnMut(&[Pubkey]) -> Result<Vec<Option<Account>>>,
+    ) -> Result<Self> {
+        let amm = T::from_keyed_account(&keyed_account, amm_context)?;
+        let addresses = amm.get_accounts_to_update();
+        let accounts = fetch_accounts(&addresses)?;
+        ensure!(
+            accounts.len() == addresses.len(),
+            "Fetched {} accounts for {} addresses",
+            accounts.len(),
+            addresses.len()
+        );
+
+        Ok(AmmSnapshot {
+            pool: KeyedUiAccount::from_keyed_account_with_encoding(
+                keyed_account,
+                UiAccountEncoding::Base64Zstd,
+            ),
+            accounts: addresses
+                .into_iter()
+                .zip(accounts)
+                .filter_map(|(key, account)| {
+                    account.map(|account| {
+                        KeyedUiAccount::from_keyed_account_with_encoding(
+                            KeyedAccount {
+                                key,
+                                account,
+                                params: None,
+                            },
+                            UiAccountEncoding::Base64Zstd,
+                        )
+                    })
+                })
+                .collect(),
+        })
+    }
+
+    pub fn keyed_account(&self) -> Result<KeyedAccount> {
+        KeyedAccount::try_from(self.pool.clone())
+    }
+
+    /// The account map to pass to `Amm::update`
+    pub fn account_map(&self) -> Result<AccountMap> {
+        self.accounts
+            .iter()
+            .map(|keyed_ui_account| {
+                let KeyedAccount { key, account, .. } =
+                    KeyedAccount::try_from(keyed_ui_account.clone())?;
+                Ok((key, account))
+            })
+            .collect()
+    }
+
+    /// Builds the Amm from the snapshot and updates it, ready to quote
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(amm.key = %self.pool.pubkey)))]
+    pub fn load_amm<T: Amm>(&self, amm_context: &AmmContext) -> Result<T> {
+        let mut amm = T::from_keyed_account(&self.keyed_account()?, amm_context)?;
+        amm.update(&self.account_map()?)?;
+        Ok(amm)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{reference::MockAmm, QuoteParams, SwapMode};
+    use serde_json::json;
+
+    #[test]
+    fn test_capture_and_load_amm() {
+        let usdc = Pubkey::new_unique();
+        let sol = Pubkey::new_unique();
+        let keyed_account = KeyedAccount {
+            key: Pubkey::new_unique(),
+            account: Account::default(),
+            params: Some(json!({
+                "reserveMints": [usdc.to_string(), sol.to_string()],
+                "prices": [{
+                    "inputMint": sol.to_string(),
+                    "outputMint": usdc.to_string(),
+                    "price": "150.5",
+                }],
+            })),
+        };
+        let amm_context = AmmContext::default();
+
+        let snapshot = AmmSnapshot::capture::<MockAmm>(keyed_account, &amm_context, |addresses| {
+            Ok(addresses.iter().map(|_| Some(Account::default())).collect())
+        })
+        .unwrap();
+        let snapshot: AmmSnapshot =
+            serde_json::from_str(&serde_json::to_string(&snapshot).unwrap()).unwrap();
+
+        let amm = snapshot.load_amm::<MockAmm>(&amm_context).unwrap();
+        assert_eq!(amm.get_reserve_mints(), vec![usdc, sol]);
+        let quote = amm
+            .quote(&QuoteParams {
+                amount: 2_000,
+                input_mint: sol,
+                output_mint: usdc,
+                swap_mode: SwapMode::ExactIn,
+                budget: None,
+                limit_price: None,
+            })
+            .unwrap();
+        assert_eq!(quote.out_amount, 301_000);
+    }
+}
@@ -1,13 +1,87 @@
+use std::{fmt, str::FromStr};
+
+use anyhow::{anyhow, ensure, Context, Result};
+#[cfg(feature = "onchain")]
+use borsh::BorshDeserialize;
 use borsh::BorshSerialize;
+use serde::{Deserialize, Serialize};
+use solana_sdk::{instruction::AccountMeta, pubkey::Pubkey};
 
-#[derive(BorshSerialize, Copy, Clone, Debug, PartialEq)]
+/// The taker side of an order book swap, `Bid` buys base with quote and `Ask` sells base for quote
+#[derive(BorshSerialize, Serialize, Deserialize, Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "onchain", derive(BorshDeserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub enum Side {
     Bid,
     Ask,
 }
 
-#[derive(BorshSerialize, Clone, Debug, PartialEq)]
-pub enum Swap {
+impl Side {
+    /// The side swapping from `input_mint`
+    pub fn from_mints(
+        base_mint: &Pubkey,
+        quote_mint: &Pubkey,
+        input_mint: &Pubkey,
+    ) -> Result<Side> {
+        if input_mint == quote_mint {
+            Ok(Side::Bid)
+        } else if input_mint == base_mint {
+            Ok(Side::Ask)
+        } else {
+            Err(anyhow!(
+                "{input_mint} is neither the base mint {base_mint} nor the quote mint {quote_mint}"
+            ))
+        }
+    }
+
+    pub fn opposite(&self) -> Side {
+        match self {
+            Side::Bid => Side::Ask,
+            Side::Ask => Side::Bid,
+        }
+    }
+}
+
+macro_rules! swaps {
+    ($($variant:ident $({ $($field:ident: $field_type:ty),* $(,)? })?,)*) => {
+        /// The swap to perform onchain, each variant maps to the variant of the onchain aggregator Swap enum with the same index
+        ///
+        /// Variants must only ever be appended, the mapping is covered by the `route_ix_data.json` test vectors
+        #[derive(BorshSerialize, Serialize, Deserialize, Clone, Debug, PartialEq)]
+        #[cfg_attr(feature = "onchain", derive(BorshDeserialize))]
+        #[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+        #[serde(rename_all_fields = "camelCase")]
+        pub enum Swap {
+            $($variant $({ $($field: $field_type),* })?,)*
+        }
+
+        impl Swap {
+            pub fn kind(&self) -> SwapKind {
+                match self {
+                    $(Swap::$variant { .. } => SwapKind::$variant,)*
+                }
+            }
+        }
+
+        /// The variants of [`Swap`] without their fields, in the same order
+        #[derive(Serialize, Deserialize, Copy, Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+        pub enum SwapKind {
+            $($variant,)*
+        }
+
+        impl SwapKind {
+            pub const ALL: &'static [SwapKind] = &[$(SwapKind::$variant,)*];
+
+            pub fn name(&self) -> &'static str {
+                match self {
+                    $(SwapKind::$variant => stringify!($variant),)*
+                }
+            }
+        }
+    };
+}
+
+swaps! {
     Saber,
     SaberAddDecimalsDeposit,
     SaberAddDecimalsWithdraw,
@@ -127,26 +201,343 @@ pub enum Swap {
     },
 }
 
-#[derive(BorshSerialize, Copy, Clone, PartialEq, Eq, Debug)]
+impl Swap {
+    /// The name of the variant, stable across releases, for grouping swaps in metrics and logs
+    pub fn discriminant_name(&self) -> &'static str {
+        self.kind().name()
+    }
+}
+
+impl fmt::Display for SwapKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for SwapKind {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        SwapKind::ALL
+            .iter()
+            .find(|kind| kind.name() == s)
+            .copied()
+            .ok_or_else(|| anyhow!("{s} is not a valid SwapKind"))
+    }
+}
+
+/// Variants are only ever appended, the borsh discriminant is part of the onchain instruction data
+#[derive(BorshSerialize, Serialize, Deserialize, Copy, Clone, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "onchain", derive(BorshDeserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
 pub enum AccountsType {
     TransferHookA,
     TransferHookB,
-    // TransferHookReward,
-    // TransferHookInput,
-    // TransferHookIntermediate,
-    // TransferHookOutput,
-    //TickArray,
-    //TickArrayOne,
-    //TickArrayTwo,
+    TransferHookReward,
+    TransferHookInput,
+    TransferHookIntermediate,
+    TransferHookOutput,
+    TickArray,
+    TickArrayOne,
+    TickArrayTwo,
 }
 
-#[derive(BorshSerialize, Clone, Debug, PartialEq)]
+#[derive(BorshSerialize, Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "onchain", derive(BorshDeserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
 pub struct RemainingAccountsSlice {
     pub accounts_type: AccountsType,
     pub length: u8,
 }
 
-#[derive(BorshSerialize, Clone, Debug, PartialEq)]
+#[derive(BorshSerialize, Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "onchain", derive(BorshDeserialize))]
+#[cfg_attr(feature = "schemars", derive(schemars::JsonSchema))]
+#[serde(rename_all = "camelCase")]
 pub struct RemainingAccountsInfo {
     pub slices: Vec<RemainingAccountsSlice>,
 }
+
+impl RemainingAccountsInfo {
+    /// The number of remaining accounts the slices describe
+    pub fn accounts_len(&self) -> usize {
+        self.slices
+            .iter()
+            .map(|slice| usize::from(slice.length))
+            .sum()
+    }
+
+    /// Checks the slices describe exactly `metas_tail`, the account metas following the fixed accounts of the swap
+    pub fn validate(&self, metas_tail: &[AccountMeta]) -> Result<()> {
+        ensure!(
+            self.accounts_len() == metas_tail.len(),
+            "Remaining accounts slices describe {} accounts but {} account metas follow",
+            self.accounts_len(),
+            metas_tail.len()
+        );
+        for (i, slice) in self.slices.iter().enumerate() {
+            ensure!(
+                !self.slices[..i]
+                    .iter()
+                    .any(|other| other.accounts_type == slice.accounts_type),
+                "Remaining accounts slice {:?} is repeated",
+                slice.accounts_type
+            );
+        }
+        Ok(())
+    }
+}
+
+/// Builds a [`RemainingAccountsInfo`] along the account metas it describes, so slice lengths cannot drift from the metas
+#[derive(Debug, Default)]
+pub struct RemainingAccountsInfoBuilder {
+    slices: Vec<RemainingAccountsSlice>,
+    /// Every type appended, empty slices included, so a type cannot be appended twice
+    accounts_types: Vec<AccountsType>,
+    account_metas: Vec<AccountMeta>,
+}
+
+impl RemainingAccountsInfoBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Appends a slice of `accounts_type`, skipped when `account_metas` is empty
+    pub fn slice(
+        mut self,
+        accounts_type: AccountsType,
+        account_metas: impl IntoIterator<Item = AccountMeta>,
+    ) -> Result<Self> {
+        ensure!(
+            !self.accounts_types.contains(&accounts_type),
+            "Remaining accounts slice {accounts_type:?} is repeated"
+        );
+        self.accounts_types.push(accounts_type);
+        let len_before = self.account_metas.len();
+        self.account_metas.extend(account_metas);
+        let length = self.account_metas.len() - len_before;
+        if length > 0 {
+            self.slices.push(RemainingAccountsSlice {
+                accounts_type,
+                length: u8::try_from(length).with_context(|| {
+                    format!("Remaining accounts slice {accounts_type:?} has {length} accounts")
+                })?,
+            });
+        }
+        Ok(self)
+    }
+
+    /// The info, None when there are no slices, and the account metas to append after the fixed accounts
+    pub fn build(self) -> (Option<RemainingAccountsInfo>, Vec<AccountMeta>) {
+        let remaining_accounts_info = (!self.slices.is_empty()).then_some(RemainingAccountsInfo {
+            slices: self.slices,
+        });
+        (remaining_accounts_info, self.account_metas)
+    }
+}
+
+/// A step of the route plan of the onchain aggregator instruction, swapping `percent` of the amount held at
+/// `input_index` into the amount held at `output_index`
+#[derive(BorshSerialize, Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "onchain", derive(BorshDeserialize))]
+pub struct RoutePlanStep {
+    pub swap: Swap,
+    pub percent: u8,
+    pub input_index: u8,
+    pub output_index: u8,
+}
+
+/// Encodes a route plan as it follows the discriminator in the onchain aggregator route instruction data
+#[cfg(feature = "onchain")]
+pub fn encode_route_ix_data(route_plan: &[RoutePlanStep]) -> Vec<u8> {
+    BorshSerialize::try_to_vec(route_plan).expect("Serializing to a Vec cannot fail")
+}
+
+/// Decodes a route plan encoded by [`encode_route_ix_data`]
+#[cfg(feature = "onchain")]
+pub fn decode_route_ix_data(data: &[u8]) -> anyhow::Result<Vec<RoutePlanStep>> {
+    Vec::<RoutePlanStep>::try_from_slice(data)
+        .map_err(|e| anyhow::anyhow!("Failed to decode route ix data: {e}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_side_from_mints() {
+        let (base_mint, quote_mint) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let side = Side::from_mints(&base_mint, &quote_mint, &quote_mint).unwrap();
+        assert_eq!(side, Side::Bid);
+        assert_eq!(side.opposite(), Side::Ask);
+        assert_eq!(
+            Side::from_mints(&base_mint, &quote_mint, &base_mint).unwrap(),
+            Side::Ask
+        );
+        assert!(Side::from_mints(&base_mint, &quote_mint, &Pubkey::new_unique()).is_err());
+        assert_eq!(serde_json::to_string(&Side::Bid).unwrap(), r#""Bid""#);
+    }
+
+    #[test]
+    fn test_swap_kind() {
+        let swap = Swap::Whirlpool { a_to_b: true };
+        assert_eq!(swap.kind(), SwapKind::Whirlpool);
+        assert_eq!(swap.discriminant_name(), "Whirlpool");
+
+        for (discriminant, kind) in SwapKind::ALL.iter().enumerate() {
+            assert_eq!(kind.to_string().parse::<SwapKind>().unwrap(), *kind);
+            assert_eq!(*kind as usize, discriminant);
+        }
+        // The last variant has the discriminant of the last Swap variant
+        let data = borsh::BorshSerialize::try_to_vec(&Swap::SolFi {
+            is_quote_to_base: true,
+        })
+        .unwrap();
+        assert_eq!(usize::from(data[0]), SwapKind::ALL.len() - 1);
+        assert!("Unknown".parse::<SwapKind>().is_err());
+    }
+
+    #[test]
+    fn test_remaining_accounts_info_builder() {
+        let hook_metas = |n| (0..n).map(|_| AccountMeta::new_readonly(Pubkey::new_unique(), false));
+        let (remaining_accounts_info, account_metas) = RemainingAccountsInfoBuilder::new()
+            .slice(AccountsType::TransferHookA, hook_metas(0))
+            .unwrap()
+            .slice(AccountsType::TransferHookB, hook_metas(2))
+            .unwrap()
+            .slice(AccountsType::TickArray, hook_metas(3))
+            .unwrap()
+            .build();
+        let remaining_accounts_info = remaining_accounts_info.unwrap();
+        assert_eq!(remaining_accounts_info.slices.len(), 2);
+        assert_eq!(remaining_accounts_info.accounts_len(), 5);
+        remaining_accounts_info.validate(&account_metas).unwrap();
+        assert!(remaining_accounts_info
+            .validate(&account_metas[1..])
+            .is_err());
+
+        assert!(RemainingAccountsInfoBuilder::new()
+            .slice(AccountsType::TransferHookInput, hook_metas(256))
+            .is_err());
+        // An empty slice still claims its type
+        assert!(RemainingAccountsInfoBuilder::new()
+            .slice(AccountsType::TransferHookA, hook_metas(0))
+            .unwrap()
+            .slice(AccountsType::TransferHookA, hook_metas(1))
+            .is_err());
+        assert_eq!(RemainingAccountsInfoBuilder::new().build(), (None, vec![]));
+    }
+
+    #[cfg(feature = "onchain")]
+    #[derive(Deserialize)]
+    struct TestVector {
+        name: String,
+        data: String,
+    }
+
+    /// The discriminator of the aggregator route instruction, the first 8 bytes of sha256("global:route")
+    #[cfg(feature = "onchain")]
+    const ROUTE_DISCRIMINATOR: [u8; 8] = [0xe5, 0x17, 0xcb, 0x97, 0x7a, 0xe3, 0xad, 0x2a];
+    /// The in amount, quoted out amount, slippage bps and platform fee bps following the route plan
+    #[cfg(feature = "onchain")]
+    const ROUTE_AMOUNTS_BYTES: usize = 8 + 8 + 2 + 1;
+
+    #[cfg(feature = "onchain")]
+    fn test_vector_route_plans() -> Vec<(&'static str, Vec<RoutePlanStep>)> {
+        let step = |swap, percent, input_index, output_index| RoutePlanStep {
+            swap,
+            percent,
+            input_index,
+            output_index,
+        };
+        vec![
+            ("saber", vec![step(Swap::Saber, 100, 0, 1)]),
+            (
+                "whirlpool_a_to_b",
+                vec![step(Swap::Whirlpool { a_to_b: true }, 100, 0, 1)],
+            ),
+            (
+                "openbook_v2_ask_then_raydium_cp",
+                vec![
+                    step(Swap::OpenBookV2 { side: Side::Ask }, 100, 0, 1),
+                    step(Swap::RaydiumCP, 100, 1, 2),
+                ],
+            ),
+            (
+                "saber_and_raydium_cp_split",
+                vec![step(Swap::Saber, 60, 0, 1), step(Swap::RaydiumCP, 40, 0, 1)],
+            ),
+            (
+                "stakedex_swap_via_stake",
+                vec![step(
+                    Swap::StakeDexSwapViaStake {
+                        bridge_stake_seed: 0x01020304,
+                    },
+                    100,
+                    0,
+                    1,
+                )],
+            ),
+            (
+                "whirlpool_swap_v2_transfer_hooks",
+                vec![step(
+                    Swap::WhirlpoolSwapV2 {
+                        a_to_b: false,
+                        remaining_accounts_info: Some(RemainingAccountsInfo {
+                            slices: vec![RemainingAccountsSlice {
+                                accounts_type: AccountsType::TransferHookB,
+                                length: 2,
+                            }],
+                        }),
+                    },
+                    100,
+                    0,
+                    1,
+                )],
+            ),
+            (
+                "solfi",
+                vec![step(
+                    Swap::SolFi {
+                        is_quote_to_base: true,
+                    },
+                    100,
+                    0,
+                    1,
+                )],
+            ),
+        ]
+    }
+
+    #[cfg(feature = "onchain")]
+    fn to_hex(data: &[u8]) -> String {
+        data.iter().map(|byte| format!("{byte:02x}")).collect()
+    }
+
+    #[cfg(feature = "onchain")]
+    #[test]
+    fn test_route_ix_data_vectors() {
+        let test_vectors: Vec<TestVector> =
+            serde_json::from_str(include_str!("../tests/fixtures/route_ix_data.json")).unwrap();
+        let route_plans = test_vector_route_plans();
+        assert_eq!(test_vectors.len(), route_plans.len());
+
+        for (test_vector, (name, route_plan)) in test_vectors.iter().zip(route_plans) {
+            assert_eq!(test_vector.name, name);
+            let (discriminator, data) = test_vector.data.split_at(2 * ROUTE_DISCRIMINATOR.len());
+            assert_eq!(discriminator, to_hex(&ROUTE_DISCRIMINATOR), "{name}");
+            let route_plan_data = encode_route_ix_data(&route_plan);
+            assert_eq!(
+                to_hex(&route_plan_data),
+                data[..data.len() - 2 * ROUTE_AMOUNTS_BYTES],
+                "{name}"
+            );
+            assert_eq!(
+                decode_route_ix_data(&route_plan_data).unwrap(),
+                route_plan,
+                "{name}"
+            );
+        }
+    }
+}
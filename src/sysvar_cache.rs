@@ -0,0 +1,158 @@
+use std::{
+    collections::VecDeque,
+    sync::{Arc, PoisonError, RwLock},
+};
+
+use anyhow::{anyhow, Context, Result};
+use solana_sdk::{
+    account::{from_account, Account},
+    clock::Slot,
+    epoch_schedule::EpochSchedule,
+    hash::Hash,
+    pubkey::Pubkey,
+    rent::Rent,
+    slot_hashes::SlotHashes,
+    sysvar,
+};
+
+/// The most recent blockhashes kept, matching the RecentBlockhashes sysvar
+pub const MAX_RECENT_BLOCKHASHES: usize = 150;
+
+/// Sysvars beyond the clock, refreshed by the host and shared by every Amm like [`crate::ClockRef`]
+#[derive(Debug, Default, Clone)]
+pub struct SysvarCache {
+    rent: Arc<RwLock<Rent>>,
+    epoch_schedule: Arc<RwLock<EpochSchedule>>,
+    /// Newest first, bounded to `slot_hashes::MAX_ENTRIES`
+    slot_hashes: Arc<RwLock<SlotHashes>>,
+    /// Newest first, bounded to [`MAX_RECENT_BLOCKHASHES`]
+    recent_blockhashes: Arc<RwLock<VecDeque<Hash>>>,
+}
+
+impl SysvarCache {
+    pub fn rent(&self) -> Rent {
+        self.rent
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    pub fn set_rent(&self, rent: Rent) {
+        *self.rent.write().unwrap_or_else(PoisonError::into_inner) = rent;
+    }
+
+    pub fn epoch_schedule(&self) -> EpochSchedule {
+        self.epoch_schedule
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    pub fn set_epoch_schedule(&self, epoch_schedule: EpochSchedule) {
+        *self
+            .epoch_schedule
+            .write()
+            .unwrap_or_else(PoisonError::into_inner) = epoch_schedule;
+    }
+
+    pub fn slot_hash(&self, slot: Slot) -> Option<Hash> {
+        self.slot_hashes
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(&slot)
+            .copied()
+    }
+
+    /// Records the bank hash of a new slot, evicting the oldest one when full
+    pub fn push_slot_hash(&self, slot: Slot, hash: Hash) {
+        self.slot_hashes
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .add(slot, hash);
+    }
+
+    pub fn recent_blockhashes(&self) -> Vec<Hash> {
+        self.recent_blockhashes
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .iter()
+            .copied()
+            .collect()
+    }
+
+    /// Records a new blockhash, evicting the oldest one when full
+    pub fn push_recent_blockhash(&self, blockhash: Hash) {
+        let mut recent_blockhashes = self
+            .recent_blockhashes
+            .write()
+            .unwrap_or_else(PoisonError::into_inner);
+        recent_blockhashes.push_front(blockhash);
+        recent_blockhashes.truncate(MAX_RECENT_BLOCKHASHES);
+    }
+
+    /// Refreshes the cache from a fetched sysvar account
+    pub fn update_from_account(&self, address: &Pubkey, account: &Account) -> Result<()> {
+        if *address == sysvar::rent::id() {
+            self.set_rent(from_account(account).context("Invalid rent sysvar")?);
+        } else if *address == sysvar::epoch_schedule::id() {
+            self.set_epoch_schedule(
+                from_account(account).context("Invalid epoch schedule sysvar")?,
+            );
+        } else if *address == sysvar::slot_hashes::id() {
+            *self
+                .slot_hashes
+                .write()
+                .unwrap_or_else(PoisonError::into_inner) =
+                from_account(account).context("Invalid slot hashes sysvar")?;
+        } else {
+            return Err(anyhow!("Sysvar {address} is not cached"));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::account::create_account_for_test;
+
+    #[test]
+    fn test_sysvar_cache() {
+        let sysvar_cache = SysvarCache::default();
+        let rent = Rent {
+            lamports_per_byte_year: 1,
+            ..Rent::default()
+        };
+        sysvar_cache
+            .update_from_account(&sysvar::rent::id(), &create_account_for_test(&rent))
+            .unwrap();
+        assert_eq!(sysvar_cache.rent(), rent);
+
+        for slot in 0..(MAX_RECENT_BLOCKHASHES as u64 + 1) {
+            sysvar_cache.push_slot_hash(slot, Hash::new_unique());
+            sysvar_cache.push_recent_blockhash(Hash::new_unique());
+        }
+        assert!(sysvar_cache.slot_hash(0).is_some());
+        let recent_blockhashes = sysvar_cache.recent_blockhashes();
+        assert_eq!(recent_blockhashes.len(), MAX_RECENT_BLOCKHASHES);
+    }
+
+    #[test]
+    fn test_sysvar_cache_recovers_poisoned_locks() {
+        let sysvar_cache = SysvarCache::default();
+        let poisoning_sysvar_cache = sysvar_cache.clone();
+        assert!(std::thread::spawn(move || {
+            let _rent = poisoning_sysvar_cache.rent.write().unwrap();
+            panic!("Poisons the rent lock");
+        })
+        .join()
+        .is_err());
+
+        assert_eq!(sysvar_cache.rent(), Rent::default());
+        sysvar_cache.set_rent(Rent {
+            lamports_per_byte_year: 1,
+            ..Rent::default()
+        });
+        assert_eq!(sysvar_cache.rent().lamports_per_byte_year, 1);
+    }
+}
@@ -0,0 +1,111 @@
+//! Time-boxed quoting, adapters cooperate by checking the [`QuoteBudget`] of the quote params in their loops
+use std::time::Instant;
+
+use anyhow::Result;
+
+use crate::{Amm, Quote, QuoteError, QuoteParams};
+
+/// Caps on the work a single quote may do, so pathological inputs cannot stall the routing hot path
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct QuoteBudget {
+    /// Iterations of searches or solvers, e.g. exact out bisection
+    pub max_iterations: Option<u32>,
+    /// Initialized ticks or bins crossed by concentrated liquidity swaps
+    pub max_tick_crossings: Option<u32>,
+    pub deadline: Option<Instant>,
+}
+
+impl QuoteBudget {
+    pub fn check_iterations(&self, iterations: u32) -> Result<(), QuoteError> {
+        match self.max_iterations {
+            Some(max_iterations) if iterations > max_iterations => Err(QuoteError::BudgetExhausted),
+            _ => self.check_deadline(),
+        }
+    }
+
+    pub fn check_tick_crossings(&self, tick_crossings: u32) -> Result<(), QuoteError> {
+        match self.max_tick_crossings {
+            Some(max_tick_crossings) if tick_crossings > max_tick_crossings => {
+                Err(QuoteError::BudgetExhausted)
+            }
+            _ => self.check_deadline(),
+        }
+    }
+
+    pub fn check_deadline(&self) -> Result<(), QuoteError> {
+        match self.deadline {
+            Some(deadline) if Instant::now() > deadline => Err(QuoteError::DeadlineExceeded),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Quotes with `deadline` added to the budget of `quote_params`, quotes completing past the deadline are dropped
+pub fn quote_with_deadline<A: Amm + ?Sized>(
+    amm: &A,
+    quote_params: &QuoteParams,
+    deadline: Instant,
+) -> Result<Quote> {
+    let budget = QuoteBudget {
+        deadline: Some(deadline),
+        ..quote_params.budget.unwrap_or_default()
+    };
+    budget.check_deadline()?;
+    let quote = amm.quote(&QuoteParams {
+        budget: Some(budget),
+        ..quote_params.clone()
+    })?;
+    budget.check_deadline()?;
+    Ok(quote)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        reference::{MockAmm, MockAmmCall},
+        SwapMode,
+    };
+    use rust_decimal::Decimal;
+    use solana_sdk::pubkey::Pubkey;
+    use std::time::Duration;
+
+    #[test]
+    fn test_quote_with_deadline() {
+        let (sol, usdc) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let amm = MockAmm::new(Pubkey::new_unique(), vec![sol, usdc]).with_price(
+            sol,
+            usdc,
+            Decimal::from(150),
+        );
+        let quote_params = QuoteParams {
+            amount: 1_000,
+            input_mint: sol,
+            output_mint: usdc,
+            swap_mode: SwapMode::ExactIn,
+            budget: None,
+            limit_price: None,
+        };
+
+        let deadline = Instant::now() + Duration::from_secs(60);
+        let quote = quote_with_deadline(&amm, &quote_params, deadline).unwrap();
+        assert_eq!(quote.out_amount, 150_000);
+        let MockAmmCall::Quote(recorded_quote_params) = amm.calls().pop().unwrap() else {
+            panic!("Expected a quote call");
+        };
+        assert_eq!(
+            recorded_quote_params.budget.unwrap().deadline,
+            Some(deadline)
+        );
+
+        let error =
+            quote_with_deadline(&amm, &quote_params, Instant::now() - Duration::from_secs(1))
+                .unwrap_err();
+        assert_eq!(
+            error.downcast_ref::<QuoteError>(),
+            Some(&QuoteError::DeadlineExceeded)
+        );
+        assert!(!QuoteError::DeadlineExceeded.is_retryable_with_smaller_amount());
+        assert!(QuoteError::BudgetExhausted.is_retryable_with_smaller_amount());
+    }
+}
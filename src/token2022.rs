@@ -0,0 +1,138 @@
+use anyhow::{Context, Result};
+
+use crate::Quote;
+
+const MAX_FEE_BASIS_POINTS: u16 = 10_000;
+
+/// Mirrors the Token-2022 `TransferFee` extension entry
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct TransferFee {
+    /// First epoch where the transfer fee takes effect
+    pub epoch: u64,
+    pub maximum_fee: u64,
+    pub transfer_fee_basis_points: u16,
+}
+
+impl TransferFee {
+    /// The fee withheld when transferring `pre_fee_amount`
+    pub fn calculate_fee(&self, pre_fee_amount: u64) -> Option<u64> {
+        if self.transfer_fee_basis_points == 0 || pre_fee_amount == 0 {
+            return Some(0);
+        }
+        let fee = (u128::from(pre_fee_amount) * u128::from(self.transfer_fee_basis_points))
+            .div_ceil(u128::from(MAX_FEE_BASIS_POINTS));
+        Some(u64::try_from(fee).ok()?.min(self.maximum_fee))
+    }
+
+    /// The fee to add to `post_fee_amount` so that `post_fee_amount` is received
+    pub fn calculate_inverse_fee(&self, post_fee_amount: u64) -> Option<u64> {
+        if self.transfer_fee_basis_points == 0 || post_fee_amount == 0 {
+            return Some(0);
+        }
+        if self.transfer_fee_basis_points >= MAX_FEE_BASIS_POINTS {
+            return Some(self.maximum_fee);
+        }
+        let numerator = u128::from(post_fee_amount) * u128::from(MAX_FEE_BASIS_POINTS);
+        let denominator = u128::from(MAX_FEE_BASIS_POINTS - self.transfer_fee_basis_points);
+        let pre_fee_amount = numerator.div_ceil(denominator);
+        let fee = u64::try_from(pre_fee_amount - u128::from(post_fee_amount)).ok()?;
+        Some(fee.min(self.maximum_fee))
+    }
+}
+
+/// Mirrors the Token-2022 `TransferFeeConfig` mint extension
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct TransferFeeConfig {
+    pub older_transfer_fee: TransferFee,
+    pub newer_transfer_fee: TransferFee,
+}
+
+impl TransferFeeConfig {
+    pub fn get_epoch_fee(&self, epoch: u64) -> &TransferFee {
+        if epoch >= self.newer_transfer_fee.epoch {
+            &self.newer_transfer_fee
+        } else {
+            &self.older_transfer_fee
+        }
+    }
+}
+
+/// Adjusts a quote computed on the amounts seen by the pool to the amounts seen by the user
+///
+/// The in amount is grossed up so the pool still receives the quoted in amount, the out amount is netted of the fee withheld on the way out
+pub fn adjust_quote_for_transfer_fees(
+    quote: &Quote,
+    in_mint_fee_config: Option<&TransferFeeConfig>,
+    out_mint_fee_config: Option<&TransferFeeConfig>,
+    epoch: u64,
+) -> Result<Quote> {
+    let mut quote = quote.clone();
+    if let Some(in_mint_fee_config) = in_mint_fee_config {
+        let in_fee = in_mint_fee_config
+            .get_epoch_fee(epoch)
+            .calculate_inverse_fee(quote.in_amount)
+            .context("In amount transfer fee overflow")?;
+        quote.in_amount = quote
+            .in_amount
+            .checked_add(in_fee)
+            .context("In amount overflow")?;
+    }
+    if let Some(out_mint_fee_config) = out_mint_fee_config {
+        let out_fee = out_mint_fee_config
+            .get_epoch_fee(epoch)
+            .calculate_fee(quote.out_amount)
+            .context("Out amount transfer fee overflow")?;
+        quote.out_amount = quote
+            .out_amount
+            .checked_sub(out_fee)
+            .context("Out amount transfer fee exceeds the out amount")?;
+    }
+    Ok(quote)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_adjust_quote_for_transfer_fees() {
+        let fee_config = TransferFeeConfig {
+            older_transfer_fee: TransferFee::default(),
+            newer_transfer_fee: TransferFee {
+                epoch: 10,
+                maximum_fee: 5_000,
+                transfer_fee_basis_points: 100,
+            },
+        };
+        let quote = Quote {
+            in_amount: 99_000,
+            out_amount: 200_000,
+            ..Quote::default()
+        };
+
+        let adjusted =
+            adjust_quote_for_transfer_fees(&quote, Some(&fee_config), Some(&fee_config), 10)
+                .unwrap();
+        assert_eq!(adjusted.in_amount, 100_000);
+        assert_eq!(adjusted.out_amount, 198_000);
+
+        // The older fee applies before the newer fee epoch
+        let adjusted =
+            adjust_quote_for_transfer_fees(&quote, Some(&fee_config), Some(&fee_config), 9)
+                .unwrap();
+        assert_eq!(adjusted, quote);
+
+        // The fee is capped
+        let adjusted = adjust_quote_for_transfer_fees(
+            &Quote {
+                out_amount: 10_000_000,
+                ..quote
+            },
+            None,
+            Some(&fee_config),
+            10,
+        )
+        .unwrap();
+        assert_eq!(adjusted.out_amount, 9_995_000);
+    }
+}
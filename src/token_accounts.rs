@@ -0,0 +1,69 @@
+use std::collections::HashMap;
+
+use solana_sdk::{pubkey, pubkey::Pubkey};
+
+pub const SPL_TOKEN_PROGRAM_ID: Pubkey = pubkey!("TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA");
+pub const SPL_TOKEN_2022_PROGRAM_ID: Pubkey =
+    pubkey!("TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb");
+pub const SPL_ASSOCIATED_TOKEN_ACCOUNT_PROGRAM_ID: Pubkey =
+    pubkey!("ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL");
+pub const WSOL_MINT: Pubkey = pubkey!("So11111111111111111111111111111111111111112");
+
+/// A user token account a swap leg needs to exist, or that the leg creates by itself
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RequiredTokenAccount {
+    pub mint: Pubkey,
+    pub token_program: Pubkey,
+    /// The swap instruction creates the token account, the router does not have to
+    pub created_by_swap: bool,
+}
+
+impl RequiredTokenAccount {
+    /// The associated token account address of `owner` for this mint
+    pub fn associated_token_address(&self, owner: &Pubkey) -> Pubkey {
+        get_associated_token_address(owner, &self.mint, &self.token_program)
+    }
+}
+
+/// How a swap instruction handles native SOL, see `Amm::accepts_native_sol`
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum NativeSolPolicy {
+    /// SOL has to be wrapped into a wSOL token account before the swap and unwrapped after it
+    #[default]
+    RequiresWsol,
+    /// The swap can move lamports of the token transfer authority directly, when `is_native_source/destination` is set
+    AcceptsNativeLamports,
+    /// The swap wraps and unwraps SOL by itself, e.g. Pump and Moonshot wrapped buys and sells
+    WrapsInternally,
+}
+
+impl NativeSolPolicy {
+    /// Indicates if the router may skip the wrap and unwrap instructions around the swap
+    pub fn can_skip_wrap(&self) -> bool {
+        !matches!(self, NativeSolPolicy::RequiresWsol)
+    }
+}
+
+pub fn get_associated_token_address(
+    owner: &Pubkey,
+    mint: &Pubkey,
+    token_program: &Pubkey,
+) -> Pubkey {
+    Pubkey::find_program_address(
+        &[owner.as_ref(), token_program.as_ref(), mint.as_ref()],
+        &SPL_ASSOCIATED_TOKEN_ACCOUNT_PROGRAM_ID,
+    )
+    .0
+}
+
+/// The user token balances known to the host, e.g. from a connected wallet
+pub trait TokenBalanceSource {
+    /// The balance of the user token account of `mint`, None when unknown
+    fn token_balance(&self, mint: &Pubkey) -> Option<u64>;
+}
+
+impl<S: std::hash::BuildHasher> TokenBalanceSource for HashMap<Pubkey, u64, S> {
+    fn token_balance(&self, mint: &Pubkey) -> Option<u64> {
+        self.get(mint).copied()
+    }
+}
@@ -0,0 +1,213 @@
+//! Planning of the token ledger, which lets a leg swap the balance change of its input token account
+//!
+//! When an ExactOut route is composed of ExactIn adapters, only the first in amount is known when building,
+//! the leg following an ExactIn leg swaps whatever the previous leg produced, as recorded by the ledger
+//!
+//! A transaction records a single ledger, so at most one leg of a route can use it
+use anyhow::{anyhow, ensure, Result};
+use solana_sdk::pubkey::Pubkey;
+
+use crate::{Quote, QuoteParams, SwapMode};
+
+/// Marks a leg, next to its `Swap`, whose in amount is read from the token ledger rather than fixed at build time
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UsesTokenLedger {
+    /// The mint of the token account whose balance change the ledger records
+    pub mint: Pubkey,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PlannedLeg {
+    /// The quoted in amount, only a lower bound when the leg uses the token ledger
+    pub in_amount: u64,
+    pub token_ledger: Option<UsesTokenLedger>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TokenLedgerPlan {
+    pub legs: Vec<PlannedLeg>,
+    pub in_amount: u64,
+    pub out_amount: u64,
+}
+
+impl TokenLedgerPlan {
+    /// Indicates if the route must record the token ledger before its first ledger relative leg
+    pub fn uses_token_ledger(&self) -> bool {
+        self.legs.iter().any(|leg| leg.token_ledger.is_some())
+    }
+}
+
+/// Plans which legs of a route use the token ledger, a leg does when the amount the previous one produces is only
+/// known at execution, i.e. it is ExactIn or an ExactOut quote overshooting its requested out amount
+///
+/// Fails if more than one leg would need the ledger
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(skip_all, fields(route.legs = legs.len()))
+)]
+pub fn plan(legs: &[(QuoteParams, Quote)]) -> Result<TokenLedgerPlan> {
+    let ((_, first_quote), (_, last_quote)) = legs
+        .first()
+        .zip(legs.last())
+        .ok_or_else(|| anyhow!("Route has no legs"))?;
+
+    let mut planned_legs = vec![PlannedLeg {
+        in_amount: first_quote.in_amount,
+        token_ledger: None,
+    }];
+    for window in legs.windows(2) {
+        let (quote_params, quote) = &window[0];
+        let (next_quote_params, next_quote) = &window[1];
+        ensure!(
+            quote_params.output_mint == next_quote_params.input_mint,
+            "Leg outputs {} but the next leg expects {}",
+            quote_params.output_mint,
+            next_quote_params.input_mint
+        );
+        ensure!(
+            next_quote.in_amount <= quote.out_amount,
+            "Leg consumes {} but the previous leg only produces {}",
+            next_quote.in_amount,
+            quote.out_amount
+        );
+        let produces_exact_amount =
+            quote_params.swap_mode == SwapMode::ExactOut && quote.out_amount == quote_params.amount;
+        let token_ledger = (!produces_exact_amount).then_some(UsesTokenLedger {
+            mint: next_quote_params.input_mint,
+        });
+        ensure!(
+            token_ledger.is_none()
+                || planned_legs
+                    .iter()
+                    .all(|planned_leg| planned_leg.token_ledger.is_none()),
+            "Leg {} needs the token ledger already used by a previous leg",
+            planned_legs.len()
+        );
+        #[cfg(feature = "tracing")]
+        tracing::trace!(
+            leg = planned_legs.len(),
+            uses_token_ledger = token_ledger.is_some(),
+            "Planned leg"
+        );
+        planned_legs.push(PlannedLeg {
+            in_amount: next_quote.in_amount,
+            token_ledger,
+        });
+    }
+
+    Ok(TokenLedgerPlan {
+        legs: planned_legs,
+        in_amount: first_quote.in_amount,
+        out_amount: last_quote.out_amount,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn leg(
+        input_mint: Pubkey,
+        output_mint: Pubkey,
+        swap_mode: SwapMode,
+        in_amount: u64,
+        out_amount: u64,
+    ) -> (QuoteParams, Quote) {
+        (
+            QuoteParams {
+                amount: match swap_mode {
+                    SwapMode::ExactIn => in_amount,
+                    SwapMode::ExactOut => out_amount,
+                },
+                input_mint,
+                output_mint,
+                swap_mode,
+                budget: None,
+                limit_price: None,
+            },
+            Quote {
+                in_amount,
+                out_amount,
+                swap_mode,
+                ..Quote::default()
+            },
+        )
+    }
+
+    #[test]
+    fn test_plan() {
+        let (sol, usdc, bonk, jup) = (
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+        );
+        let token_ledger_plan = plan(&[
+            leg(sol, usdc, SwapMode::ExactIn, 1_000, 150_000),
+            leg(usdc, bonk, SwapMode::ExactOut, 149_000, 7_000_000),
+            leg(bonk, jup, SwapMode::ExactIn, 7_000_000, 300),
+        ])
+        .unwrap();
+        assert_eq!(
+            token_ledger_plan.legs,
+            vec![
+                PlannedLeg {
+                    in_amount: 1_000,
+                    token_ledger: None,
+                },
+                PlannedLeg {
+                    in_amount: 149_000,
+                    token_ledger: Some(UsesTokenLedger { mint: usdc }),
+                },
+                // The ExactOut leg produces exactly its requested out amount
+                PlannedLeg {
+                    in_amount: 7_000_000,
+                    token_ledger: None,
+                },
+            ]
+        );
+        assert!(token_ledger_plan.uses_token_ledger());
+        assert_eq!(
+            (token_ledger_plan.in_amount, token_ledger_plan.out_amount),
+            (1_000, 300)
+        );
+
+        assert!(plan(&[]).is_err());
+        // Only one leg can use the ledger
+        assert!(plan(&[
+            leg(sol, usdc, SwapMode::ExactIn, 1_000, 150_000),
+            leg(usdc, bonk, SwapMode::ExactIn, 150_000, 7_000_000),
+            leg(bonk, jup, SwapMode::ExactIn, 7_000_000, 300),
+        ])
+        .is_err());
+        // An ExactOut quote overshooting its out amount, e.g. from exact_out_via_exact_in, produces an unknown amount
+        let (quote_params, quote) = leg(usdc, bonk, SwapMode::ExactOut, 149_000, 7_000_000);
+        let overshooting_leg = (
+            QuoteParams {
+                amount: 6_999_000,
+                ..quote_params
+            },
+            quote,
+        );
+        assert_eq!(
+            plan(&[
+                overshooting_leg,
+                leg(bonk, jup, SwapMode::ExactIn, 6_999_000, 300),
+            ])
+            .unwrap()
+            .legs[1]
+                .token_ledger,
+            Some(UsesTokenLedger { mint: bonk })
+        );
+        assert!(plan(&[
+            leg(sol, usdc, SwapMode::ExactIn, 1_000, 150_000),
+            leg(bonk, jup, SwapMode::ExactIn, 150_000, 300),
+        ])
+        .is_err());
+        assert!(plan(&[
+            leg(sol, usdc, SwapMode::ExactIn, 1_000, 150_000),
+            leg(usdc, jup, SwapMode::ExactIn, 150_001, 300),
+        ])
+        .is_err());
+    }
+}
@@ -0,0 +1,62 @@
+//! Spans and events around Amm calls, following the field conventions below
+//!
+//! Spans carry `amm.label`, `amm.key` and `amm.fields` from `Amm::tracing_fields`, quote spans carry `quote.amount` too
+use std::time::Instant;
+
+use anyhow::Result;
+use tracing::Span;
+
+use crate::{AccountMap, Amm, Quote, QuoteParams};
+
+pub const AMM_LABEL_FIELD: &str = "amm.label";
+pub const AMM_KEY_FIELD: &str = "amm.key";
+pub const AMM_FIELDS_FIELD: &str = "amm.fields";
+pub const QUOTE_AMOUNT_FIELD: &str = "quote.amount";
+
+pub fn quote_span<A: Amm + ?Sized>(amm: &A, quote_params: &QuoteParams) -> Span {
+    tracing::debug_span!(
+        "quote",
+        amm.label = %amm.label(),
+        amm.key = %amm.key(),
+        amm.fields = ?amm.tracing_fields(),
+        quote.amount = quote_params.amount,
+        quote.swap_mode = ?quote_params.swap_mode,
+    )
+}
+
+pub fn update_span<A: Amm + ?Sized>(amm: &A) -> Span {
+    tracing::debug_span!(
+        "update",
+        amm.label = %amm.label(),
+        amm.key = %amm.key(),
+        amm.fields = ?amm.tracing_fields(),
+    )
+}
+
+/// Quotes within a [`quote_span`], logging the elapsed time and the error if any
+pub fn traced_quote<A: Amm + ?Sized>(amm: &A, quote_params: &QuoteParams) -> Result<Quote> {
+    let span = quote_span(amm, quote_params);
+    let _entered = span.enter();
+    let start = Instant::now();
+    let result = amm.quote(quote_params);
+    let elapsed_us = start.elapsed().as_micros() as u64;
+    match &result {
+        Ok(quote) => tracing::debug!(elapsed_us, quote.out_amount = quote.out_amount, "Quoted"),
+        Err(e) => tracing::debug!(elapsed_us, error = %e, "Quote failed"),
+    }
+    result
+}
+
+/// Updates within an [`update_span`], logging the elapsed time and the error if any
+pub fn traced_update<A: Amm + ?Sized>(amm: &mut A, account_map: &AccountMap) -> Result<()> {
+    let span = update_span(amm);
+    let _entered = span.enter();
+    let start = Instant::now();
+    let result = amm.update(account_map);
+    let elapsed_us = start.elapsed().as_micros() as u64;
+    match &result {
+        Ok(()) => tracing::debug!(elapsed_us, "Updated"),
+        Err(e) => tracing::warn!(elapsed_us, error = %e, "Update failed"),
+    }
+    result
+}
@@ -0,0 +1,414 @@
+//! Resolution of the extra accounts of Token-2022 transfer hooks, e.g. for `WhirlpoolSwapV2` and `MeteoraDlmmSwapV2`
+//!
+//! Mirrors the offchain resolution of `spl-transfer-hook-interface` without depending on it
+use anyhow::{anyhow, bail, ensure, Context, Result};
+use solana_sdk::{account::Account, hash::hashv, instruction::AccountMeta, pubkey::Pubkey};
+
+use crate::{
+    swap::{AccountsType, RemainingAccountsInfoBuilder},
+    AccountMap, SPL_TOKEN_2022_PROGRAM_ID,
+};
+
+/// The size of a Token-2022 account, mints with extensions are padded to it before their account type
+const BASE_ACCOUNT_LENGTH: usize = 165;
+const MINT_LENGTH: usize = 82;
+const ACCOUNT_TYPE_MINT: u8 = 1;
+const TRANSFER_HOOK_EXTENSION_TYPE: u16 = 14;
+const EXTRA_ACCOUNT_META_LENGTH: usize = 35;
+/// The accounts of the execute instruction preceding the extra accounts
+const EXECUTE_ACCOUNTS_LEN: usize = 5;
+
+/// The discriminator of the execute instruction, which also tags its `ExtraAccountMetaList` entry
+pub fn execute_discriminator() -> [u8; 8] {
+    hashv(&[b"spl-transfer-hook-interface:execute"]).to_bytes()[..8]
+        .try_into()
+        .unwrap()
+}
+
+/// The transfer hook program of a Token-2022 mint, None if the mint has no hook
+pub fn transfer_hook_program_id(mint_account: &Account) -> Result<Option<Pubkey>> {
+    if mint_account.owner != SPL_TOKEN_2022_PROGRAM_ID || mint_account.data.len() <= MINT_LENGTH {
+        return Ok(None);
+    }
+    let data = &mint_account.data;
+    ensure!(
+        data.get(BASE_ACCOUNT_LENGTH) == Some(&ACCOUNT_TYPE_MINT),
+        "Account is not a Token-2022 mint"
+    );
+
+    let mut offset = BASE_ACCOUNT_LENGTH + 1;
+    while let Some(header) = data.get(offset..offset + 4) {
+        let extension_type = u16::from_le_bytes([header[0], header[1]]);
+        let length = usize::from(u16::from_le_bytes([header[2], header[3]]));
+        let value = data
+            .get(offset + 4..offset + 4 + length)
+            .context("Truncated mint extension")?;
+        if extension_type == TRANSFER_HOOK_EXTENSION_TYPE {
+            // The authority followed by the program id, both zero when unset
+            let program_id = Pubkey::new_from_array(
+                value
+                    .get(32..64)
+                    .context("Invalid transfer hook extension")?
+                    .try_into()
+                    .unwrap(),
+            );
+            return Ok((program_id != Pubkey::default()).then_some(program_id));
+        }
+        if extension_type == 0 {
+            break;
+        }
+        offset += 4 + length;
+    }
+    Ok(None)
+}
+
+/// The `ExtraAccountMetaList` account of `mint` for the hook `program_id`
+pub fn extra_account_meta_list_address(mint: &Pubkey, program_id: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[b"extra-account-metas", mint.as_ref()], program_id).0
+}
+
+/// Mirrors an `ExtraAccountMeta` entry of an `ExtraAccountMetaList`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ExtraAccountMeta {
+    pub discriminator: u8,
+    pub address_config: [u8; 32],
+    pub is_signer: bool,
+    pub is_writable: bool,
+}
+
+/// Parses the extra account metas of the execute instruction from `ExtraAccountMetaList` account data
+pub fn parse_extra_account_metas(data: &[u8]) -> Result<Vec<ExtraAccountMeta>> {
+    let execute_discriminator = execute_discriminator();
+    let mut offset = 0;
+    while let Some(header) = data.get(offset..offset + 12) {
+        let length = u32::from_le_bytes(header[8..12].try_into().unwrap()) as usize;
+        let value = data
+            .get(offset + 12..offset + 12 + length)
+            .context("Truncated ExtraAccountMetaList entry")?;
+        if header[..8] != execute_discriminator {
+            offset += 12 + length;
+            continue;
+        }
+
+        let count = u32::from_le_bytes(
+            value
+                .get(..4)
+                .context("Invalid ExtraAccountMetaList")?
+                .try_into()
+                .unwrap(),
+        ) as usize;
+        let items = value
+            .get(4..4 + count * EXTRA_ACCOUNT_META_LENGTH)
+            .context("Truncated ExtraAccountMetaList")?;
+        return Ok(items
+            .chunks_exact(EXTRA_ACCOUNT_META_LENGTH)
+            .map(|item| ExtraAccountMeta {
+                discriminator: item[0],
+                address_config: item[1..33].try_into().unwrap(),
+                is_signer: item[33] != 0,
+                is_writable: item[34] != 0,
+            })
+            .collect());
+    }
+    Err(anyhow!("ExtraAccountMetaList has no execute entry"))
+}
+
+/// The token transfer the hook runs on
+#[derive(Debug, Clone, Copy)]
+pub struct HookedTransfer {
+    pub source: Pubkey,
+    pub mint: Pubkey,
+    pub destination: Pubkey,
+    pub authority: Pubkey,
+    /// Only matters to hooks deriving accounts from the amount, an estimate is fine otherwise
+    pub amount: u64,
+}
+
+/// The accounts to pass after the transfer accounts so the token program can invoke the hook
+#[derive(Debug, Clone, PartialEq)]
+pub struct TransferHookAccounts {
+    pub program_id: Pubkey,
+    /// The extra accounts followed by the hook program and the `ExtraAccountMetaList` account
+    pub account_metas: Vec<AccountMeta>,
+}
+
+impl TransferHookAccounts {
+    /// Appends `account_metas` to the remaining accounts as a slice of `accounts_type`
+    pub fn append_to(
+        &self,
+        builder: RemainingAccountsInfoBuilder,
+        accounts_type: AccountsType,
+    ) -> Result<RemainingAccountsInfoBuilder> {
+        builder.slice(accounts_type, self.account_metas.iter().cloned())
+    }
+}
+
+struct Resolver<'a> {
+    program_id: Pubkey,
+    instruction_data: Vec<u8>,
+    account_metas: Vec<AccountMeta>,
+    account_map: &'a AccountMap,
+}
+
+impl Resolver<'_> {
+    fn account_key(&self, index: u8) -> Result<Pubkey> {
+        self.account_metas
+            .get(usize::from(index))
+            .map(|account_meta| account_meta.pubkey)
+            .with_context(|| format!("Transfer hook account index {index} is not resolved yet"))
+    }
+
+    fn account_data(&self, index: u8) -> Result<&[u8]> {
+        let pubkey = self.account_key(index)?;
+        self.account_map
+            .get(&pubkey)
+            .map(|account| account.data.as_slice())
+            .with_context(|| format!("Transfer hook seed account {pubkey} not in the account map"))
+    }
+
+    fn slice<'b>(data: &'b [u8], start: u8, length: usize, what: &str) -> Result<&'b [u8]> {
+        let start = usize::from(start);
+        data.get(start..start + length)
+            .with_context(|| format!("Transfer hook {what} too short"))
+    }
+
+    fn seeds(&self, config: &[u8; 32]) -> Result<Vec<Vec<u8>>> {
+        let mut seeds = vec![];
+        let mut offset = 0;
+        while offset < config.len() {
+            let seed = match config[offset..] {
+                [0, ..] => break,
+                [1, length, ref rest @ ..] => {
+                    offset += 2 + usize::from(length);
+                    Self::slice(rest, 0, length.into(), "literal seed")?.to_vec()
+                }
+                [2, index, length, ..] => {
+                    offset += 3;
+                    Self::slice(
+                        &self.instruction_data,
+                        index,
+                        length.into(),
+                        "instruction data",
+                    )?
+                    .to_vec()
+                }
+                [3, index, ..] => {
+                    offset += 2;
+                    self.account_key(index)?.to_bytes().to_vec()
+                }
+                [4, account_index, data_index, length, ..] => {
+                    offset += 4;
+                    let data = self.account_data(account_index)?;
+                    Self::slice(data, data_index, length.into(), "account data")?.to_vec()
+                }
+                [discriminator, ..] => bail!("Unknown transfer hook seed {discriminator}"),
+                [] => unreachable!(),
+            };
+            seeds.push(seed);
+        }
+        Ok(seeds)
+    }
+
+    fn pubkey_data(&self, config: &[u8; 32]) -> Result<Pubkey> {
+        let bytes = match config {
+            [1, index, ..] => Self::slice(&self.instruction_data, *index, 32, "instruction data")?,
+            [2, account_index, data_index, ..] => Self::slice(
+                self.account_data(*account_index)?,
+                *data_index,
+                32,
+                "account data",
+            )?,
+            [discriminator, ..] => bail!("Unknown transfer hook pubkey data {discriminator}"),
+        };
+        Ok(Pubkey::new_from_array(bytes.try_into().unwrap()))
+    }
+
+    fn resolve(&self, extra_account_meta: &ExtraAccountMeta) -> Result<Pubkey> {
+        let config = &extra_account_meta.address_config;
+        let find_program_address = |program_id: &Pubkey| -> Result<Pubkey> {
+            let seeds = self.seeds(config)?;
+            let seeds = seeds.iter().map(Vec::as_slice).collect::<Vec<_>>();
+            Ok(Pubkey::find_program_address(&seeds, program_id).0)
+        };
+        match extra_account_meta.discriminator {
+            0 => Ok(Pubkey::new_from_array(*config)),
+            1 => find_program_address(&self.program_id),
+            2 => self.pubkey_data(config),
+            discriminator @ 128.. => find_program_address(&self.account_key(discriminator - 128)?),
+            discriminator => bail!("Unknown extra account meta {discriminator}"),
+        }
+    }
+}
+
+/// Resolves the accounts the token program needs to invoke the transfer hook of `mint_account`
+///
+/// Returns None if the mint has no hook, `account_map` provides the data of accounts seeds read from
+pub fn resolve_transfer_hook_accounts(
+    transfer: &HookedTransfer,
+    mint_account: &Account,
+    extra_account_meta_list: &Account,
+    account_map: &AccountMap,
+) -> Result<Option<TransferHookAccounts>> {
+    let Some(program_id) = transfer_hook_program_id(mint_account)? else {
+        return Ok(None);
+    };
+    let extra_account_meta_list_key = extra_account_meta_list_address(&transfer.mint, &program_id);
+    let extra_account_metas = parse_extra_account_metas(&extra_account_meta_list.data)
+        .with_context(|| format!("Invalid ExtraAccountMetaList {extra_account_meta_list_key}"))?;
+
+    let mut resolver = Resolver {
+        program_id,
+        instruction_data: [
+            execute_discriminator().as_slice(),
+            &transfer.amount.to_le_bytes(),
+        ]
+        .concat(),
+        account_metas: vec![
+            AccountMeta::new_readonly(transfer.source, false),
+            AccountMeta::new_readonly(transfer.mint, false),
+            AccountMeta::new_readonly(transfer.destination, false),
+            AccountMeta::new_readonly(transfer.authority, false),
+            AccountMeta::new_readonly(extra_account_meta_list_key, false),
+        ],
+        account_map,
+    };
+    for extra_account_meta in &extra_account_metas {
+        let pubkey = resolver.resolve(extra_account_meta)?;
+        resolver.account_metas.push(AccountMeta {
+            pubkey,
+            is_signer: extra_account_meta.is_signer,
+            is_writable: extra_account_meta.is_writable,
+        });
+    }
+
+    let mut account_metas = resolver.account_metas.split_off(EXECUTE_ACCOUNTS_LEN);
+    account_metas.push(AccountMeta::new_readonly(program_id, false));
+    account_metas.push(AccountMeta::new_readonly(
+        extra_account_meta_list_key,
+        false,
+    ));
+    Ok(Some(TransferHookAccounts {
+        program_id,
+        account_metas,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mint_account(hook_program_id: &Pubkey) -> Account {
+        let mut data = vec![0; BASE_ACCOUNT_LENGTH];
+        data.push(ACCOUNT_TYPE_MINT);
+        data.extend(TRANSFER_HOOK_EXTENSION_TYPE.to_le_bytes());
+        data.extend(64u16.to_le_bytes());
+        data.extend([0; 32]);
+        data.extend(hook_program_id.to_bytes());
+        Account {
+            data,
+            owner: SPL_TOKEN_2022_PROGRAM_ID,
+            ..Account::default()
+        }
+    }
+
+    fn extra_account_meta(discriminator: u8, config: &[u8], is_writable: bool) -> Vec<u8> {
+        let mut address_config = [0; 32];
+        address_config[..config.len()].copy_from_slice(config);
+        [
+            vec![discriminator],
+            address_config.to_vec(),
+            vec![0, u8::from(is_writable)],
+        ]
+        .concat()
+    }
+
+    #[test]
+    fn test_resolve_transfer_hook_accounts() {
+        assert_eq!(
+            execute_discriminator(),
+            [105, 37, 101, 197, 75, 251, 102, 26]
+        );
+
+        let hook_program_id = Pubkey::new_unique();
+        let fixed = Pubkey::new_unique();
+        let transfer = HookedTransfer {
+            source: Pubkey::new_unique(),
+            mint: Pubkey::new_unique(),
+            destination: Pubkey::new_unique(),
+            authority: Pubkey::new_unique(),
+            amount: 42,
+        };
+
+        let items = [
+            extra_account_meta(0, fixed.as_ref(), false),
+            // PDA of the hook program from a literal and the source
+            extra_account_meta(1, &[1, 4, b'h', b'o', b'o', b'k', 3, 0], true),
+            // PDA of the fixed account (index 5) from the amount
+            extra_account_meta(128 + 5, &[2, 8, 8], false),
+        ]
+        .concat();
+        let value = [3u32.to_le_bytes().as_slice(), &items].concat();
+        let extra_account_meta_list = Account {
+            data: [
+                execute_discriminator().as_slice(),
+                &(value.len() as u32).to_le_bytes(),
+                &value,
+            ]
+            .concat(),
+            owner: hook_program_id,
+            ..Account::default()
+        };
+
+        let hook_accounts = resolve_transfer_hook_accounts(
+            &transfer,
+            &mint_account(&hook_program_id),
+            &extra_account_meta_list,
+            &AccountMap::default(),
+        )
+        .unwrap()
+        .unwrap();
+        let pubkeys = hook_accounts
+            .account_metas
+            .iter()
+            .map(|account_meta| account_meta.pubkey)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            pubkeys,
+            vec![
+                fixed,
+                Pubkey::find_program_address(
+                    &[b"hook", transfer.source.as_ref()],
+                    &hook_program_id
+                )
+                .0,
+                Pubkey::find_program_address(&[&42u64.to_le_bytes()], &fixed).0,
+                hook_program_id,
+                extra_account_meta_list_address(&transfer.mint, &hook_program_id),
+            ]
+        );
+        assert!(hook_accounts.account_metas[1].is_writable);
+        let (remaining_accounts_info, account_metas) = hook_accounts
+            .append_to(
+                RemainingAccountsInfoBuilder::new(),
+                AccountsType::TransferHookA,
+            )
+            .unwrap()
+            .build();
+        assert_eq!(remaining_accounts_info.unwrap().slices[0].length, 5);
+        assert_eq!(account_metas, hook_accounts.account_metas);
+
+        let mint_without_hook = Account {
+            data: vec![0; MINT_LENGTH],
+            owner: SPL_TOKEN_2022_PROGRAM_ID,
+            ..Account::default()
+        };
+        assert!(resolve_transfer_hook_accounts(
+            &transfer,
+            &mint_without_hook,
+            &extra_account_meta_list,
+            &AccountMap::default()
+        )
+        .unwrap()
+        .is_none());
+    }
+}
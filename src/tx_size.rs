@@ -0,0 +1,204 @@
+//! Estimation of the size of the v0 transaction of a route, to drop routes that cannot be sent before building them
+use std::collections::HashMap;
+
+use borsh::BorshSerialize;
+use solana_sdk::{
+    address_lookup_table::AddressLookupTableAccount, packet::PACKET_DATA_SIZE, pubkey::Pubkey,
+};
+
+use crate::SwapAndAccountMetas;
+
+const SIGNATURE_BYTES: usize = 64;
+const PUBKEY_BYTES: usize = 32;
+/// The version prefix and the message header
+const MESSAGE_PREFIX_BYTES: usize = 1 + 3;
+const BLOCKHASH_BYTES: usize = 32;
+/// The discriminator, the route plan vec length and the amounts, slippage and platform fee following it
+const ROUTE_IX_DATA_BYTES: usize = 8 + 4 + 8 + 8 + 2 + 1;
+/// The percent, input index and output index of a route plan step
+const ROUTE_PLAN_STEP_BYTES: usize = 3;
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct TxSizeEstimate {
+    pub signatures: usize,
+    pub static_account_keys: usize,
+    /// The writable and readonly accounts loaded from each lookup table used
+    pub lookup_table_accounts: Vec<(Pubkey, usize, usize)>,
+    pub instruction_data_len: usize,
+    /// The serialized size of the transaction, signatures included
+    pub size: usize,
+}
+
+impl TxSizeEstimate {
+    pub fn fits(&self) -> bool {
+        self.size <= PACKET_DATA_SIZE
+    }
+}
+
+fn compact_len(len: usize) -> usize {
+    match len {
+        0..=0x7f => 1,
+        0x80..=0x3fff => 2,
+        _ => 3,
+    }
+}
+
+fn compact_vec_bytes(len: usize, item_bytes: usize) -> usize {
+    compact_len(len) + len * item_bytes
+}
+
+/// Estimates the transaction of a single route instruction of `program_id` whose accounts are those of `legs`
+///
+/// Accounts are looked up in the first table of `alts` holding them, as `v0::Message::try_compile` does
+pub fn estimate(
+    program_id: &Pubkey,
+    legs: &[SwapAndAccountMetas],
+    alts: &[AddressLookupTableAccount],
+) -> TxSizeEstimate {
+    // Signer and writable flags merged per address, the first signer pays the fees
+    let mut accounts: HashMap<Pubkey, (bool, bool)> = HashMap::new();
+    for account_meta in legs.iter().flat_map(|leg| &leg.account_metas) {
+        let (is_signer, is_writable) = accounts.entry(account_meta.pubkey).or_default();
+        *is_signer |= account_meta.is_signer;
+        *is_writable |= account_meta.is_writable;
+    }
+    accounts.remove(program_id);
+    let signatures = accounts
+        .values()
+        .filter(|(is_signer, _)| *is_signer)
+        .count()
+        .max(1);
+
+    let mut static_account_keys = 1 + signatures;
+    let mut lookup_table_accounts = vec![];
+    let mut remaining = accounts
+        .into_iter()
+        .filter(|(_, (is_signer, _))| !is_signer)
+        .collect::<HashMap<_, _>>();
+    for alt in alts {
+        let (mut writable, mut readonly) = (0, 0);
+        for address in &alt.addresses {
+            match remaining.remove(address) {
+                Some((_, true)) => writable += 1,
+                Some((_, false)) => readonly += 1,
+                None => {}
+            }
+        }
+        if writable + readonly > 0 {
+            lookup_table_accounts.push((alt.key, writable, readonly));
+        }
+    }
+    static_account_keys += remaining.len();
+
+    let instruction_data_len = ROUTE_IX_DATA_BYTES
+        + legs
+            .iter()
+            .map(|leg| {
+                BorshSerialize::try_to_vec(&leg.swap)
+                    .expect("Serializing to a Vec cannot fail")
+                    .len()
+                    + ROUTE_PLAN_STEP_BYTES
+            })
+            .sum::<usize>();
+    let instruction_accounts_len = legs.iter().map(|leg| leg.account_metas.len()).sum();
+    let instruction_bytes = 1
+        + compact_vec_bytes(instruction_accounts_len, 1)
+        + compact_vec_bytes(instruction_data_len, 1);
+    let lookup_bytes = compact_len(lookup_table_accounts.len())
+        + lookup_table_accounts
+            .iter()
+            .map(|(_, writable, readonly)| {
+                PUBKEY_BYTES + compact_vec_bytes(*writable, 1) + compact_vec_bytes(*readonly, 1)
+            })
+            .sum::<usize>();
+
+    let size = compact_vec_bytes(signatures, SIGNATURE_BYTES)
+        + MESSAGE_PREFIX_BYTES
+        + compact_vec_bytes(static_account_keys, PUBKEY_BYTES)
+        + BLOCKHASH_BYTES
+        + compact_len(1)
+        + instruction_bytes
+        + lookup_bytes;
+
+    TxSizeEstimate {
+        signatures,
+        static_account_keys,
+        lookup_table_accounts,
+        instruction_data_len,
+        size,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Side, Swap};
+    use smallvec::smallvec;
+    use solana_sdk::{
+        hash::Hash,
+        instruction::{AccountMeta, Instruction},
+        message::{v0, VersionedMessage},
+    };
+
+    #[test]
+    fn test_estimate_matches_compiled_message() {
+        let program_id = Pubkey::new_unique();
+        let user = Pubkey::new_unique();
+        let (user_source, user_intermediate, user_destination) = (
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+        );
+        let pool_accounts = (0..20).map(|_| Pubkey::new_unique()).collect::<Vec<_>>();
+        let legs = [
+            SwapAndAccountMetas::new(
+                Swap::TokenSwap,
+                smallvec![
+                    AccountMeta::new_readonly(pool_accounts[0], false),
+                    AccountMeta::new_readonly(user, true),
+                    AccountMeta::new(user_source, false),
+                    AccountMeta::new(pool_accounts[1], false),
+                    AccountMeta::new(pool_accounts[2], false),
+                    AccountMeta::new(user_intermediate, false),
+                    AccountMeta::new_readonly(program_id, false),
+                ],
+            ),
+            SwapAndAccountMetas::new(
+                Swap::OpenBookV2 { side: Side::Ask },
+                pool_accounts[3..]
+                    .iter()
+                    .map(|pubkey| AccountMeta::new(*pubkey, false))
+                    .chain([
+                        AccountMeta::new_readonly(user, true),
+                        AccountMeta::new(user_intermediate, false),
+                        AccountMeta::new(user_destination, false),
+                    ])
+                    .collect(),
+            ),
+        ];
+        let alts = [AddressLookupTableAccount {
+            key: Pubkey::new_unique(),
+            addresses: pool_accounts[..15].to_vec(),
+        }];
+
+        let estimate = estimate(&program_id, &legs, &alts);
+        assert_eq!(estimate.signatures, 1);
+        assert_eq!(estimate.lookup_table_accounts, vec![(alts[0].key, 14, 1)]);
+        assert!(estimate.fits());
+
+        let instruction = Instruction {
+            program_id,
+            accounts: legs
+                .iter()
+                .flat_map(|leg| leg.account_metas.clone())
+                .collect(),
+            data: vec![0; estimate.instruction_data_len],
+        };
+        let message =
+            v0::Message::try_compile(&user, &[instruction], &alts, Hash::default()).unwrap();
+        assert_eq!(
+            estimate.size,
+            compact_vec_bytes(1, SIGNATURE_BYTES) + VersionedMessage::V0(message).serialize().len()
+        );
+    }
+}
@@ -0,0 +1,106 @@
+use serde::{de, Deserialize, Deserializer, Serialize};
+use serde_json::Value;
+use solana_sdk::pubkey::Pubkey;
+
+use crate::field_as_string;
+
+/// The Serum or Openbook market accounts an Amm settles through, e.g. Raydium AMM v4
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct SerumMarketParams {
+    #[serde(with = "field_as_string")]
+    pub serum_bids: Pubkey,
+    #[serde(with = "field_as_string")]
+    pub serum_asks: Pubkey,
+    #[serde(with = "field_as_string")]
+    pub serum_event_queue: Pubkey,
+    #[serde(with = "field_as_string")]
+    pub serum_coin_vault_account: Pubkey,
+    #[serde(with = "field_as_string")]
+    pub serum_pc_vault_account: Pubkey,
+    #[serde(with = "field_as_string")]
+    pub serum_vault_signer: Pubkey,
+}
+
+/// An oracle to price the pool with instead of the one stored in its state
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct OracleOverrideParams {
+    #[serde(with = "field_as_string")]
+    pub oracle: Pubkey,
+}
+
+/// The common shapes of `KeyedAccount` and `Market` params, validated in one place
+///
+/// Serialized untagged so existing params keep their JSON shape. Params whose fields all belong to a common shape
+/// have to be valid for it, params with any other field are `Custom`
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(untagged)]
+pub enum TypedParams {
+    SerumMarket(SerumMarketParams),
+    OracleOverride(OracleOverrideParams),
+    Custom(Value),
+}
+
+const SERUM_MARKET_FIELDS: &[&str] = &[
+    "serumBids",
+    "serumAsks",
+    "serumEventQueue",
+    "serumCoinVaultAccount",
+    "serumPcVaultAccount",
+    "serumVaultSigner",
+];
+const ORACLE_OVERRIDE_FIELDS: &[&str] = &["oracle"];
+
+impl<'de> Deserialize<'de> for TypedParams {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = Value::deserialize(deserializer)?;
+        let has_only_fields = |fields: &[&str]| {
+            value.as_object().is_some_and(|object| {
+                !object.is_empty() && object.keys().all(|key| fields.contains(&key.as_str()))
+            })
+        };
+        if has_only_fields(SERUM_MARKET_FIELDS) {
+            serde_json::from_value(value)
+                .map(TypedParams::SerumMarket)
+                .map_err(de::Error::custom)
+        } else if has_only_fields(ORACLE_OVERRIDE_FIELDS) {
+            serde_json::from_value(value)
+                .map(TypedParams::OracleOverride)
+                .map_err(de::Error::custom)
+        } else {
+            Ok(TypedParams::Custom(value))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_typed_params_shapes() {
+        let oracle = Pubkey::new_unique();
+        let typed_params: TypedParams =
+            serde_json::from_value(json!({ "oracle": oracle.to_string() })).unwrap();
+        assert_eq!(
+            typed_params,
+            TypedParams::OracleOverride(OracleOverrideParams { oracle })
+        );
+
+        // Unknown fields keep the params custom rather than dropping them
+        let params = json!({ "oracle": oracle.to_string(), "feeBps": 30 });
+        let typed_params: TypedParams = serde_json::from_value(params.clone()).unwrap();
+        assert_eq!(typed_params, TypedParams::Custom(params));
+
+        // Malformed common shapes are errors rather than custom params
+        assert!(
+            serde_json::from_value::<TypedParams>(json!({ "oracle": "not a pubkey" })).is_err()
+        );
+        assert!(serde_json::from_value::<TypedParams>(
+            json!({ "serumBids": oracle.to_string(), "serumAsks": oracle.to_string() })
+        )
+        .is_err());
+    }
+}
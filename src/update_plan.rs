@@ -0,0 +1,51 @@
+use solana_sdk::pubkey::Pubkey;
+
+use crate::AccountMap;
+
+/// The accounts to fetch for the next update of an Amm, see `Amm::plan_update`
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct UpdatePlan {
+    /// Passed back to `Amm::apply_update`, identifies the state the plan was made from
+    pub plan_id: u64,
+    pub accounts: Vec<Pubkey>,
+    /// The plan should be dropped if the accounts cannot be fetched by this slot
+    pub deadline_slot: Option<u64>,
+}
+
+impl UpdatePlan {
+    /// The accounts of the plan `fetched` lacks, to retry instead of the whole plan
+    pub fn missing_accounts(&self, fetched: &AccountMap) -> Vec<Pubkey> {
+        self.accounts
+            .iter()
+            .filter(|address| !fetched.contains_key(address))
+            .copied()
+            .collect()
+    }
+
+    pub fn is_expired(&self, current_slot: u64) -> bool {
+        self.deadline_slot
+            .is_some_and(|deadline_slot| current_slot > deadline_slot)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{reference::MockAmm, Amm};
+    use solana_sdk::account::Account;
+
+    #[test]
+    fn test_plan_and_apply_update() {
+        let accounts = vec![Pubkey::new_unique(), Pubkey::new_unique()];
+        let mut amm =
+            MockAmm::new(Pubkey::new_unique(), vec![]).with_accounts_to_update(accounts.clone());
+        let plan = amm.plan_update();
+        assert_eq!(plan.accounts, accounts);
+        assert!(!plan.is_expired(u64::MAX));
+
+        let fetched = AccountMap::from_iter([(accounts[0], Account::default())]);
+        assert_eq!(plan.missing_accounts(&fetched), vec![accounts[1]]);
+        assert!(amm.apply_update(&fetched, plan.plan_id).is_ok());
+        assert!(amm.apply_update(&fetched, plan.plan_id + 1).is_err());
+    }
+}
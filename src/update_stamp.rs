@@ -0,0 +1,63 @@
+use crate::SlotAccount;
+
+/// Records the slot of the last `update`, for adapters to back `Amm::last_update_slot`
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct UpdateStamp {
+    slot: Option<u64>,
+}
+
+impl UpdateStamp {
+    /// Stamps with the context slot of the fetch the update applied, not the clock slot which can run ahead of it
+    pub fn stamp(&mut self, context_slot: u64) {
+        self.slot = Some(context_slot);
+    }
+
+    /// Stamps with the oldest slot the shared accounts the update read were fetched at, left untouched if there are none
+    pub fn stamp_with_slot_accounts<'a>(
+        &mut self,
+        slot_accounts: impl IntoIterator<Item = &'a SlotAccount>,
+    ) {
+        if let Some(slot) = slot_accounts
+            .into_iter()
+            .map(|slot_account| slot_account.slot)
+            .min()
+        {
+            self.stamp(slot);
+        }
+    }
+
+    pub fn slot(&self) -> Option<u64> {
+        self.slot
+    }
+
+    /// The slots elapsed from the last update to `current_slot`, None if never updated
+    pub fn slots_since(&self, current_slot: u64) -> Option<u64> {
+        self.slot.map(|slot| current_slot.saturating_sub(slot))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::account::Account;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_update_stamp() {
+        let mut update_stamp = UpdateStamp::default();
+        assert_eq!(update_stamp.slots_since(100), None);
+
+        let slot_account = |slot| SlotAccount {
+            slot,
+            account: Arc::new(Account::default()),
+        };
+        update_stamp.stamp_with_slot_accounts(&[slot_account(90), slot_account(85)]);
+        assert_eq!(update_stamp.slot(), Some(85));
+        assert_eq!(update_stamp.slots_since(100), Some(15));
+
+        update_stamp.stamp_with_slot_accounts(&[]);
+        assert_eq!(update_stamp.slot(), Some(85));
+        update_stamp.stamp(95);
+        assert_eq!(update_stamp.slots_since(90), Some(0));
+    }
+}
@@ -0,0 +1,117 @@
+use std::{fmt, sync::Arc};
+
+use anyhow::{anyhow, Result};
+use solana_sdk::{instruction::Instruction, pubkey::Pubkey};
+
+use crate::AmmUserSetup;
+
+/// Builds a setup instruction for the user, for steps depending on what is only known when the transaction is built
+pub type UserSetupInstructionBuilder = Arc<dyn Fn(&Pubkey) -> Result<Instruction> + Send + Sync>;
+
+/// A step to run once per user before they can swap through an Amm
+#[derive(Clone)]
+pub enum UserSetupStep {
+    /// A setup the router knows how to perform, e.g. creating Serum open orders
+    Known(AmmUserSetup),
+    /// An explicit instruction, e.g. creating a blacklist PDA or delegating an account
+    Instruction(Instruction),
+    /// An instruction built for the user at transaction build time
+    Builder {
+        description: String,
+        build: UserSetupInstructionBuilder,
+    },
+}
+
+impl fmt::Debug for UserSetupStep {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            UserSetupStep::Known(AmmUserSetup::SerumDexOpenOrdersSetup { market, program_id }) => f
+                .debug_struct("SerumDexOpenOrdersSetup")
+                .field("market", market)
+                .field("program_id", program_id)
+                .finish(),
+            UserSetupStep::Instruction(instruction) => {
+                f.debug_tuple("Instruction").field(instruction).finish()
+            }
+            UserSetupStep::Builder { description, .. } => f
+                .debug_struct("Builder")
+                .field("description", description)
+                .finish_non_exhaustive(),
+        }
+    }
+}
+
+/// The setup a user needs before swapping through an Amm, see `Amm::get_user_setup_v2`
+#[derive(Debug, Default, Clone)]
+pub struct UserSetupPlan {
+    /// In execution order
+    pub steps: Vec<UserSetupStep>,
+    /// Accounts derived from the user the swap references, e.g. a per wallet PDA
+    pub user_accounts: Vec<Pubkey>,
+}
+
+impl UserSetupPlan {
+    pub fn is_empty(&self) -> bool {
+        self.steps.is_empty() && self.user_accounts.is_empty()
+    }
+
+    /// The instructions of the explicit and builder steps, failing on steps only the router can perform
+    pub fn instructions(&self, user: &Pubkey) -> Result<Vec<Instruction>> {
+        self.steps
+            .iter()
+            .map(|step| match step {
+                UserSetupStep::Known(_) => {
+                    Err(anyhow!("{step:?} has to be performed by the router"))
+                }
+                UserSetupStep::Instruction(instruction) => Ok(instruction.clone()),
+                UserSetupStep::Builder { build, .. } => build(user),
+            })
+            .collect()
+    }
+}
+
+impl From<AmmUserSetup> for UserSetupPlan {
+    fn from(amm_user_setup: AmmUserSetup) -> Self {
+        UserSetupPlan {
+            steps: vec![UserSetupStep::Known(amm_user_setup)],
+            user_accounts: vec![],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use solana_sdk::instruction::AccountMeta;
+
+    #[test]
+    fn test_user_setup_plan_instructions() {
+        let program_id = Pubkey::new_unique();
+        let user = Pubkey::new_unique();
+        let plan = UserSetupPlan {
+            steps: vec![
+                UserSetupStep::Instruction(Instruction::new_with_bytes(program_id, &[0], vec![])),
+                UserSetupStep::Builder {
+                    description: "Create blacklist PDA".to_string(),
+                    build: Arc::new(move |user| {
+                        Ok(Instruction::new_with_bytes(
+                            program_id,
+                            &[1],
+                            vec![AccountMeta::new(*user, true)],
+                        ))
+                    }),
+                },
+            ],
+            user_accounts: vec![],
+        };
+        let instructions = plan.instructions(&user).unwrap();
+        assert_eq!(instructions.len(), 2);
+        assert_eq!(instructions[1].accounts[0].pubkey, user);
+
+        let plan = UserSetupPlan::from(AmmUserSetup::SerumDexOpenOrdersSetup {
+            market: Pubkey::new_unique(),
+            program_id,
+        });
+        assert!(plan.instructions(&user).is_err());
+    }
+}